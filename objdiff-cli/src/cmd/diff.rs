@@ -18,6 +18,7 @@ use objdiff_core::{
     diff,
     diff::{
         display::{display_diff, DiffText, HighlightKind},
+        text::write_unified_diff,
         DiffObjsResult, ObjDiff, ObjInsDiffKind, ObjSymbolDiff,
     },
     obj,
@@ -49,15 +50,21 @@ pub struct Args {
     #[argp(switch, short = 'x')]
     /// Relax relocation diffs
     relax_reloc_diffs: bool,
+    #[argp(switch)]
+    /// Print a unified text diff instead of opening the interactive view
+    text: bool,
+    #[argp(option, short = 'U')]
+    /// Matching instructions to keep around each change in text output (default: all)
+    context: Option<usize>,
     #[argp(positional)]
     /// Function symbol to diff
     symbol: String,
 }
 
 pub fn run(args: Args) -> Result<()> {
-    let (target_path, base_path, project_config) =
+    let (target_path, base_path, project_config, symbol_mappings) =
         match (&args.target, &args.base, &args.project, &args.unit) {
-            (Some(t), Some(b), None, None) => (Some(t.clone()), Some(b.clone()), None),
+            (Some(t), Some(b), None, None) => (Some(t.clone()), Some(b.clone()), None, vec![]),
             (None, None, p, u) => {
                 let project = match p {
                     Some(project) => project.clone(),
@@ -140,7 +147,13 @@ pub fn run(args: Args) -> Result<()> {
                 };
                 let target_path = object.target_path.clone();
                 let base_path = object.base_path.clone();
-                (target_path, base_path, Some(project_config))
+                let symbol_mappings = object.symbol_mappings.clone().unwrap_or_default();
+                if let Some(paths) = &mut project_config.debug_search_paths {
+                    for path in paths {
+                        *path = project.join(&*path);
+                    }
+                }
+                (target_path, base_path, Some(project_config), symbol_mappings)
             }
             _ => bail!("Either target and base or project and unit must be specified"),
         };
@@ -160,6 +173,7 @@ pub fn run(args: Args) -> Result<()> {
         target_path,
         base_path,
         project_config,
+        symbol_mappings,
         left_obj: None,
         right_obj: None,
         prev_obj: None,
@@ -174,6 +188,21 @@ pub fn run(args: Args) -> Result<()> {
     });
     state.reload()?;
 
+    if args.text {
+        let mut out = String::new();
+        write_unified_diff(
+            &mut out,
+            get_symbol(state.left_obj.as_ref(), state.left_sym)
+                .zip(get_symbol_diff(state.diff_result.left.as_ref(), state.left_sym)),
+            get_symbol(state.right_obj.as_ref(), state.right_sym)
+                .zip(get_symbol_diff(state.diff_result.right.as_ref(), state.right_sym)),
+            args.context,
+            None,
+        )?;
+        print!("{out}");
+        return Ok(());
+    }
+
     crossterm_panic_handler();
     enable_raw_mode()?;
     crossterm::queue!(
@@ -258,6 +287,7 @@ struct FunctionDiffUi {
     target_path: Option<PathBuf>,
     base_path: Option<PathBuf>,
     project_config: Option<ProjectConfig>,
+    symbol_mappings: Vec<(String, String)>,
     left_obj: Option<ObjInfo>,
     right_obj: Option<ObjInfo>,
     prev_obj: Option<ObjInfo>,
@@ -718,6 +748,7 @@ impl FunctionDiffUi {
                     ObjInsDiffKind::Replace => Color::Cyan,
                     ObjInsDiffKind::Delete => Color::Red,
                     ObjInsDiffKind::Insert => Color::Green,
+                    ObjInsDiffKind::Moved => Color::Yellow,
                 };
                 let mut pad_to = 0;
                 match text {
@@ -756,10 +787,13 @@ impl FunctionDiffUi {
                             base_color = COLOR_ROTATION[diff.idx % COLOR_ROTATION.len()]
                         }
                     }
-                    DiffText::Symbol(sym) => {
+                    DiffText::Symbol(sym, diff) => {
                         let name = sym.demangled_name.as_ref().unwrap_or(&sym.name);
                         label_text = name.clone();
-                        base_color = Color::White;
+                        base_color = match diff {
+                            Some(diff) => COLOR_ROTATION[diff.idx % COLOR_ROTATION.len()],
+                            None => Color::White,
+                        };
                     }
                     DiffText::Spacing(n) => {
                         line.spans.push(Span::raw(" ".repeat(n)));
@@ -814,18 +848,52 @@ impl FunctionDiffUi {
         let prev = self.right_obj.take();
         let config = diff::DiffObjConfig {
             relax_reloc_diffs: self.relax_reloc_diffs,
-            space_between_args: true,                // TODO
-            combine_data_sections: false,            // TODO
-            x86_formatter: Default::default(),       // TODO
-            mips_abi: Default::default(),            // TODO
-            mips_instr_category: Default::default(), // TODO
-            arm_arch_version: Default::default(),    // TODO
-            arm_unified_syntax: true,                // TODO
-            arm_av_registers: false,                 // TODO
-            arm_r9_usage: Default::default(),        // TODO
-            arm_sl_usage: false,                     // TODO
-            arm_fp_usage: false,                     // TODO
-            arm_ip_usage: false,                     // TODO
+            space_between_args: true,                  // TODO
+            combine_data_sections: false,              // TODO
+            diff_alignment: Default::default(),        // TODO
+            reloc_diff_score: Default::default(),      // TODO
+            code_diff_granularity: Default::default(), // TODO
+            ignored_instructions: Default::default(),  // TODO
+            ignore_padding_nops: false,                // TODO
+            ignore_address_args: false,                // TODO
+            detect_moved_blocks: false,                // TODO
+            raw_reloc_diffs: false,                    // TODO
+            demangler: Default::default(),             // TODO
+            match_visibility: Default::default(),      // TODO
+            lazy_code_diff: false,                     // TODO
+            ppc_simplified_mnemonics: true,            // TODO
+            x86_formatter: Default::default(),         // TODO
+            mips_abi: Default::default(),              // TODO
+            mips_instr_category: Default::default(),   // TODO
+            mips_pseudo_instructions: true,            // TODO
+            arm_arch_version: Default::default(),      // TODO
+            arm_unified_syntax: true,                  // TODO
+            arm_av_registers: false,                   // TODO
+            arm_r9_usage: Default::default(),          // TODO
+            arm_sl_usage: false,                       // TODO
+            arm_fp_usage: false,                       // TODO
+            arm_ip_usage: false,                       // TODO
+            custom_relocations: self
+                .project_config
+                .as_ref()
+                .and_then(|c| c.custom_relocations.clone())
+                .unwrap_or_default(),
+            symbol_name_rules: self
+                .project_config
+                .as_ref()
+                .and_then(|c| c.symbol_name_rules.clone())
+                .unwrap_or_default(),
+            symbol_mappings: self.symbol_mappings.clone(),
+            instruction_rules: self
+                .project_config
+                .as_ref()
+                .and_then(|c| c.instruction_rules.clone())
+                .unwrap_or_default(),
+            debug_search_paths: self
+                .project_config
+                .as_ref()
+                .and_then(|c| c.debug_search_paths.clone())
+                .unwrap_or_default(),
         };
         let target = self
             .target_path
@@ -844,7 +912,11 @@ impl FunctionDiffUi {
         let result = diff::diff_objs(&config, target.as_ref(), base.as_ref(), prev.as_ref())?;
 
         let left_sym = target.as_ref().and_then(|o| find_function(o, &self.symbol_name));
-        let right_sym = base.as_ref().and_then(|o| find_function(o, &self.symbol_name));
+        // Mapped symbols are named differently in the base
+        let right_sym = left_sym
+            .zip(result.left.as_ref())
+            .and_then(|(l, diff)| diff.symbol_diff(l).diff_symbol)
+            .or_else(|| base.as_ref().and_then(|o| find_function(o, &self.symbol_name)));
         let prev_sym = prev.as_ref().and_then(|o| find_function(o, &self.symbol_name));
         self.num_rows = match (
             get_symbol_diff(result.left.as_ref(), left_sym),
@@ -0,0 +1,94 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Result};
+use argp::FromArgs;
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// List the objects of a project.
+#[argp(subcommand, name = "list-objects")]
+pub struct Args {
+    #[argp(option, short = 'p')]
+    /// Project directory
+    project: Option<PathBuf>,
+    #[argp(option, short = 'f', default = "Format::Text", from_str_fn(parse_format))]
+    /// Output format: text or json (default: text)
+    format: Format,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+enum Format {
+    Text,
+    Json,
+}
+
+fn parse_format(s: &str) -> Result<Format, String> {
+    match s {
+        "text" => Ok(Format::Text),
+        "json" => Ok(Format::Json),
+        _ => Err(format!("Invalid format '{s}', expected text or json")),
+    }
+}
+
+#[derive(serde::Serialize)]
+struct ObjectEntry {
+    name: String,
+    target_path: Option<PathBuf>,
+    base_path: Option<PathBuf>,
+    category: Option<String>,
+    complete: Option<bool>,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let project_dir = args.project.as_deref().unwrap_or_else(|| Path::new("."));
+    let Some((project, info)) = objdiff_core::config::try_project_config(project_dir) else {
+        bail!("No project configuration found");
+    };
+    let project = project
+        .with_context(|| format!("Failed to load project config {}", info.path.display()))?;
+    let entries = project
+        .objects
+        .into_iter()
+        .map(|mut object| {
+            object.resolve_paths(
+                project_dir,
+                project.target_dir.as_deref(),
+                project.base_dir.as_deref(),
+            );
+            ObjectEntry {
+                name: object.name().to_string(),
+                target_path: object.target_path,
+                base_path: object.base_path,
+                category: object.category,
+                complete: object.complete,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut out = std::io::stdout().lock();
+    match args.format {
+        Format::Json => {
+            serde_json::to_writer_pretty(&mut out, &entries)?;
+            writeln!(out)?;
+        }
+        Format::Text => {
+            let path_str = |p: &Option<PathBuf>| {
+                p.as_ref().map(|p| p.display().to_string()).unwrap_or_else(|| "-".to_string())
+            };
+            for entry in &entries {
+                writeln!(
+                    out,
+                    "{}\t{}\t{}\t{}\t{}",
+                    entry.name,
+                    path_str(&entry.target_path),
+                    path_str(&entry.base_path),
+                    entry.category.as_deref().unwrap_or("-"),
+                    if entry.complete == Some(true) { "complete" } else { "incomplete" }
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -1,2 +1,4 @@
 pub mod diff;
+pub mod list_objects;
 pub mod report;
+pub mod tags;
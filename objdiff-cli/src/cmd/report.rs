@@ -1,5 +1,7 @@
 use std::{
-    collections::HashSet,
+    borrow::Cow,
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt::Write as _,
     fs::File,
     io::{BufReader, BufWriter, Write},
     path::{Path, PathBuf},
@@ -9,7 +11,8 @@ use std::{
 use anyhow::{bail, Context, Result};
 use argp::FromArgs;
 use objdiff_core::{
-    config::ProjectObject,
+    build::{apply_build_status, default_parallel_builds, run_make, BuildConfig},
+    config::{ProjectConfig, ProjectObject},
     diff, obj,
     obj::{ObjSectionKind, ObjSymbolFlags},
 };
@@ -29,6 +32,11 @@ pub struct Args {
 pub enum SubCommand {
     Generate(GenerateArgs),
     Changes(ChangesArgs),
+    Comment(CommentArgs),
+    Check(CheckArgs),
+    Badges(BadgesArgs),
+    Aggregate(AggregateArgs),
+    Status(StatusArgs),
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
@@ -44,6 +52,13 @@ pub struct GenerateArgs {
     #[argp(switch, short = 'd')]
     /// Deduplicate global and weak symbols (runs single-threaded)
     deduplicate: bool,
+    #[argp(option, short = 'v', default = "Default::default()", from_str_fn(parse_visibility))]
+    /// Symbols counted towards match percentages: all, external or global (default: all)
+    visibility: diff::SymbolVisibilityFilter,
+}
+
+fn parse_visibility(s: &str) -> Result<diff::SymbolVisibilityFilter, String> {
+    s.parse().map_err(|_| format!("Invalid visibility '{s}', expected all, external or global"))
 }
 
 #[derive(FromArgs, PartialEq, Debug)]
@@ -61,6 +76,141 @@ pub struct ChangesArgs {
     output: Option<PathBuf>,
 }
 
+#[derive(FromArgs, PartialEq, Debug)]
+/// Summarize changes from a previous report as a markdown pull request comment.
+#[argp(subcommand, name = "comment")]
+pub struct CommentArgs {
+    #[argp(positional)]
+    /// Previous (baseline) report JSON file
+    previous: PathBuf,
+    #[argp(positional)]
+    /// Current report JSON file
+    current: PathBuf,
+    #[argp(option, short = 'o')]
+    /// Output markdown file
+    output: Option<PathBuf>,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Fail if any unit's match percentage dropped from a previous report.
+#[argp(subcommand, name = "check")]
+pub struct CheckArgs {
+    #[argp(positional)]
+    /// Previous (baseline) report JSON file
+    previous: PathBuf,
+    #[argp(positional)]
+    /// Current report JSON file
+    current: PathBuf,
+    #[argp(option, short = 't', default = "0.001")]
+    /// Ignore drops of at most this many percentage points (default: 0.001, for floating point
+    /// jitter)
+    tolerance: f32,
+    #[argp(option, short = 'b', default = "0")]
+    /// Ignore drops of at most this many bytes of matched code (default: 0)
+    byte_tolerance: u64,
+    #[argp(option, short = 'i', default = "0")]
+    /// Ignore drops of at most this many instructions of matched code, counted as 4 bytes each
+    /// (default: 0)
+    instruction_tolerance: u64,
+    #[argp(option, short = 'u', from_str_fn(parse_unit_tolerance))]
+    /// Per-unit tolerance as <unit>=<value>[,<value>...], where each value is a percentage
+    /// (`0.5` or `0.5%`), bytes (`16b`) or instructions (`2i`). May be repeated.
+    unit_tolerance: Vec<UnitTolerance>,
+}
+
+/// Overrides parts of the [`Tolerance`] for one unit.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct UnitTolerance {
+    name: String,
+    percent: Option<f32>,
+    bytes: Option<u64>,
+    instructions: Option<u64>,
+}
+
+impl UnitTolerance {
+    fn apply(&self, tolerance: Tolerance) -> Tolerance {
+        Tolerance {
+            percent: self.percent.unwrap_or(tolerance.percent),
+            bytes: self.bytes.unwrap_or(tolerance.bytes),
+            instructions: self.instructions.unwrap_or(tolerance.instructions),
+        }
+    }
+}
+
+fn parse_unit_tolerance(s: &str) -> Result<UnitTolerance, String> {
+    let (name, values) =
+        s.rsplit_once('=').ok_or_else(|| format!("Invalid unit tolerance '{s}'"))?;
+    let mut result = UnitTolerance { name: name.to_string(), ..Default::default() };
+    for value in values.split(',') {
+        let invalid = || format!("Invalid tolerance '{value}' for '{name}'");
+        if let Some(bytes) = value.strip_suffix('b') {
+            result.bytes = Some(bytes.parse().map_err(|_| invalid())?);
+        } else if let Some(instructions) = value.strip_suffix('i') {
+            result.instructions = Some(instructions.parse().map_err(|_| invalid())?);
+        } else {
+            let percent = value.strip_suffix('%').unwrap_or(value);
+            result.percent = Some(percent.parse().map_err(|_| invalid())?);
+        }
+    }
+    Ok(result)
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Write an SVG progress badge per unit category, plus `_total.svg` for the whole report.
+#[argp(subcommand, name = "badges")]
+pub struct BadgesArgs {
+    #[argp(positional)]
+    /// Report JSON file
+    report: PathBuf,
+    #[argp(option, short = 'o')]
+    /// Output directory
+    output: PathBuf,
+    #[argp(option, short = 'd', default = "1")]
+    /// For units without a project category, the number of leading unit path components that
+    /// name their category (default: 1)
+    depth: usize,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Generate reports for several projects and combine them into one JSON file.
+#[argp(subcommand, name = "aggregate")]
+pub struct AggregateArgs {
+    #[argp(positional)]
+    /// Project directories or config files
+    projects: Vec<PathBuf>,
+    #[argp(option, short = 'o')]
+    /// Output JSON file
+    output: Option<PathBuf>,
+    #[argp(switch, short = 'd')]
+    /// Deduplicate global and weak symbols (runs single-threaded)
+    deduplicate: bool,
+    #[argp(option, short = 'v', default = "Default::default()", from_str_fn(parse_visibility))]
+    /// Symbols counted towards match percentages: all, external or global (default: all)
+    visibility: diff::SymbolVisibilityFilter,
+}
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Build a project and print each unit's match percentages as a table. Fails if any complete
+/// unit isn't fully matched.
+#[argp(subcommand, name = "status")]
+pub struct StatusArgs {
+    #[argp(option, short = 'p')]
+    /// Project directory
+    project: Option<PathBuf>,
+    #[argp(option)]
+    /// Only report the unit with this name
+    object: Option<String>,
+    #[argp(switch)]
+    /// Diff the existing objects without building them first
+    no_build: bool,
+    #[argp(option)]
+    /// Build inside this WSL distro (Windows only)
+    wsl_distro: Option<String>,
+    #[argp(option, short = 'v', default = "Default::default()", from_str_fn(parse_visibility))]
+    /// Symbols counted towards match percentages: all, external or global (default: all)
+    visibility: diff::SymbolVisibilityFilter,
+}
+
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 struct Report {
     fuzzy_match_percent: f32,
@@ -76,6 +226,63 @@ struct Report {
     units: Vec<ReportUnit>,
 }
 
+impl Report {
+    /// Sums the units' totals, then computes the percentages.
+    fn sum_units(&mut self) {
+        for unit in &self.units {
+            self.fuzzy_match_percent += unit.fuzzy_match_percent * unit.total_code as f32;
+            self.total_code += unit.total_code;
+            self.matched_code += unit.matched_code;
+            self.total_data += unit.total_data;
+            self.matched_data += unit.matched_data;
+            self.total_functions += unit.total_functions;
+            self.matched_functions += unit.matched_functions;
+        }
+        self.compute_percentages();
+    }
+
+    /// Computes the percentages from the totals. `fuzzy_match_percent` must hold the sum of
+    /// fuzzy match percentages weighted by code size.
+    fn compute_percentages(&mut self) {
+        if self.total_code == 0 {
+            self.fuzzy_match_percent = 100.0;
+        } else {
+            self.fuzzy_match_percent /= self.total_code as f32;
+        }
+
+        self.matched_code_percent = if self.total_code == 0 {
+            100.0
+        } else {
+            self.matched_code as f32 / self.total_code as f32 * 100.0
+        };
+        self.matched_data_percent = if self.total_data == 0 {
+            100.0
+        } else {
+            self.matched_data as f32 / self.total_data as f32 * 100.0
+        };
+        self.matched_functions_percent = if self.total_functions == 0 {
+            100.0
+        } else {
+            self.matched_functions as f32 / self.total_functions as f32 * 100.0
+        };
+    }
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+struct AggregateReport {
+    /// Grand totals across all projects that loaded
+    #[serde(flatten)]
+    total: Report,
+    projects: BTreeMap<String, AggregateProject>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum AggregateProject {
+    Report(Report),
+    Error { error: String },
+}
+
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 struct ReportUnit {
     name: String,
@@ -92,7 +299,11 @@ struct ReportUnit {
     module_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     module_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    category: Option<String>,
+    #[serde(default)]
     sections: Vec<ReportItem>,
+    #[serde(default)]
     functions: Vec<ReportItem>,
 }
 
@@ -116,11 +327,40 @@ pub fn run(args: Args) -> Result<()> {
     match args.command {
         SubCommand::Generate(args) => generate(args),
         SubCommand::Changes(args) => changes(args),
+        SubCommand::Comment(args) => comment(args),
+        SubCommand::Check(args) => check(args),
+        SubCommand::Badges(args) => badges(args),
+        SubCommand::Aggregate(args) => aggregate(args),
+        SubCommand::Status(args) => status(args),
     }
 }
 
 fn generate(args: GenerateArgs) -> Result<()> {
     let project_dir = args.project.as_deref().unwrap_or_else(|| Path::new("."));
+    let report = generate_report(project_dir, args.deduplicate, args.visibility)?;
+    write_json(args.output.as_deref(), &report)
+}
+
+fn write_json<T: serde::Serialize>(output: Option<&Path>, value: &T) -> Result<()> {
+    if let Some(output) = output {
+        info!("Writing to {}", output.display());
+        let mut output = BufWriter::new(
+            File::create(output)
+                .with_context(|| format!("Failed to create file {}", output.display()))?,
+        );
+        serde_json::to_writer_pretty(&mut output, value)?;
+        output.flush()?;
+    } else {
+        serde_json::to_writer_pretty(std::io::stdout(), value)?;
+    }
+    Ok(())
+}
+
+fn generate_report(
+    project_dir: &Path,
+    deduplicate: bool,
+    visibility: diff::SymbolVisibilityFilter,
+) -> Result<Report> {
     info!("Loading project {}", project_dir.display());
 
     let config = objdiff_core::config::try_project_config(project_dir);
@@ -130,13 +370,14 @@ fn generate(args: GenerateArgs) -> Result<()> {
     info!(
         "Generating report for {} units (using {} threads)",
         project.objects.len(),
-        if args.deduplicate { 1 } else { rayon::current_num_threads() }
+        if deduplicate { 1 } else { rayon::current_num_threads() }
     );
 
+    let config = report_diff_config(&project, project_dir, visibility);
     let start = Instant::now();
     let mut report = Report::default();
     let mut existing_functions: HashSet<String> = HashSet::new();
-    if args.deduplicate {
+    if deduplicate {
         // If deduplicating, we need to run single-threaded
         for object in &mut project.objects {
             if let Some(unit) = report_object(
@@ -144,6 +385,7 @@ fn generate(args: GenerateArgs) -> Result<()> {
                 project_dir,
                 project.target_dir.as_deref(),
                 project.base_dir.as_deref(),
+                &config,
                 Some(&mut existing_functions),
             )? {
                 report.units.push(unit);
@@ -159,68 +401,229 @@ fn generate(args: GenerateArgs) -> Result<()> {
                     project_dir,
                     project.target_dir.as_deref(),
                     project.base_dir.as_deref(),
+                    &config,
                     None,
                 )
             })
             .collect::<Result<Vec<Option<ReportUnit>>>>()?;
         report.units = units.into_iter().flatten().collect();
     }
-    for unit in &report.units {
-        report.fuzzy_match_percent += unit.fuzzy_match_percent * unit.total_code as f32;
-        report.total_code += unit.total_code;
-        report.matched_code += unit.matched_code;
-        report.total_data += unit.total_data;
-        report.matched_data += unit.matched_data;
-        report.total_functions += unit.total_functions;
-        report.matched_functions += unit.matched_functions;
-    }
-    if report.total_code == 0 {
-        report.fuzzy_match_percent = 100.0;
-    } else {
-        report.fuzzy_match_percent /= report.total_code as f32;
+    report.sum_units();
+    let duration = start.elapsed();
+    info!("Report generated in {}.{:03}s", duration.as_secs(), duration.subsec_millis());
+    Ok(report)
+}
+
+fn report_diff_config(
+    project: &ProjectConfig,
+    project_dir: &Path,
+    visibility: diff::SymbolVisibilityFilter,
+) -> diff::DiffObjConfig {
+    diff::DiffObjConfig {
+        relax_reloc_diffs: true,
+        match_visibility: visibility,
+        custom_relocations: project.custom_relocations.clone().unwrap_or_default(),
+        symbol_name_rules: project.symbol_name_rules.clone().unwrap_or_default(),
+        instruction_rules: project.instruction_rules.clone().unwrap_or_default(),
+        demangler: project.demangler(),
+        debug_search_paths: project
+            .debug_search_paths
+            .iter()
+            .flatten()
+            .map(|p| project_dir.join(p))
+            .collect(),
+        ..Default::default()
     }
+}
 
-    report.matched_code_percent = if report.total_code == 0 {
-        100.0
-    } else {
-        report.matched_code as f32 / report.total_code as f32 * 100.0
+fn status(args: StatusArgs) -> Result<()> {
+    let project_dir = args.project.as_deref().unwrap_or_else(|| Path::new("."));
+    let Some((project, info)) = objdiff_core::config::try_project_config(project_dir) else {
+        bail!("No project configuration found");
     };
-    report.matched_data_percent = if report.total_data == 0 {
-        100.0
-    } else {
-        report.matched_data as f32 / report.total_data as f32 * 100.0
+    let project = project
+        .with_context(|| format!("Failed to load project config {}", info.path.display()))?;
+    let objects = project
+        .objects
+        .iter()
+        .filter(|o| args.object.as_ref().map_or(true, |name| o.name() == name))
+        .cloned()
+        .collect::<Vec<_>>();
+    if let (Some(name), true) = (&args.object, objects.is_empty()) {
+        bail!("No unit named '{name}'");
+    }
+
+    let config = report_diff_config(&project, project_dir, args.visibility);
+    let build_config = BuildConfig {
+        project_dir: Some(project_dir.to_path_buf()),
+        custom_make: project.custom_make.clone(),
+        custom_args: project.custom_args.clone(),
+        selected_wsl_distro: args.wsl_distro.clone(),
+        wsl_mount_prefix: None,
+        max_parallel_builds: default_parallel_builds(),
+        timeout: None,
+        cancel: None,
     };
-    report.matched_functions_percent = if report.total_functions == 0 {
-        100.0
-    } else {
-        report.matched_functions as f32 / report.total_functions as f32 * 100.0
+    let mut report = Report::default();
+    let mut build_failures = vec![];
+    for mut object in objects {
+        if !args.no_build {
+            if let Err(e) = build_object(&project, &build_config, project_dir, &object) {
+                build_failures.push((object.name().to_string(), e));
+                continue;
+            }
+        }
+        if let Some(unit) = report_object(
+            &mut object,
+            project_dir,
+            project.target_dir.as_deref(),
+            project.base_dir.as_deref(),
+            &config,
+            None,
+        )? {
+            report.units.push(unit);
+        }
+    }
+    report.sum_units();
+
+    let name_width = report
+        .units
+        .iter()
+        .map(|u| u.name.len())
+        .chain(build_failures.iter().map(|(name, _)| name.len()))
+        .max()
+        .unwrap_or(0)
+        .max(5);
+    println!(
+        "{:name_width$}  {:>8}  {:>8}  {:>12}  {:>8}",
+        "Unit", "Fuzzy", "Code", "Functions", "Data"
+    );
+    let row = |name: &str, fuzzy: f32, code: f32, functions: String, data: f32| {
+        println!(
+            "{name:name_width$}  {:>8}  {:>8}  {functions:>12}  {:>8}",
+            format_percent(fuzzy),
+            format_percent(code),
+            format_percent(data),
+        );
     };
-    let duration = start.elapsed();
-    info!("Report generated in {}.{:03}s", duration.as_secs(), duration.subsec_millis());
-    if let Some(output) = &args.output {
-        info!("Writing to {}", output.display());
-        let mut output = BufWriter::new(
-            File::create(output)
-                .with_context(|| format!("Failed to create file {}", output.display()))?,
+    let percent = |matched: u64, total: u64| {
+        if total == 0 {
+            100.0
+        } else {
+            matched as f32 / total as f32 * 100.0
+        }
+    };
+    for unit in &report.units {
+        row(
+            &unit.name,
+            unit.fuzzy_match_percent,
+            percent(unit.matched_code, unit.total_code),
+            format!("{}/{}", unit.matched_functions, unit.total_functions),
+            percent(unit.matched_data, unit.total_data),
         );
-        serde_json::to_writer_pretty(&mut output, &report)?;
-        output.flush()?;
-    } else {
-        serde_json::to_writer_pretty(std::io::stdout(), &report)?;
+    }
+    for (name, _) in &build_failures {
+        println!("{name:name_width$}  {:>8}", "build failed");
+    }
+    row(
+        "Total",
+        report.fuzzy_match_percent,
+        report.matched_code_percent,
+        format!("{}/{}", report.matched_functions, report.total_functions),
+        report.matched_data_percent,
+    );
+
+    let regressed = report
+        .units
+        .iter()
+        .filter(|u| u.complete == Some(true) && u.matched_code != u.total_code)
+        .map(|u| u.name.as_str())
+        .collect::<Vec<_>>();
+    for (name, e) in &build_failures {
+        eprintln!("\n{name}: {e:#}");
+    }
+    if !build_failures.is_empty() {
+        bail!("{} unit(s) failed to build", build_failures.len());
+    }
+    if !regressed.is_empty() {
+        bail!("Complete units not fully matched: {}", regressed.join(", "));
+    }
+    Ok(())
+}
+
+/// Builds an object's target and base with the project's make command, as configured by
+/// `build_target` and `build_base`.
+fn build_object(
+    project: &ProjectConfig,
+    config: &BuildConfig,
+    project_dir: &Path,
+    object: &ProjectObject,
+) -> Result<()> {
+    let mut object = object.clone();
+    object.resolve_paths(project_dir, project.target_dir.as_deref(), project.base_dir.as_deref());
+    let paths = [
+        object.target_path.as_deref().filter(|_| project.build_target),
+        object.base_path.as_deref().filter(|_| project.build_base),
+    ];
+    for path in paths.into_iter().flatten() {
+        let path = path.strip_prefix(project_dir).unwrap_or(path);
+        info!("Building {}", path.display());
+        let extra_args = object.make_args.as_deref().unwrap_or_default();
+        let mut status = run_make(config, path, extra_args, None);
+        if let Some(build_status) = &object.build_status {
+            apply_build_status(&mut status, build_status);
+        }
+        if !status.success {
+            bail!("Failed to build {}:\n{}{}", path.display(), status.stdout, status.stderr);
+        }
     }
     Ok(())
 }
 
+fn aggregate(args: AggregateArgs) -> Result<()> {
+    if args.projects.is_empty() {
+        bail!("No projects specified");
+    }
+    let mut aggregate = AggregateReport::default();
+    for path in &args.projects {
+        // Accept the config file itself as well as its directory
+        let project_dir = if path.is_file() { path.parent().unwrap_or(path) } else { path };
+        let name = path.display().to_string();
+        match generate_report(project_dir, args.deduplicate, args.visibility) {
+            Ok(report) => {
+                let total = &mut aggregate.total;
+                total.fuzzy_match_percent += report.fuzzy_match_percent * report.total_code as f32;
+                total.total_code += report.total_code;
+                total.matched_code += report.matched_code;
+                total.total_data += report.total_data;
+                total.matched_data += report.matched_data;
+                total.total_functions += report.total_functions;
+                total.matched_functions += report.matched_functions;
+                aggregate.projects.insert(name, AggregateProject::Report(report));
+            }
+            Err(e) => {
+                warn!("Failed to generate report for {name}: {e:#}");
+                aggregate
+                    .projects
+                    .insert(name, AggregateProject::Error { error: format!("{e:#}") });
+            }
+        }
+    }
+    aggregate.total.compute_percentages();
+    write_json(args.output.as_deref(), &aggregate)
+}
+
 fn report_object(
     object: &mut ProjectObject,
     project_dir: &Path,
     target_dir: Option<&Path>,
     base_dir: Option<&Path>,
+    config: &diff::DiffObjConfig,
     mut existing_functions: Option<&mut HashSet<String>>,
 ) -> Result<Option<ReportUnit>> {
     object.resolve_paths(project_dir, target_dir, base_dir);
     match (&object.target_path, &object.base_path) {
-        (None, Some(_)) if object.complete != Some(true) => {
+        (None, Some(_)) if !object.is_complete() => {
             warn!("Skipping object without target: {}", object.name());
             return Ok(None);
         }
@@ -230,21 +633,26 @@ fn report_object(
         }
         _ => {}
     }
-    let config = diff::DiffObjConfig { relax_reloc_diffs: true, ..Default::default() };
     let target = object
         .target_path
         .as_ref()
         .map(|p| {
-            obj::read::read(p, &config).with_context(|| format!("Failed to open {}", p.display()))
+            obj::read::read(p, config).with_context(|| format!("Failed to open {}", p.display()))
         })
         .transpose()?;
     let base = object
         .base_path
         .as_ref()
         .map(|p| {
-            obj::read::read(p, &config).with_context(|| format!("Failed to open {}", p.display()))
+            obj::read::read(p, config).with_context(|| format!("Failed to open {}", p.display()))
         })
         .transpose()?;
+    let config = match &object.symbol_mappings {
+        Some(mappings) => {
+            Cow::Owned(diff::DiffObjConfig { symbol_mappings: mappings.clone(), ..config.clone() })
+        }
+        None => Cow::Borrowed(config),
+    };
     let result = diff::diff_objs(&config, target.as_ref(), base.as_ref(), None)?;
     let mut unit = ReportUnit {
         name: object.name().to_string(),
@@ -254,6 +662,7 @@ fn report_object(
             .and_then(|o| o.split_meta.as_ref())
             .and_then(|m| m.module_name.clone()),
         module_id: target.as_ref().and_then(|o| o.split_meta.as_ref()).and_then(|m| m.module_id),
+        category: object.category.clone(),
         ..Default::default()
     };
     let obj = target.as_ref().or(base.as_ref()).unwrap();
@@ -263,7 +672,7 @@ fn report_object(
         let section_match_percent = section_diff.match_percent.unwrap_or_else(|| {
             // Support cases where we don't have a target object,
             // assume complete means 100% match
-            if object.complete == Some(true) {
+            if object.is_complete() {
                 100.0
             } else {
                 0.0
@@ -289,7 +698,7 @@ fn report_object(
         }
 
         for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
-            if symbol.size == 0 {
+            if symbol.size == 0 || !config.match_visibility.includes(symbol) {
                 continue;
             }
             if let Some(existing_functions) = &mut existing_functions {
@@ -303,7 +712,7 @@ fn report_object(
             let match_percent = symbol_diff.match_percent.unwrap_or_else(|| {
                 // Support cases where we don't have a target object,
                 // assume complete means 100% match
-                if object.complete == Some(true) {
+                if object.is_complete() {
                     100.0
                 } else {
                     0.0
@@ -480,6 +889,228 @@ fn changes(args: ChangesArgs) -> Result<()> {
     Ok(())
 }
 
+/// Percentages are compared at the precision they're displayed with.
+fn format_percent(percent: f32) -> String { format!("{percent:.2}%") }
+
+fn format_delta(from: f32, to: f32) -> String {
+    let delta = to - from;
+    if format_percent(from) == format_percent(to) {
+        "±0.00%".to_string()
+    } else if delta > 0.0 {
+        format!("+{delta:.2}%")
+    } else {
+        format!("{delta:.2}%")
+    }
+}
+
+fn comment(args: CommentArgs) -> Result<()> {
+    let previous = read_report(&args.previous)?;
+    let current = read_report(&args.current)?;
+
+    // (name, before, after), omitting units whose displayed percentage is unchanged
+    let mut rows: Vec<(&str, Option<f32>, Option<f32>)> = vec![];
+    for prev_unit in &previous.units {
+        let curr_unit = current.units.iter().find(|u| u.name == prev_unit.name);
+        let (from, to) = (prev_unit.fuzzy_match_percent, curr_unit.map(|u| u.fuzzy_match_percent));
+        if to.map(format_percent) != Some(format_percent(from)) {
+            rows.push((&prev_unit.name, Some(from), to));
+        }
+    }
+    for curr_unit in &current.units {
+        if !previous.units.iter().any(|u| u.name == curr_unit.name) {
+            rows.push((&curr_unit.name, None, Some(curr_unit.fuzzy_match_percent)));
+        }
+    }
+    let delta =
+        |(_, from, to): &(&str, Option<f32>, Option<f32>)| to.unwrap_or(0.0) - from.unwrap_or(0.0);
+    rows.sort_by(|a, b| delta(b).total_cmp(&delta(a)).then(a.0.cmp(b.0)));
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "### Match progress: {} ({})",
+        format_percent(current.fuzzy_match_percent),
+        format_delta(previous.fuzzy_match_percent, current.fuzzy_match_percent)
+    )?;
+    writeln!(out)?;
+    if rows.is_empty() {
+        writeln!(out, "No objects changed.")?;
+    } else {
+        writeln!(out, "| | Object | Before | After | Change |")?;
+        writeln!(out, "|---|---|---:|---:|---:|")?;
+        for row in &rows {
+            let (name, from, to) = *row;
+            // New objects have nothing to trend from
+            let emoji = match from {
+                None => "",
+                Some(_) if delta(row) > 0.0 => "📈",
+                Some(_) => "📉",
+            };
+            writeln!(
+                out,
+                "| {emoji} | `{name}` | {} | {} | {} |",
+                from.map(format_percent).unwrap_or_else(|| "-".to_string()),
+                to.map(format_percent).unwrap_or_else(|| "-".to_string()),
+                format_delta(from.unwrap_or(0.0), to.unwrap_or(0.0))
+            )?;
+        }
+    }
+
+    if let Some(output) = &args.output {
+        info!("Writing to {}", output.display());
+        std::fs::write(output, out)
+            .with_context(|| format!("Failed to write {}", output.display()))?;
+    } else {
+        print!("{out}");
+    }
+    Ok(())
+}
+
+/// Code size counted per instruction, since reports don't record the architecture
+const INSTRUCTION_SIZE: u64 = 4;
+
+/// How much a unit's match percentage may drop before it's considered a regression.
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Tolerance {
+    /// Percentage points
+    percent: f32,
+    bytes: u64,
+    instructions: u64,
+}
+
+impl Tolerance {
+    /// Drops within either the percentage or the code size tolerance are ignored, so jitter in
+    /// large units and a stray instruction in small ones don't count.
+    fn is_regression(&self, from: &ReportUnit, to: &ReportUnit) -> bool {
+        let drop = from.fuzzy_match_percent - to.fuzzy_match_percent;
+        let drop_bytes = drop / 100.0 * to.total_code.max(from.total_code) as f32;
+        let size = self.bytes.max(self.instructions * INSTRUCTION_SIZE);
+        drop > self.percent && drop_bytes > size as f32
+    }
+}
+
+fn check(args: CheckArgs) -> Result<()> {
+    let previous = read_report(&args.previous)?;
+    let current = read_report(&args.current)?;
+    let tolerance = Tolerance {
+        percent: args.tolerance,
+        bytes: args.byte_tolerance,
+        instructions: args.instruction_tolerance,
+    };
+
+    let mut regressions = vec![];
+    for prev_unit in &previous.units {
+        let Some(curr_unit) = current.units.iter().find(|u| u.name == prev_unit.name) else {
+            regressions.push(format!("{}: missing from current report", prev_unit.name));
+            continue;
+        };
+        let tolerance = match args.unit_tolerance.iter().rfind(|u| u.name == prev_unit.name) {
+            Some(unit_tolerance) => unit_tolerance.apply(tolerance),
+            None => tolerance,
+        };
+        if tolerance.is_regression(prev_unit, curr_unit) {
+            regressions.push(format!(
+                "{}: {} -> {} ({})",
+                prev_unit.name,
+                format_percent(prev_unit.fuzzy_match_percent),
+                format_percent(curr_unit.fuzzy_match_percent),
+                format_delta(prev_unit.fuzzy_match_percent, curr_unit.fuzzy_match_percent)
+            ));
+        }
+    }
+    if !regressions.is_empty() {
+        bail!("{} unit(s) regressed:\n{}", regressions.len(), regressions.join("\n"));
+    }
+    info!("No regressions in {} units", current.units.len());
+    Ok(())
+}
+
+/// Interpolates from red at 0% to green at 100%.
+fn badge_color(percent: f32) -> String {
+    let t = (percent / 100.0).clamp(0.0, 1.0);
+    let lerp = |from: f32, to: f32| (from + (to - from) * t).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", lerp(224.0, 68.0), lerp(93.0, 204.0), lerp(68.0, 17.0))
+}
+
+/// Renders a flat shields.io-style badge.
+fn badge_svg(label: &str, percent: f32) -> String {
+    let value = format_percent(percent);
+    // Approximate Verdana 11px widths
+    let text_width = |s: &str| s.chars().count() as u32 * 7 + 10;
+    let (label_width, value_width) = (text_width(label), text_width(&value));
+    let width = label_width + value_width;
+    let label = label.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;");
+    format!(
+        concat!(
+            r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20">"##,
+            r##"<rect width="{label_width}" height="20" fill="#555"/>"##,
+            r##"<rect x="{label_width}" width="{value_width}" height="20" fill="{color}"/>"##,
+            r##"<g fill="#fff" text-anchor="middle" font-family="Verdana,sans-serif" font-size="11">"##,
+            r##"<text x="{label_x}" y="14">{label}</text>"##,
+            r##"<text x="{value_x}" y="14">{value}</text>"##,
+            "</g></svg>\n"
+        ),
+        width = width,
+        label_width = label_width,
+        value_width = value_width,
+        color = badge_color(percent),
+        label_x = label_width / 2,
+        value_x = label_width + value_width / 2,
+        label = label,
+        value = value,
+    )
+}
+
+/// File name reserved for the whole report's badge.
+const TOTAL_BADGE: &str = "_total";
+
+/// A category's badge file name, with characters other than ASCII letters, digits, `-` and `_`
+/// replaced.
+fn badge_file_name(category: &str) -> String {
+    category
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+fn badges(args: BadgesArgs) -> Result<()> {
+    let report = read_report(&args.report)?;
+
+    // Category name -> (fuzzy percent weighted by code size, total code)
+    let mut categories = std::collections::BTreeMap::<String, (f32, u64)>::new();
+    for unit in &report.units {
+        let category = match &unit.category {
+            Some(category) => category.clone(),
+            None => unit.name.split('/').take(args.depth.max(1)).collect::<Vec<_>>().join("/"),
+        };
+        let entry = categories.entry(category).or_default();
+        entry.0 += unit.fuzzy_match_percent * unit.total_code as f32;
+        entry.1 += unit.total_code;
+    }
+
+    std::fs::create_dir_all(&args.output)
+        .with_context(|| format!("Failed to create directory {}", args.output.display()))?;
+    // File name -> badge label, to catch categories that map to the same file
+    let mut written = HashMap::<String, String>::new();
+    let mut write_badge = |file_name: String, label: &str, percent: f32| -> Result<()> {
+        if let Some(other) = written.get(&file_name) {
+            bail!("Badges for '{other}' and '{label}' would both be written to {file_name}.svg");
+        }
+        let path = args.output.join(format!("{file_name}.svg"));
+        std::fs::write(&path, badge_svg(label, percent))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        written.insert(file_name, label.to_string());
+        Ok(())
+    };
+    write_badge(TOTAL_BADGE.to_string(), "total", report.fuzzy_match_percent)?;
+    for (name, (weighted, total_code)) in &categories {
+        let percent = if *total_code == 0 { 100.0 } else { weighted / *total_code as f32 };
+        write_badge(badge_file_name(name), name, percent)?;
+    }
+    info!("Wrote {} badges to {}", categories.len() + 1, args.output.display());
+    Ok(())
+}
+
 fn process_items<F: Fn(&ReportUnit) -> &Vec<ReportItem>>(
     prev_unit: &ReportUnit,
     curr_unit: Option<&ReportUnit>,
@@ -565,3 +1196,60 @@ where D: serde::Deserializer<'de> {
         u64::from_str_radix(&s[2..], 16).map(Some).map_err(serde::de::Error::custom)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit(fuzzy_match_percent: f32, total_code: u64) -> ReportUnit {
+        ReportUnit {
+            name: "main/foo".to_string(),
+            fuzzy_match_percent,
+            total_code,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn tolerance_ignores_small_drops() {
+        let tolerance = Tolerance { percent: 0.05, bytes: 0, instructions: 1 };
+        // 0.04% of 1000 bytes
+        assert!(!tolerance.is_regression(&unit(50.0, 1000), &unit(49.96, 1000)));
+        // 0.3%, but only 3 bytes
+        assert!(!tolerance.is_regression(&unit(50.0, 1000), &unit(49.7, 1000)));
+        // 1%, 10 bytes
+        assert!(tolerance.is_regression(&unit(50.0, 1000), &unit(49.0, 1000)));
+        // Improvements are never regressions
+        assert!(!tolerance.is_regression(&unit(50.0, 1000), &unit(60.0, 1000)));
+    }
+
+    #[test]
+    fn unit_tolerance_overrides_parts() {
+        let unit_tolerance = parse_unit_tolerance("main/foo=0.5%,16b").unwrap();
+        assert_eq!(unit_tolerance, UnitTolerance {
+            name: "main/foo".to_string(),
+            percent: Some(0.5),
+            bytes: Some(16),
+            instructions: None,
+        });
+        let tolerance = Tolerance { percent: 0.001, bytes: 0, instructions: 2 };
+        assert_eq!(unit_tolerance.apply(tolerance), Tolerance {
+            percent: 0.5,
+            bytes: 16,
+            instructions: 2
+        });
+        assert_eq!(parse_unit_tolerance("bar=0.25").unwrap().percent, Some(0.25));
+        assert_eq!(parse_unit_tolerance("bar=3i").unwrap().instructions, Some(3));
+        assert!(parse_unit_tolerance("bar=x").is_err());
+    }
+
+    #[test]
+    fn badge_shows_label_and_percent() {
+        let svg = badge_svg("game", 42.5);
+        assert!(svg.contains(">game</text>"));
+        assert!(svg.contains(">42.50%</text>"));
+        assert!(svg.contains(&badge_color(42.5)));
+        assert_eq!(badge_file_name("a-b"), "a-b");
+        assert_eq!(badge_file_name("a/b"), "a_b");
+    }
+}
@@ -0,0 +1,126 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::PathBuf,
+};
+
+use anyhow::{Context, Result};
+use argp::FromArgs;
+use objdiff_core::{
+    diff,
+    obj::{self, ObjInfo, ObjSectionKind},
+};
+use tracing::info;
+
+#[derive(FromArgs, PartialEq, Debug)]
+/// Export an object's symbols to a ctags file.
+#[argp(subcommand, name = "tags")]
+pub struct Args {
+    #[argp(positional)]
+    /// Object file
+    object: PathBuf,
+    #[argp(option, short = 'o')]
+    /// Output tags file (default: stdout)
+    output: Option<PathBuf>,
+}
+
+struct TagEntry<'a> {
+    name: &'a str,
+    file: &'a str,
+    line: u64,
+    kind: char,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    let config = diff::DiffObjConfig::default();
+    let obj = obj::read::read(&args.object, &config)
+        .with_context(|| format!("Failed to open {}", args.object.display()))?;
+    let entries = tag_entries(&obj);
+    if obj.source_file.is_none() {
+        info!("No source info found in {}", args.object.display());
+    }
+    if let Some(output) = &args.output {
+        info!("Writing {} tags to {}", entries.len(), output.display());
+        let mut output = BufWriter::new(
+            File::create(output)
+                .with_context(|| format!("Failed to create file {}", output.display()))?,
+        );
+        write_tags(&mut output, &entries)?;
+        output.flush()?;
+    } else {
+        write_tags(&mut std::io::stdout().lock(), &entries)?;
+    }
+    Ok(())
+}
+
+/// Collects symbols with known source locations, sorted by name.
+fn tag_entries(obj: &ObjInfo) -> Vec<TagEntry<'_>> {
+    let mut entries = Vec::new();
+    for section in &obj.sections {
+        let kind = match section.kind {
+            ObjSectionKind::Code => 'f',
+            ObjSectionKind::Data | ObjSectionKind::Bss => 'v',
+        };
+        for symbol in &section.symbols {
+            let Some((file, line)) = obj.symbol_source(section, symbol) else {
+                continue;
+            };
+            entries.push(TagEntry { name: &symbol.name, file, line, kind });
+        }
+    }
+    entries.sort_by(|a, b| a.name.cmp(b.name).then(a.file.cmp(b.file)).then(a.line.cmp(&b.line)));
+    entries
+}
+
+fn write_tags<W: Write>(w: &mut W, entries: &[TagEntry]) -> Result<()> {
+    writeln!(w, "!_TAG_FILE_FORMAT\t2\t/extended format/")?;
+    writeln!(w, "!_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/")?;
+    writeln!(w, "!_TAG_PROGRAM_NAME\tobjdiff-cli\t//")?;
+    for entry in entries {
+        writeln!(w, "{}\t{}\t{};\"\t{}", entry.name, entry.file, entry.line, entry.kind)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::*;
+
+    #[test]
+    fn writes_extended_format_tags() {
+        let entries =
+            [TagEntry { name: "main", file: "src/main.c", line: 12, kind: 'f' }, TagEntry {
+                name: "table",
+                file: "src/data.c",
+                line: 3,
+                kind: 'v',
+            }];
+        let mut out = Vec::new();
+        write_tags(&mut out, &entries).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "!_TAG_FILE_FORMAT\t2\t/extended format/\n\
+             !_TAG_FILE_SORTED\t1\t/0=unsorted, 1=sorted, 2=foldcase/\n\
+             !_TAG_PROGRAM_NAME\tobjdiff-cli\t//\n\
+             main\tsrc/main.c\t12;\"\tf\n\
+             table\tsrc/data.c\t3;\"\tv\n"
+        );
+    }
+
+    #[test]
+    fn tags_symbols_with_source() {
+        let path =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("../objdiff-core/tests/data/inline.elf");
+        let obj = obj::read::read(&path, &diff::DiffObjConfig::default()).unwrap();
+        let entries = tag_entries(&obj);
+        let [entry] = entries.as_slice() else {
+            panic!("expected one tag, got {}", entries.len());
+        };
+        assert_eq!(entry.name, "_start");
+        assert!(entry.file.ends_with("inline.c"), "{}", entry.file);
+        assert_eq!(entry.line, 4);
+        assert_eq!(entry.kind, 'f');
+    }
+}
@@ -77,7 +77,9 @@ struct TopLevel {
 #[argp(subcommand)]
 enum SubCommand {
     Diff(cmd::diff::Args),
+    ListObjects(cmd::list_objects::Args),
     Report(cmd::report::Args),
+    Tags(cmd::tags::Args),
 }
 
 // Duplicated from supports-color so we can check early.
@@ -132,7 +134,9 @@ fn main() {
     }
     result = result.and_then(|_| match args.command {
         SubCommand::Diff(c_args) => cmd::diff::run(c_args),
+        SubCommand::ListObjects(c_args) => cmd::list_objects::run(c_args),
         SubCommand::Report(c_args) => cmd::report::run(c_args),
+        SubCommand::Tags(c_args) => cmd::tags::run(c_args),
     });
     if let Err(e) = result {
         eprintln!("Failed: {e:?}");
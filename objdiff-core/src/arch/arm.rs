@@ -17,7 +17,9 @@ use unarm::{
 };
 
 use crate::{
-    arch::{ObjArch, ProcessCodeResult},
+    arch::{
+        display_unknown_reloc, find_custom_reloc, CustomRelocation, ObjArch, ProcessCodeResult,
+    },
     diff::{ArmArchVersion, ArmR9Usage, DiffObjConfig},
     obj::{ObjIns, ObjInsArg, ObjInsArgValue, ObjReloc, ObjSection},
 };
@@ -27,16 +29,17 @@ pub struct ObjArchArm {
     disasm_modes: HashMap<SectionIndex, Vec<DisasmMode>>,
     detected_version: Option<ArmVersion>,
     endianness: object::Endianness,
+    custom_relocations: Vec<CustomRelocation>,
 }
 
 impl ObjArchArm {
-    pub fn new(file: &File) -> Result<Self> {
+    pub fn new(file: &File, custom_relocations: Vec<CustomRelocation>) -> Result<Self> {
         let endianness = file.endianness();
         match file {
             File::Elf32(_) => {
                 let disasm_modes = Self::elf_get_mapping_symbols(file);
                 let detected_version = Self::elf_detect_arm_version(file)?;
-                Ok(Self { disasm_modes, detected_version, endianness })
+                Ok(Self { disasm_modes, detected_version, endianness, custom_relocations })
             }
             _ => bail!("Unsupported file format {:?}", file.format()),
         }
@@ -189,7 +192,8 @@ impl ObjArch for ObjArchArm {
                     | RelocationFlags::Elf { r_type: elf::R_ARM_THM_PC22 }
                     | RelocationFlags::Elf { r_type: elf::R_ARM_PC24 }
                     | RelocationFlags::Elf { r_type: elf::R_ARM_XPC25 }
-                    | RelocationFlags::Elf { r_type: elf::R_ARM_CALL } => {
+                    | RelocationFlags::Elf { r_type: elf::R_ARM_CALL }
+                    | RelocationFlags::Elf { r_type: elf::R_ARM_JUMP24 } => {
                         reloc_arg =
                             ins.args.iter().rposition(|a| matches!(a, Argument::BranchDest(_)));
                     }
@@ -219,6 +223,7 @@ impl ObjArch for ObjArchArm {
                 line,
                 formatted: ins.display(display_options).to_string(),
                 orig: None,
+                canonical: None,
             });
         }
 
@@ -237,7 +242,8 @@ impl ObjArch for ObjArchArm {
             // ARM calls
             RelocationFlags::Elf { r_type: elf::R_ARM_PC24 }
             | RelocationFlags::Elf { r_type: elf::R_ARM_XPC25 }
-            | RelocationFlags::Elf { r_type: elf::R_ARM_CALL } => {
+            | RelocationFlags::Elf { r_type: elf::R_ARM_CALL }
+            | RelocationFlags::Elf { r_type: elf::R_ARM_JUMP24 } => {
                 let data = section.data[address..address + 4].try_into()?;
                 let addend = self.endianness.read_i32_bytes(data);
                 let imm24 = addend & 0xffffff;
@@ -262,7 +268,12 @@ impl ObjArch for ObjArchArm {
                 self.endianness.read_i32_bytes(data)
             }
 
-            flags => bail!("Unsupported ARM implicit relocation {flags:?}"),
+            flags => match find_custom_reloc(&self.custom_relocations, flags) {
+                Some(custom) => {
+                    return custom.implicit_addend(section, address as u64, self.endianness)
+                }
+                None => bail!("Unsupported ARM implicit relocation {flags:?}"),
+            },
         } as i64)
     }
 
@@ -273,7 +284,66 @@ impl ObjArch for ObjArchArm {
     }
 
     fn display_reloc(&self, flags: RelocationFlags) -> Cow<'static, str> {
-        Cow::Owned(format!("<{flags:?}>"))
+        match flags {
+            RelocationFlags::Elf { r_type } => match r_type {
+                elf::R_ARM_ABS32 => Cow::Borrowed("R_ARM_ABS32"),
+                elf::R_ARM_PC24 => Cow::Borrowed("R_ARM_PC24"),
+                elf::R_ARM_XPC25 => Cow::Borrowed("R_ARM_XPC25"),
+                elf::R_ARM_CALL => Cow::Borrowed("R_ARM_CALL"),
+                elf::R_ARM_JUMP24 => Cow::Borrowed("R_ARM_JUMP24"),
+                elf::R_ARM_THM_PC22 => Cow::Borrowed("R_ARM_THM_CALL"),
+                elf::R_ARM_THM_XPC22 => Cow::Borrowed("R_ARM_THM_XPC22"),
+                _ => display_unknown_reloc(&self.custom_relocations, flags),
+            },
+            _ => display_unknown_reloc(&self.custom_relocations, flags),
+        }
+    }
+
+    fn instruction_cost(&self, ins: &ObjIns) -> Option<u32> {
+        // Approximate ARM7TDMI cycle counts
+        let mnemonic = ins.mnemonic.as_str();
+        Some(if mnemonic.starts_with("umlal") || mnemonic.starts_with("smlal") {
+            5
+        } else if mnemonic.starts_with("mla")
+            || mnemonic.starts_with("umull")
+            || mnemonic.starts_with("smull")
+        {
+            4
+        } else if mnemonic.starts_with("mul")
+            || mnemonic.starts_with("ldr")
+            || mnemonic.starts_with("ldm")
+            || mnemonic.starts_with("pop")
+            || mnemonic.starts_with('b')
+        {
+            3
+        } else if mnemonic.starts_with("str")
+            || mnemonic.starts_with("stm")
+            || mnemonic.starts_with("push")
+        {
+            2
+        } else {
+            1
+        })
+    }
+
+    fn is_address_arg(&self, insts: &[ObjIns], ins_idx: usize, arg_idx: usize) -> bool {
+        let ins = &insts[ins_idx];
+        // The halves of an address loaded with movw/movt
+        (ins.mnemonic.starts_with("movw") || ins.mnemonic.starts_with("movt"))
+            && matches!(ins.args.get(arg_idx), Some(ObjInsArg::Arg(ObjInsArgValue::Unsigned(_))))
+    }
+
+    fn is_register_arg(&self, ins: &ObjIns, arg_idx: usize) -> bool {
+        // r0-r12 under either naming scheme, but not sp, lr or pc
+        let Some(ObjInsArg::Arg(ObjInsArgValue::Opaque(name))) = ins.args.get(arg_idx) else {
+            return false;
+        };
+        let numbered = ["r", "a", "v"].iter().any(|prefix| {
+            name.strip_prefix(prefix)
+                .and_then(|n| n.parse::<u8>().ok())
+                .is_some_and(|n| *prefix != "r" || n <= 12)
+        });
+        numbered || matches!(name.as_ref(), "ip" | "sb" | "sl" | "fp")
     }
 }
 
@@ -8,16 +8,20 @@ use object::{
 use rabbitizer::{config, Abi, InstrCategory, Instruction, OperandType};
 
 use crate::{
-    arch::{ObjArch, ProcessCodeResult},
+    arch::{
+        display_unknown_reloc, find_custom_reloc, push_unknown_reloc, CustomRelocation, ObjArch,
+        ProcessCodeResult,
+    },
     diff::{DiffObjConfig, MipsAbi, MipsInstrCategory},
     obj::{ObjIns, ObjInsArg, ObjInsArgValue, ObjReloc, ObjSection},
 };
 
 static RABBITIZER_MUTEX: Mutex<()> = Mutex::new(());
 
-fn configure_rabbitizer(abi: Abi) {
+fn configure_rabbitizer(abi: Abi, pseudos: bool) {
     unsafe {
         config::RabbitizerConfig_Cfg.reg_names.fpr_abi_names = abi;
+        config::RabbitizerConfig_Cfg.pseudos.enable_pseudos = pseudos;
     }
 }
 
@@ -26,6 +30,7 @@ pub struct ObjArchMips {
     pub abi: Abi,
     pub instr_category: InstrCategory,
     pub ri_gp_value: i32,
+    pub custom_relocations: Vec<CustomRelocation>,
 }
 
 const EF_MIPS_ABI: u32 = 0x0000F000;
@@ -35,7 +40,7 @@ const EF_MIPS_MACH_ALLEGREX: u32 = 0x00840000;
 const EF_MIPS_MACH_5900: u32 = 0x00920000;
 
 impl ObjArchMips {
-    pub fn new(object: &File) -> Result<Self> {
+    pub fn new(object: &File, custom_relocations: Vec<CustomRelocation>) -> Result<Self> {
         let mut abi = Abi::NUMERIC;
         let mut instr_category = InstrCategory::CPU;
         match object.flags() {
@@ -72,7 +77,13 @@ impl ObjArchMips {
             .map(|bytes| object.endianness().read_i32_bytes(bytes))
             .unwrap_or(0);
 
-        Ok(Self { endianness: object.endianness(), abi, instr_category, ri_gp_value })
+        Ok(Self {
+            endianness: object.endianness(),
+            abi,
+            instr_category,
+            ri_gp_value,
+            custom_relocations,
+        })
     }
 }
 
@@ -87,12 +98,17 @@ impl ObjArch for ObjArchMips {
         config: &DiffObjConfig,
     ) -> Result<ProcessCodeResult> {
         let _guard = RABBITIZER_MUTEX.lock().map_err(|e| anyhow!("Failed to lock mutex: {e}"))?;
-        configure_rabbitizer(match config.mips_abi {
-            MipsAbi::Auto => self.abi,
-            MipsAbi::O32 => Abi::O32,
-            MipsAbi::N32 => Abi::N32,
-            MipsAbi::N64 => Abi::N64,
-        });
+        // Pseudo-instructions also change the unique IDs used as ops, but both sides share the
+        // config
+        configure_rabbitizer(
+            match config.mips_abi {
+                MipsAbi::Auto => self.abi,
+                MipsAbi::O32 => Abi::O32,
+                MipsAbi::N32 => Abi::N32,
+                MipsAbi::N64 => Abi::N64,
+            },
+            config.mips_pseudo_instructions,
+        );
         let instr_category = match config.mips_instr_category {
             MipsInstrCategory::Auto => self.instr_category,
             MipsInstrCategory::Cpu => InstrCategory::CPU,
@@ -108,6 +124,8 @@ impl ObjArch for ObjArchMips {
         let mut ops = Vec::<u16>::with_capacity(ins_count);
         let mut insts = Vec::<ObjIns>::with_capacity(ins_count);
         let mut cur_addr = start_address as u32;
+        // Line of the preceding branch or jump, if the current instruction is in its delay slot
+        let mut delay_slot_line = None;
         for chunk in code.chunks_exact(4) {
             let reloc = relocations.iter().find(|r| (r.address as u32 & !3) == cur_addr);
             let code = self.endianness.read_u32_bytes(chunk.try_into()?);
@@ -144,7 +162,7 @@ impl ObjArch for ObjArchMips {
                             {
                                 args.push(ObjInsArg::BranchDest(reloc.target.address));
                             } else {
-                                push_reloc(&mut args, reloc)?;
+                                push_reloc(&mut args, reloc, &self.custom_relocations)?;
                                 branch_dest = None;
                             }
                         } else if let Some(branch_dest) = branch_dest {
@@ -157,7 +175,7 @@ impl ObjArch for ObjArchMips {
                     }
                     OperandType::cpu_immediate_base => {
                         if let Some(reloc) = reloc {
-                            push_reloc(&mut args, reloc)?;
+                            push_reloc(&mut args, reloc, &self.custom_relocations)?;
                         } else {
                             args.push(ObjInsArg::Arg(ObjInsArgValue::Opaque(
                                 OperandType::cpu_immediate.disassemble(&instruction, None).into(),
@@ -176,7 +194,14 @@ impl ObjArch for ObjArchMips {
                     }
                 }
             }
-            let line = line_info.range(..=cur_addr as u64).last().map(|(_, &b)| b);
+            // Compilers often attribute the delay slot to the following statement, but it
+            // executes as part of the branch, so keep it with the branch's line.
+            let line = delay_slot_line
+                .take()
+                .unwrap_or_else(|| line_info.range(..=cur_addr as u64).last().map(|(_, &b)| b));
+            if instruction.has_delay_slot() {
+                delay_slot_line = Some(line);
+            }
             insts.push(ObjIns {
                 address: cur_addr as u64,
                 size: 4,
@@ -188,6 +213,7 @@ impl ObjArch for ObjArchMips {
                 line,
                 formatted,
                 orig: None,
+                canonical: None,
             });
             cur_addr += 4;
         }
@@ -227,7 +253,10 @@ impl ObjArch for ObjArchMips {
             }
             RelocationFlags::Elf { r_type: elf::R_MIPS_26 } => ((addend & 0x03FFFFFF) << 2) as i64,
             RelocationFlags::Elf { r_type: elf::R_MIPS_PC16 } => 0, // PC-relative relocation
-            flags => bail!("Unsupported MIPS implicit relocation {flags:?}"),
+            flags => match find_custom_reloc(&self.custom_relocations, flags) {
+                Some(custom) => custom.implicit_addend(section, address, self.endianness)?,
+                None => bail!("Unsupported MIPS implicit relocation {flags:?}"),
+            },
         })
     }
 
@@ -242,14 +271,55 @@ impl ObjArch for ObjArchMips {
                 elf::R_MIPS_GPREL16 => Cow::Borrowed("R_MIPS_GPREL16"),
                 elf::R_MIPS_32 => Cow::Borrowed("R_MIPS_32"),
                 elf::R_MIPS_26 => Cow::Borrowed("R_MIPS_26"),
-                _ => Cow::Owned(format!("<{flags:?}>")),
+                _ => display_unknown_reloc(&self.custom_relocations, flags),
             },
-            _ => Cow::Owned(format!("<{flags:?}>")),
+            _ => display_unknown_reloc(&self.custom_relocations, flags),
         }
     }
+
+    fn instruction_cost(&self, ins: &ObjIns) -> Option<u32> {
+        // Approximate VR4300 latencies
+        Some(match ins.mnemonic.as_str() {
+            "mult" | "multu" => 5,
+            "dmult" | "dmultu" => 8,
+            "div" | "divu" => 37,
+            "ddiv" | "ddivu" => 69,
+            "add.s" | "sub.s" | "add.d" | "sub.d" => 3,
+            "mul.s" => 5,
+            "mul.d" => 8,
+            "div.s" | "sqrt.s" => 29,
+            "div.d" | "sqrt.d" => 58,
+            m if m.starts_with("cvt.") => 5,
+            "lb" | "lbu" | "lh" | "lhu" | "lw" | "lwu" | "ld" | "lwc1" | "ldc1" => 2,
+            _ => 1,
+        })
+    }
+
+    fn is_address_arg(&self, insts: &[ObjIns], ins_idx: usize, arg_idx: usize) -> bool {
+        let ins = &insts[ins_idx];
+        // The upper half of an address loaded with lui/addiu
+        ins.mnemonic == "lui"
+            && matches!(
+                ins.args.get(arg_idx),
+                Some(ObjInsArg::Arg(ObjInsArgValue::Opaque(v))) if !v.starts_with('$')
+            )
+    }
+
+    fn is_register_arg(&self, ins: &ObjIns, arg_idx: usize) -> bool {
+        // Registers with a fixed purpose aren't allocated
+        matches!(
+            ins.args.get(arg_idx),
+            Some(ObjInsArg::Arg(ObjInsArgValue::Opaque(v)))
+                if v.starts_with('$') && !matches!(v.as_ref(), "$zero" | "$sp" | "$gp" | "$ra")
+        )
+    }
 }
 
-fn push_reloc(args: &mut Vec<ObjInsArg>, reloc: &ObjReloc) -> Result<()> {
+fn push_reloc(
+    args: &mut Vec<ObjInsArg>,
+    reloc: &ObjReloc,
+    custom_relocations: &[CustomRelocation],
+) -> Result<()> {
     match reloc.flags {
         RelocationFlags::Elf { r_type } => match r_type {
             elf::R_MIPS_HI16 => {
@@ -280,9 +350,35 @@ fn push_reloc(args: &mut Vec<ObjInsArg>, reloc: &ObjReloc) -> Result<()> {
             elf::R_MIPS_32 | elf::R_MIPS_26 | elf::R_MIPS_PC16 => {
                 args.push(ObjInsArg::Reloc);
             }
-            _ => bail!("Unsupported ELF MIPS relocation type {r_type}"),
+            _ => push_unknown_reloc(args, custom_relocations, reloc.flags),
         },
         flags => panic!("Unsupported MIPS relocation flags {flags:?}"),
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(config: &DiffObjConfig, code: u32) -> ProcessCodeResult {
+        let arch = ObjArchMips {
+            endianness: Endianness::Big,
+            abi: Abi::O32,
+            instr_category: InstrCategory::CPU,
+            ri_gp_value: 0,
+            custom_relocations: vec![],
+        };
+        arch.process_code(0, &code.to_be_bytes(), 0, &[], &BTreeMap::new(), config).unwrap()
+    }
+
+    #[test]
+    fn pseudo_instructions_toggle_display() {
+        // or $a0, $a1, $zero
+        let code = 0x00a02025;
+        let mut config = DiffObjConfig::default();
+        assert_eq!(process(&config, code).insts[0].mnemonic, "move");
+        config.mips_pseudo_instructions = false;
+        assert_eq!(process(&config, code).insts[0].mnemonic, "or");
+    }
+}
@@ -1,11 +1,15 @@
 use std::{borrow::Cow, collections::BTreeMap};
 
-use anyhow::{bail, Result};
-use object::{Architecture, File, Object, ObjectSymbol, Relocation, RelocationFlags, Symbol};
+use anyhow::{bail, ensure, Context, Result};
+use object::{
+    Architecture, Endian, Endianness, File, Object, ObjectSymbol, Relocation, RelocationFlags,
+    Symbol,
+};
+use regex::Regex;
 
 use crate::{
     diff::DiffObjConfig,
-    obj::{ObjIns, ObjReloc, ObjSection},
+    obj::{ObjIns, ObjInsArg, ObjReloc, ObjSection},
 };
 
 #[cfg(feature = "arm")]
@@ -41,6 +45,31 @@ pub trait ObjArch: Send + Sync {
     fn display_reloc(&self, flags: RelocationFlags) -> Cow<'static, str>;
 
     fn symbol_address(&self, symbol: &Symbol) -> u64 { symbol.address() }
+
+    /// Whether the instruction is a NOP that may be used to pad the end of a function.
+    fn is_padding_nop(&self, ins: &ObjIns) -> bool { ins.mnemonic == "nop" }
+
+    /// Approximate cycle cost of the instruction, if the architecture has a cost model.
+    fn instruction_cost(&self, _ins: &ObjIns) -> Option<u32> { None }
+
+    /// Whether the argument of `insts[ins_idx]` holds (part of) an unrelocated absolute
+    /// address, whose value depends on the final layout rather than the code itself. The
+    /// function's other instructions are there for context.
+    fn is_address_arg(&self, _insts: &[ObjIns], _ins_idx: usize, _arg_idx: usize) -> bool { false }
+
+    /// Whether the argument is a register operand that compilers may allocate differently.
+    fn is_register_arg(&self, _ins: &ObjIns, _arg_idx: usize) -> bool { false }
+
+    /// Built-in (pattern, canonical) [`InstructionRule`]s, applied after the project's.
+    fn default_instruction_rules(&self) -> &'static [(&'static str, &'static str)] { &[] }
+}
+
+/// Sums the cycle costs of the instructions, if the architecture has a cost model.
+pub fn total_cost<'a>(
+    arch: &dyn ObjArch,
+    insts: impl IntoIterator<Item = &'a ObjIns>,
+) -> Option<u32> {
+    insts.into_iter().map(|ins| arch.instruction_cost(ins)).sum()
 }
 
 pub struct ProcessCodeResult {
@@ -48,16 +77,293 @@ pub struct ProcessCodeResult {
     pub insts: Vec<ObjIns>,
 }
 
-pub fn new_arch(object: &object::File) -> Result<Box<dyn ObjArch>> {
+/// A relocation type unknown to objdiff, described by the project configuration.
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct CustomRelocation {
+    /// Relocation type number (ELF `r_type` or COFF `typ`)
+    pub r_type: u32,
+    /// Display name, e.g. `R_PPC_CUSTOM`
+    pub name: String,
+    /// Operand format, where `{}` is replaced by the relocation target, e.g. `{}@l`
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Mask of the instruction word holding the implicit addend, if any
+    #[serde(default)]
+    pub addend_mask: Option<u32>,
+    /// Left shift applied to the masked addend
+    #[serde(default)]
+    pub addend_shift: u32,
+    /// Whether the masked addend is sign-extended
+    #[serde(default)]
+    pub addend_signed: bool,
+}
+
+impl CustomRelocation {
+    fn matches(&self, flags: RelocationFlags) -> bool {
+        match flags {
+            RelocationFlags::Elf { r_type } => r_type == self.r_type,
+            RelocationFlags::Coff { typ } => typ as u32 == self.r_type,
+            _ => false,
+        }
+    }
+
+    /// Pushes the relocation operand using the configured format.
+    pub fn push_args(&self, args: &mut Vec<ObjInsArg>) {
+        let (prefix, suffix) =
+            self.format.as_deref().and_then(|f| f.split_once("{}")).unwrap_or(("", ""));
+        if !prefix.is_empty() {
+            args.push(ObjInsArg::PlainText(prefix.to_string().into()));
+        }
+        args.push(ObjInsArg::Reloc);
+        if !suffix.is_empty() {
+            args.push(ObjInsArg::PlainText(suffix.to_string().into()));
+        }
+    }
+
+    /// Reads the implicit addend from the instruction word at `address`.
+    pub fn implicit_addend(
+        &self,
+        section: &ObjSection,
+        address: u64,
+        endianness: Endianness,
+    ) -> Result<i64> {
+        let Some(mask) = self.addend_mask else {
+            bail!("Custom relocation {} has no addend mask", self.name);
+        };
+        ensure!(mask != 0, "Custom relocation {} has an empty addend mask", self.name);
+        let data = section
+            .data
+            .get(address as usize..address as usize + 4)
+            .ok_or_else(|| anyhow::anyhow!("Relocation {} out of bounds", self.name))?;
+        let value = (endianness.read_u32_bytes(data.try_into()?) & mask) >> mask.trailing_zeros();
+        let mut addend = value as i64;
+        if self.addend_signed {
+            let bits = 32 - (mask >> mask.trailing_zeros()).leading_zeros();
+            addend = ((value as i64) << (64 - bits)) >> (64 - bits);
+        }
+        Ok(addend << self.addend_shift)
+    }
+}
+
+/// Maps an instruction form to an equivalent canonical one that it's compared as, so that
+/// compilers emitting different idioms for the same operation still match. The instruction
+/// is still displayed as it was disassembled.
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct InstructionRule {
+    /// Regex matched against the whole instruction, e.g. `addi (r\d+), (r\d+), 0x0`.
+    /// Operands are separated by `, ` and immediates are displayed in hex.
+    pub pattern: String,
+    /// Replacement, with `$1`-style capture group references, e.g. `mr $1, $2`
+    pub canonical: String,
+}
+
+/// Applies the [`InstructionRule`]s for an object's architecture.
+#[derive(Default)]
+pub struct InsNormalizer {
+    rules: Vec<(Regex, String)>,
+    /// Mnemonics of the canonical forms. Instructions with these mnemonics share an opcode
+    /// when aligning, so that canonicalized instructions align with the real ones.
+    mnemonics: Vec<String>,
+}
+
+impl InsNormalizer {
+    pub fn new(
+        architecture: Architecture,
+        arch: &dyn ObjArch,
+        config: &DiffObjConfig,
+    ) -> Result<Self> {
+        // Project rules for the architecture replace the defaults, so an empty list disables them
+        let rules = match config.instruction_rules.get(arch_key(architecture)) {
+            Some(rules) => {
+                rules.iter().map(|r| (r.pattern.as_str(), r.canonical.as_str())).collect()
+            }
+            None => arch.default_instruction_rules().to_vec(),
+        };
+        let rules = rules
+            .into_iter()
+            .map(|(pattern, canonical)| {
+                let regex = Regex::new(&format!("^(?:{pattern})$"))
+                    .with_context(|| format!("Invalid instruction pattern '{pattern}'"))?;
+                Ok((regex, canonical.to_string()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mut mnemonics = vec![];
+        for (_, canonical) in &rules {
+            match canonical.split_whitespace().next() {
+                Some(m) if !m.contains('$') && !mnemonics.iter().any(|s| s == m) => {
+                    mnemonics.push(m.to_string())
+                }
+                _ => {}
+            }
+        }
+        Ok(Self { rules, mnemonics })
+    }
+
+    /// Sets the instruction's canonical form if a rule matches it, returning the opcode to
+    /// align it by. Instructions with relocations or branch destinations are left as they are.
+    pub fn normalize(&self, ins: &mut ObjIns) -> u16 {
+        ins.canonical = self.canonical(ins);
+        let mnemonic = match &ins.canonical {
+            Some(canonical) => canonical.split(' ').next().unwrap_or_default(),
+            None => ins.mnemonic.as_str(),
+        };
+        match self.mnemonics.iter().position(|m| m == mnemonic) {
+            // Above any architecture's opcodes
+            Some(idx) => u16::MAX - idx as u16,
+            None => ins.op,
+        }
+    }
+
+    fn canonical(&self, ins: &ObjIns) -> Option<String> {
+        if self.rules.is_empty()
+            || ins.args.iter().any(|a| matches!(a, ObjInsArg::Reloc | ObjInsArg::BranchDest(_)))
+        {
+            return None;
+        }
+        let text = instruction_text(ins);
+        let (regex, canonical) = self.rules.iter().find(|(r, _)| r.is_match(&text))?;
+        let canonical = regex.replace(&text, canonical.as_str()).into_owned();
+        (canonical != text).then_some(canonical)
+    }
+}
+
+/// The text instructions are compared by when either has a canonical form.
+pub(crate) fn comparison_text(ins: &ObjIns) -> Cow<'_, str> {
+    match &ins.canonical {
+        Some(canonical) => Cow::Borrowed(canonical),
+        None => Cow::Owned(instruction_text(ins)),
+    }
+}
+
+/// The instruction as matched by [`InstructionRule::pattern`].
+fn instruction_text(ins: &ObjIns) -> String {
+    let mut text = ins.mnemonic.clone();
+    if !ins.args.is_empty() {
+        text.push(' ');
+    }
+    for arg in &ins.args {
+        match arg {
+            ObjInsArg::PlainText(s) if s.trim() == "," => text.push_str(", "),
+            ObjInsArg::PlainText(s) => text.push_str(s),
+            ObjInsArg::Arg(v) => text.push_str(&v.to_string()),
+            ObjInsArg::Reloc | ObjInsArg::BranchDest(_) => {}
+        }
+    }
+    text
+}
+
+pub fn find_custom_reloc(
+    relocations: &[CustomRelocation],
+    flags: RelocationFlags,
+) -> Option<&CustomRelocation> {
+    relocations.iter().find(|r| r.matches(flags))
+}
+
+/// Name of a relocation type not handled by the architecture.
+pub fn display_unknown_reloc(
+    relocations: &[CustomRelocation],
+    flags: RelocationFlags,
+) -> Cow<'static, str> {
+    if let Some(custom) = find_custom_reloc(relocations, flags) {
+        return Cow::Owned(custom.name.clone());
+    }
+    match flags {
+        RelocationFlags::Elf { r_type } => Cow::Owned(format!("<unknown reloc type {r_type}>")),
+        RelocationFlags::Coff { typ } => Cow::Owned(format!("<unknown reloc type {typ}>")),
+        _ => Cow::Owned(format!("<{flags:?}>")),
+    }
+}
+
+/// Pushes an operand for a relocation type not handled by the architecture.
+pub fn push_unknown_reloc(
+    args: &mut Vec<ObjInsArg>,
+    relocations: &[CustomRelocation],
+    flags: RelocationFlags,
+) {
+    if let Some(custom) = find_custom_reloc(relocations, flags) {
+        custom.push_args(args);
+    } else {
+        args.push(ObjInsArg::Reloc);
+        args.push(ObjInsArg::PlainText(
+            format!(" {}", display_unknown_reloc(relocations, flags)).into(),
+        ));
+    }
+}
+
+/// Key used for architecture-specific project configuration.
+fn arch_key(architecture: Architecture) -> &'static str {
+    match architecture {
+        Architecture::PowerPc => "ppc",
+        Architecture::Mips => "mips",
+        Architecture::I386 | Architecture::X86_64 => "x86",
+        Architecture::Arm => "arm",
+        _ => "",
+    }
+}
+
+/// The PowerPC architecture, for GameCube/Wii images that aren't object files.
+#[cfg(feature = "ppc")]
+pub fn new_ppc_arch(config: &DiffObjConfig) -> Box<dyn ObjArch> {
+    let custom_relocations =
+        config.custom_relocations.get(arch_key(Architecture::PowerPc)).cloned().unwrap_or_default();
+    Box::new(ppc::ObjArchPpc::with_custom_relocations(custom_relocations))
+}
+
+pub fn new_arch(object: &object::File, config: &DiffObjConfig) -> Result<Box<dyn ObjArch>> {
+    let custom_relocations =
+        config.custom_relocations.get(arch_key(object.architecture())).cloned().unwrap_or_default();
     Ok(match object.architecture() {
         #[cfg(feature = "ppc")]
-        Architecture::PowerPc => Box::new(ppc::ObjArchPpc::new(object)?),
+        Architecture::PowerPc => Box::new(ppc::ObjArchPpc::new(object, custom_relocations)?),
         #[cfg(feature = "mips")]
-        Architecture::Mips => Box::new(mips::ObjArchMips::new(object)?),
+        Architecture::Mips => Box::new(mips::ObjArchMips::new(object, custom_relocations)?),
         #[cfg(feature = "x86")]
-        Architecture::I386 | Architecture::X86_64 => Box::new(x86::ObjArchX86::new(object)?),
+        Architecture::I386 | Architecture::X86_64 => {
+            Box::new(x86::ObjArchX86::new(object, custom_relocations)?)
+        }
         #[cfg(feature = "arm")]
-        Architecture::Arm => Box::new(arm::ObjArchArm::new(object)?),
+        Architecture::Arm => Box::new(arm::ObjArchArm::new(object, custom_relocations)?),
         arch => bail!("Unsupported architecture: {arch:?}"),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn custom_relocations() -> Vec<CustomRelocation> {
+        vec![CustomRelocation {
+            r_type: 250,
+            name: "R_PPC_CUSTOM_LO".to_string(),
+            format: Some("{}@l".to_string()),
+            ..Default::default()
+        }]
+    }
+
+    #[test]
+    fn custom_relocation_display_name() {
+        let relocations = custom_relocations();
+        let custom = RelocationFlags::Elf { r_type: 250 };
+        assert_eq!(display_unknown_reloc(&relocations, custom), "R_PPC_CUSTOM_LO");
+        assert_eq!(
+            display_unknown_reloc(&relocations, RelocationFlags::Elf { r_type: 251 }),
+            "<unknown reloc type 251>"
+        );
+
+        let mut args = vec![];
+        push_unknown_reloc(&mut args, &relocations, custom);
+        assert_eq!(args, vec![ObjInsArg::Reloc, ObjInsArg::PlainText("@l".into())]);
+    }
+
+    #[test]
+    #[cfg(feature = "ppc")]
+    fn custom_relocation_display_name_in_arch() {
+        let arch = ppc::ObjArchPpc::with_custom_relocations(custom_relocations());
+        assert_eq!(arch.display_reloc(RelocationFlags::Elf { r_type: 250 }), "R_PPC_CUSTOM_LO");
+        // Known relocations aren't affected
+        assert_eq!(
+            arch.display_reloc(RelocationFlags::Elf { r_type: object::elf::R_PPC_ADDR16_LO }),
+            "R_PPC_ADDR16_LO"
+        );
+    }
+}
@@ -1,11 +1,14 @@
 use std::{borrow::Cow, collections::BTreeMap};
 
 use anyhow::{bail, Result};
-use object::{elf, File, Relocation, RelocationFlags};
+use object::{elf, File, Object, Relocation, RelocationFlags};
 use ppc750cl::{Argument, InsIter, GPR};
 
 use crate::{
-    arch::{ObjArch, ProcessCodeResult},
+    arch::{
+        display_unknown_reloc, find_custom_reloc, push_unknown_reloc, CustomRelocation, ObjArch,
+        ProcessCodeResult,
+    },
     diff::DiffObjConfig,
     obj::{ObjIns, ObjInsArg, ObjInsArgValue, ObjReloc, ObjSection},
 };
@@ -22,10 +25,18 @@ fn is_rel_abs_arg(arg: &Argument) -> bool {
 
 fn is_offset_arg(arg: &Argument) -> bool { matches!(arg, Argument::Offset(_)) }
 
-pub struct ObjArchPpc {}
+pub struct ObjArchPpc {
+    custom_relocations: Vec<CustomRelocation>,
+}
 
 impl ObjArchPpc {
-    pub fn new(_file: &File) -> Result<Self> { Ok(Self {}) }
+    pub fn new(_file: &File, custom_relocations: Vec<CustomRelocation>) -> Result<Self> {
+        Ok(Self::with_custom_relocations(custom_relocations))
+    }
+
+    pub fn with_custom_relocations(custom_relocations: Vec<CustomRelocation>) -> Self {
+        Self { custom_relocations }
+    }
 }
 
 impl ObjArch for ObjArchPpc {
@@ -56,8 +67,10 @@ impl ObjArch for ObjArchPpc {
                 };
             }
 
-            let orig = ins.basic().to_string();
-            let simplified = ins.simplified();
+            let basic = ins.basic();
+            let orig = basic.to_string();
+            // Only affects display: ops are compared by opcode and both sides share the config
+            let simplified = if config.ppc_simplified_mnemonics { ins.simplified() } else { basic };
             let formatted = simplified.to_string();
 
             let mut reloc_arg = None;
@@ -74,6 +87,9 @@ impl ObjArch for ObjArchPpc {
                     } => {
                         reloc_arg = simplified.args.iter().rposition(is_rel_abs_arg);
                     }
+                    flags if find_custom_reloc(&self.custom_relocations, flags).is_some() => {
+                        reloc_arg = simplified.args.iter().rposition(is_rel_abs_arg);
+                    }
                     _ => {}
                 }
             }
@@ -88,7 +104,7 @@ impl ObjArch for ObjArchPpc {
 
                 if reloc_arg == Some(idx) {
                     let reloc = reloc.unwrap();
-                    push_reloc(&mut args, reloc)?;
+                    push_reloc(&mut args, reloc, &self.custom_relocations)?;
                     // For @sda21, we can omit the register argument
                     if matches!(reloc.flags, RelocationFlags::Elf { r_type: elf::R_PPC_EMB_SDA21 })
                         // Sanity check: the next argument should be r0
@@ -143,6 +159,7 @@ impl ObjArch for ObjArchPpc {
                 line,
                 formatted,
                 orig: Some(orig),
+                canonical: None,
             });
         }
         Ok(ProcessCodeResult { ops, insts })
@@ -150,11 +167,14 @@ impl ObjArch for ObjArchPpc {
 
     fn implcit_addend(
         &self,
-        _file: &File<'_>,
-        _section: &ObjSection,
+        file: &File<'_>,
+        section: &ObjSection,
         address: u64,
         reloc: &Relocation,
     ) -> Result<i64> {
+        if let Some(custom) = find_custom_reloc(&self.custom_relocations, reloc.flags()) {
+            return custom.implicit_addend(section, address, file.endianness());
+        }
         bail!("Unsupported PPC implicit relocation {:#x}:{:?}", address, reloc.flags())
     }
 
@@ -173,14 +193,83 @@ impl ObjArch for ObjArchPpc {
                 elf::R_PPC_UADDR32 => Cow::Borrowed("R_PPC_UADDR32"),
                 elf::R_PPC_REL24 => Cow::Borrowed("R_PPC_REL24"),
                 elf::R_PPC_REL14 => Cow::Borrowed("R_PPC_REL14"),
-                _ => Cow::Owned(format!("<{flags:?}>")),
+                _ => display_unknown_reloc(&self.custom_relocations, flags),
             },
-            _ => Cow::Owned(format!("<{flags:?}>")),
+            _ => display_unknown_reloc(&self.custom_relocations, flags),
         }
     }
+
+    fn instruction_cost(&self, ins: &ObjIns) -> Option<u32> {
+        // Approximate Gekko/Broadway latencies
+        let mnemonic = ins.mnemonic.trim_end_matches(['.', '+', '-']);
+        Some(match mnemonic {
+            "mullw" | "mullwo" | "mulhw" | "mulhwu" => 5,
+            "mulli" => 3,
+            "divw" | "divwu" | "divwo" | "divwuo" => 19,
+            "fdiv" => 31,
+            "fdivs" => 17,
+            "fres" => 10,
+            "fadd" | "fadds" | "fsub" | "fsubs" | "fmul" | "fmuls" | "fmadd" | "fmadds"
+            | "fmsub" | "fmsubs" | "fnmadd" | "fnmadds" | "fnmsub" | "fnmsubs" => 3,
+            "mtctr" | "mtlr" | "mflr" | "mfctr" | "mfcr" | "mtcrf" => 2,
+            "sync" | "isync" => 3,
+            m if m.starts_with('l') && !matches!(m, "li" | "lis") => 2,
+            _ => 1,
+        })
+    }
+
+    fn default_instruction_rules(&self) -> &'static [(&'static str, &'static str)] {
+        &[
+            // Register moves
+            (r"addi (r\d+), (r[1-9]\d*), 0x0", "mr $1, $2"),
+            (r"ori (r\d+), (r\d+), 0x0", "mr $1, $2"),
+            // Zeroing a register
+            (r"lis (r\d+), 0x0", "li $1, 0x0"),
+        ]
+    }
+
+    fn is_address_arg(&self, insts: &[ObjIns], ins_idx: usize, arg_idx: usize) -> bool {
+        // The upper half of an address loaded with lis, when the next instruction reading the
+        // register adds the lower half with addi/ori or has a relocation (e.g. `@l` loads)
+        let ins = &insts[ins_idx];
+        if ins.mnemonic != "lis"
+            || !matches!(
+                ins.args.get(arg_idx),
+                Some(ObjInsArg::Arg(ObjInsArgValue::Signed(_) | ObjInsArgValue::Unsigned(_)))
+            )
+        {
+            return false;
+        }
+        let Some(ObjInsArg::Arg(ObjInsArgValue::Opaque(reg))) = ins.args.first() else {
+            return false;
+        };
+        let reads_reg = |ins: &&ObjIns| {
+            ins.args.iter().skip(1).any(
+                |arg| matches!(arg, ObjInsArg::Arg(ObjInsArgValue::Opaque(name)) if name == reg),
+            )
+        };
+        insts[ins_idx + 1..].iter().find(reads_reg).is_some_and(|next| {
+            next.reloc.is_some() || matches!(next.mnemonic.as_str(), "addi" | "ori")
+        })
+    }
+
+    fn is_register_arg(&self, ins: &ObjIns, arg_idx: usize) -> bool {
+        // General purpose, floating point and condition registers, but not r1 or r2
+        let Some(ObjInsArg::Arg(ObjInsArgValue::Opaque(name))) = ins.args.get(arg_idx) else {
+            return false;
+        };
+        ["r", "f", "cr"].iter().any(|prefix| {
+            name.strip_prefix(prefix)
+                .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        }) && !matches!(name.as_ref(), "r1" | "r2")
+    }
 }
 
-fn push_reloc(args: &mut Vec<ObjInsArg>, reloc: &ObjReloc) -> Result<()> {
+fn push_reloc(
+    args: &mut Vec<ObjInsArg>,
+    reloc: &ObjReloc,
+    custom_relocations: &[CustomRelocation],
+) -> Result<()> {
     match reloc.flags {
         RelocationFlags::Elf { r_type } => match r_type {
             elf::R_PPC_ADDR16_LO => {
@@ -202,9 +291,122 @@ fn push_reloc(args: &mut Vec<ObjInsArg>, reloc: &ObjReloc) -> Result<()> {
             elf::R_PPC_ADDR32 | elf::R_PPC_UADDR32 | elf::R_PPC_REL24 | elf::R_PPC_REL14 => {
                 args.push(ObjInsArg::Reloc);
             }
-            _ => bail!("Unsupported ELF PPC relocation type {r_type}"),
+            _ => push_unknown_reloc(args, custom_relocations, reloc.flags),
         },
         flags => bail!("Unsupported PPC relocation kind: {flags:?}"),
     };
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process_with_relocs(
+        config: &DiffObjConfig,
+        code: &[u32],
+        relocations: &[ObjReloc],
+    ) -> ProcessCodeResult {
+        let code = code.iter().flat_map(|c| c.to_be_bytes()).collect::<Vec<_>>();
+        ObjArchPpc::with_custom_relocations(vec![])
+            .process_code(0, &code, 0, relocations, &BTreeMap::new(), config)
+            .unwrap()
+    }
+
+    fn process(config: &DiffObjConfig, code: &[u32]) -> ProcessCodeResult {
+        process_with_relocs(config, code, &[])
+    }
+
+    /// Whether the immediate of the first instruction, a `lis`, is treated as an address.
+    fn lis_is_address(code: &[u32], relocations: &[ObjReloc]) -> bool {
+        let result = process_with_relocs(&DiffObjConfig::default(), code, relocations);
+        // The immediate follows the register and separator
+        ObjArchPpc::with_custom_relocations(vec![]).is_address_arg(&result.insts, 0, 2)
+    }
+
+    fn addr16_lo(address: u64) -> ObjReloc {
+        ObjReloc {
+            flags: RelocationFlags::Elf { r_type: elf::R_PPC_ADDR16_LO },
+            address,
+            target: crate::obj::ObjSymbol {
+                name: "data".to_string(),
+                demangled_name: None,
+                has_extab: false,
+                extab_name: None,
+                extabindex_name: None,
+                address: 0,
+                section_address: 0,
+                size: 4,
+                size_known: true,
+                flags: Default::default(),
+                addend: 0,
+                virtual_address: None,
+                normalized_name: None,
+            },
+            target_section: None,
+        }
+    }
+
+    const LIS_R3: u32 = 0x3c608000; // lis r3, 0x8000
+
+    #[test]
+    fn lis_address_pairs() {
+        // addi r3, r3, 0x1234
+        assert!(lis_is_address(&[LIS_R3, 0x38631234], &[]));
+        // ori r3, r3, 0x1234
+        assert!(lis_is_address(&[LIS_R3, 0x60631234], &[]));
+        // lwz r4, data@l(r3)
+        assert!(lis_is_address(&[LIS_R3, 0x80830000], &[addr16_lo(4)]));
+        // li r4, 0x0 doesn't read r3, so the following addi pairs with the lis
+        assert!(lis_is_address(&[LIS_R3, 0x38800000, 0x38631234], &[]));
+    }
+
+    #[test]
+    fn lis_without_address_pair() {
+        // lwz r4, 0x10(r3) without a relocation
+        assert!(!lis_is_address(&[LIS_R3, 0x80830010], &[]));
+        // mullw r5, r3, r4
+        assert!(!lis_is_address(&[LIS_R3, 0x7ca321d6], &[]));
+        // addi r4, r5, 0x1 never reads r3
+        assert!(!lis_is_address(&[LIS_R3, 0x38850001], &[]));
+        // li r3, 0x1234 followed by addi r3, r3, 0x1234
+        assert!(!lis_is_address(&[0x38601234, 0x38631234], &[]));
+        // The register operand itself
+        let result = process(&DiffObjConfig::default(), &[LIS_R3, 0x38631234]);
+        assert!(!ObjArchPpc::with_custom_relocations(vec![]).is_address_arg(&result.insts, 0, 0));
+    }
+
+    #[test]
+    fn simplified_mnemonics_only_affect_display() {
+        // addi r3, r0, 0x1
+        let code = 0x38600001;
+        let mut config = DiffObjConfig::default();
+        let simplified = process(&config, &[code]);
+        assert_eq!(simplified.insts[0].mnemonic, "li");
+        assert_eq!(simplified.insts[0].formatted, "li r3, 0x1");
+        assert_eq!(simplified.insts[0].orig.as_deref(), Some("addi r3, r0, 0x1"));
+
+        config.ppc_simplified_mnemonics = false;
+        let basic = process(&config, &[code]);
+        assert_eq!(basic.insts[0].mnemonic, "addi");
+        assert_eq!(basic.insts[0].formatted, "addi r3, r0, 0x1");
+        assert_eq!(basic.insts[0].orig.as_deref(), Some("addi r3, r0, 0x1"));
+        assert_eq!(simplified.ops, basic.ops);
+    }
+
+    #[test]
+    fn total_cost_sums_latencies() {
+        let result = process(&DiffObjConfig::default(), &[
+            0x38600001, // li r3, 0x1
+            0x80830000, // lwz r4, 0x0(r3)
+            0x7ca321d6, // mullw r5, r3, r4
+            0x7cc523d6, // divw r6, r5, r4
+            0x4e800020, // blr
+        ]);
+        let mnemonics = result.insts.iter().map(|ins| ins.mnemonic.as_str()).collect::<Vec<_>>();
+        assert_eq!(mnemonics, ["li", "lwz", "mullw", "divw", "blr"]);
+        let arch = ObjArchPpc::with_custom_relocations(vec![]);
+        assert_eq!(crate::arch::total_cost(&arch, &result.insts), Some(1 + 2 + 5 + 19 + 1));
+        assert_eq!(crate::arch::total_cost(&arch, &[]), Some(0));
+    }
+}
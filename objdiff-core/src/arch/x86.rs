@@ -3,13 +3,15 @@ use std::{borrow::Cow, collections::BTreeMap};
 use anyhow::{anyhow, bail, ensure, Result};
 use iced_x86::{
     Decoder, DecoderOptions, DecoratorKind, Formatter, FormatterOutput, FormatterTextKind,
-    GasFormatter, Instruction, IntelFormatter, MasmFormatter, NasmFormatter, NumberKind, OpKind,
-    PrefixKind, Register,
+    GasFormatter, Instruction, IntelFormatter, MasmFormatter, Mnemonic, NasmFormatter, NumberKind,
+    OpKind, PrefixKind, Register,
 };
 use object::{pe, Endian, Endianness, File, Object, Relocation, RelocationFlags};
 
 use crate::{
-    arch::{ObjArch, ProcessCodeResult},
+    arch::{
+        display_unknown_reloc, find_custom_reloc, CustomRelocation, ObjArch, ProcessCodeResult,
+    },
     diff::{DiffObjConfig, X86Formatter},
     obj::{ObjIns, ObjInsArg, ObjInsArgValue, ObjReloc, ObjSection},
 };
@@ -17,11 +19,16 @@ use crate::{
 pub struct ObjArchX86 {
     bits: u32,
     endianness: Endianness,
+    custom_relocations: Vec<CustomRelocation>,
 }
 
 impl ObjArchX86 {
-    pub fn new(object: &File) -> Result<Self> {
-        Ok(Self { bits: if object.is_64() { 64 } else { 32 }, endianness: object.endianness() })
+    pub fn new(object: &File, custom_relocations: Vec<CustomRelocation>) -> Result<Self> {
+        Ok(Self {
+            bits: if object.is_64() { 64 } else { 32 },
+            endianness: object.endianness(),
+            custom_relocations,
+        })
     }
 }
 
@@ -58,6 +65,7 @@ impl ObjArch for ObjArchX86 {
                 line: None,
                 formatted: String::new(),
                 orig: None,
+                canonical: None,
             },
             error: None,
             ins_operands: vec![],
@@ -83,6 +91,7 @@ impl ObjArch for ObjArchX86 {
                 line,
                 formatted: String::new(),
                 orig: None,
+                canonical: None,
             };
             // Run the formatter, which will populate output.ins
             formatter.format(&instruction, &mut output);
@@ -138,7 +147,10 @@ impl ObjArch for ObjArchX86 {
                 let data = section.data[address as usize..address as usize + 4].try_into()?;
                 Ok(self.endianness.read_i32_bytes(data) as i64)
             }
-            flags => bail!("Unsupported x86 implicit relocation {flags:?}"),
+            flags => match find_custom_reloc(&self.custom_relocations, flags) {
+                Some(custom) => custom.implicit_addend(section, address, self.endianness),
+                None => bail!("Unsupported x86 implicit relocation {flags:?}"),
+            },
         }
     }
 
@@ -157,10 +169,79 @@ impl ObjArch for ObjArchX86 {
             RelocationFlags::Coff { typ } => match typ {
                 pe::IMAGE_REL_I386_DIR32 => Cow::Borrowed("IMAGE_REL_I386_DIR32"),
                 pe::IMAGE_REL_I386_REL32 => Cow::Borrowed("IMAGE_REL_I386_REL32"),
-                _ => Cow::Owned(format!("<{flags:?}>")),
+                _ => display_unknown_reloc(&self.custom_relocations, flags),
             },
-            _ => Cow::Owned(format!("<{flags:?}>")),
+            _ => display_unknown_reloc(&self.custom_relocations, flags),
+        }
+    }
+
+    fn is_padding_nop(&self, ins: &ObjIns) -> bool {
+        // MSVC pads functions with int3
+        ins.op == Mnemonic::Nop as u16 || ins.op == Mnemonic::Int3 as u16
+    }
+
+    fn instruction_cost(&self, ins: &ObjIns) -> Option<u32> {
+        // Approximate Pentium Pro latencies
+        Some(match ins.op {
+            op if op == Mnemonic::Imul as u16 => 4,
+            op if op == Mnemonic::Mul as u16 => 5,
+            op if op == Mnemonic::Div as u16 || op == Mnemonic::Idiv as u16 => 40,
+            op if op == Mnemonic::Fdiv as u16 || op == Mnemonic::Fdivp as u16 => 38,
+            op if op == Mnemonic::Fmul as u16 || op == Mnemonic::Fmulp as u16 => 5,
+            op if op == Mnemonic::Fadd as u16 || op == Mnemonic::Faddp as u16 => 3,
+            op if op == Mnemonic::Fsqrt as u16 => 69,
+            op if op == Mnemonic::Call as u16 || op == Mnemonic::Ret as u16 => 2,
+            _ => 1,
+        })
+    }
+
+    fn is_address_arg(&self, insts: &[ObjIns], ins_idx: usize, arg_idx: usize) -> bool {
+        // A memory operand that is only a displacement, e.g. `[12345678h]`
+        let ins = &insts[ins_idx];
+        let text = |idx: Option<usize>| match idx.and_then(|i| ins.args.get(i)) {
+            Some(ObjInsArg::PlainText(s)) => s.as_ref(),
+            _ => "",
+        };
+        matches!(
+            ins.args.get(arg_idx),
+            Some(ObjInsArg::Arg(ObjInsArgValue::Signed(_) | ObjInsArgValue::Unsigned(_)))
+        ) && text(arg_idx.checked_sub(1)).ends_with('[')
+            && text(Some(arg_idx + 1)).starts_with(']')
+    }
+
+    fn is_register_arg(&self, ins: &ObjIns, arg_idx: usize) -> bool {
+        // General purpose and SSE registers, but not the stack and frame pointers
+        let Some(ObjInsArg::Arg(ObjInsArgValue::Opaque(name))) = ins.args.get(arg_idx) else {
+            return false;
+        };
+        let name = name.to_ascii_lowercase();
+        if let Some(n) = name.strip_prefix("xmm") {
+            return n.parse::<u8>().is_ok();
+        }
+        if let Some(n) = name.strip_prefix('r').map(|n| n.trim_end_matches(['d', 'w', 'b'])) {
+            if n.parse::<u8>().is_ok_and(|n| (8..16).contains(&n)) {
+                return true;
+            }
         }
+        let base = name.strip_prefix(['e', 'r']).filter(|b| b.len() == 2).unwrap_or(&name);
+        matches!(
+            base,
+            "ax" | "bx"
+                | "cx"
+                | "dx"
+                | "si"
+                | "di"
+                | "al"
+                | "bl"
+                | "cl"
+                | "dl"
+                | "ah"
+                | "bh"
+                | "ch"
+                | "dh"
+                | "sil"
+                | "dil"
+        )
     }
 }
 
@@ -0,0 +1,312 @@
+use std::{
+    io::{BufRead, BufReader, Read},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Sender,
+        Arc, Condvar, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+
+use crate::config::BuildStatusConfig;
+
+pub struct BuildStatus {
+    pub success: bool,
+    pub cmdline: String,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl Default for BuildStatus {
+    fn default() -> Self {
+        BuildStatus {
+            success: true,
+            cmdline: String::new(),
+            stdout: String::new(),
+            stderr: String::new(),
+        }
+    }
+}
+
+/// A line of output from a running build, including its line ending.
+pub struct BuildOutputLine {
+    pub text: String,
+    pub stderr: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct BuildConfig {
+    pub project_dir: Option<PathBuf>,
+    pub custom_make: Option<String>,
+    pub custom_args: Option<Vec<String>>,
+    pub selected_wsl_distro: Option<String>,
+    pub wsl_mount_prefix: Option<String>,
+    /// Maximum number of build processes running at once
+    pub max_parallel_builds: usize,
+    /// Builds running longer than this are killed
+    pub timeout: Option<Duration>,
+    /// Set to kill running builds
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+/// Number of parallel builds when unconfigured: one per CPU.
+pub fn default_parallel_builds() -> usize {
+    std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Counts running build processes across all jobs, so that builds beyond the limit wait for
+/// a free slot instead of spawning.
+struct BuildSlots {
+    running: Mutex<usize>,
+    freed: Condvar,
+}
+
+static BUILD_SLOTS: BuildSlots = BuildSlots { running: Mutex::new(0), freed: Condvar::new() };
+
+struct BuildSlot;
+
+impl BuildSlots {
+    fn acquire(&self, limit: usize) -> BuildSlot {
+        // The count is only changed by these short critical sections, so it stays valid
+        // even if a build thread panicked while waiting
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        while *running >= limit.max(1) {
+            running = self.freed.wait(running).unwrap_or_else(|e| e.into_inner());
+        }
+        *running += 1;
+        BuildSlot
+    }
+}
+
+impl Drop for BuildSlot {
+    fn drop(&mut self) {
+        *BUILD_SLOTS.running.lock().unwrap_or_else(|e| e.into_inner()) -= 1;
+        BUILD_SLOTS.freed.notify_one();
+    }
+}
+
+/// Builds `arg`, passing `extra_args` after it. Output lines are sent to `output` as they're
+/// printed.
+pub fn run_make(
+    config: &BuildConfig,
+    arg: &Path,
+    extra_args: &[String],
+    output: Option<Sender<BuildOutputLine>>,
+) -> BuildStatus {
+    let Some(cwd) = &config.project_dir else {
+        return BuildStatus {
+            success: false,
+            stderr: "Missing project dir".to_string(),
+            ..Default::default()
+        };
+    };
+    let _slot = BUILD_SLOTS.acquire(config.max_parallel_builds);
+    match run_make_cmd(config, cwd, arg, extra_args, output) {
+        Ok(status) => status,
+        Err(e) => BuildStatus { success: false, stderr: e.to_string(), ..Default::default() },
+    }
+}
+
+pub const DEFAULT_WSL_MOUNT_PREFIX: &str = "/mnt";
+
+/// Translates a Windows path to its location inside a WSL distro: paths within the distro's
+/// `\\wsl.localhost\{distro}` share become absolute, and drive paths like `C:\foo` are mapped
+/// to `{mount_prefix}/c/foo`. Relative paths only have their separators converted.
+pub fn wsl_path(path: &Path, distro: &str, mount_prefix: &str) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let path = path.strip_prefix("//?/").unwrap_or(&path);
+    for share in ["//wsl.localhost/", "//wsl$/"] {
+        if let Some(rest) = path.strip_prefix(share).and_then(|p| p.strip_prefix(distro)) {
+            if rest.is_empty() || rest.starts_with('/') {
+                return format!("/{}", rest.trim_start_matches('/'));
+            }
+        }
+    }
+    let mut chars = path.chars();
+    if let (Some(drive), Some(':')) = (chars.next(), chars.next()) {
+        if drive.is_ascii_alphabetic() {
+            let rest = chars.as_str().trim_start_matches('/');
+            let prefix = mount_prefix.trim_end_matches('/');
+            let drive = drive.to_ascii_lowercase();
+            return if rest.is_empty() {
+                format!("{prefix}/{drive}")
+            } else {
+                format!("{prefix}/{drive}/{rest}")
+            };
+        }
+    }
+    path.to_string()
+}
+
+fn run_make_cmd(
+    config: &BuildConfig,
+    cwd: &Path,
+    arg: &Path,
+    extra_args: &[String],
+    output: Option<Sender<BuildOutputLine>>,
+) -> Result<BuildStatus> {
+    let make = config.custom_make.as_deref().unwrap_or("make");
+    let make_args = config.custom_args.as_deref().unwrap_or(&[]);
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut command = Command::new(make);
+        command.current_dir(cwd).args(make_args).arg(arg).args(extra_args);
+        command
+    };
+    #[cfg(windows)]
+    let mut command = {
+        use std::os::windows::process::CommandExt;
+
+        use path_slash::PathExt;
+        let mut command = if config.selected_wsl_distro.is_some() {
+            Command::new("wsl")
+        } else {
+            Command::new(make)
+        };
+        if let Some(distro) = &config.selected_wsl_distro {
+            let mount_prefix =
+                config.wsl_mount_prefix.as_deref().unwrap_or(DEFAULT_WSL_MOUNT_PREFIX);
+            command
+                .arg("--cd")
+                .arg(wsl_path(cwd, distro, mount_prefix))
+                .arg("-d")
+                .arg(distro)
+                .arg("--")
+                .arg(make)
+                .args(make_args)
+                .arg(wsl_path(arg, distro, mount_prefix))
+                .args(extra_args.iter().map(|a| {
+                    // Convert arguments that look like Windows paths
+                    if a.contains('\\') {
+                        Path::new(a).to_slash_lossy().into_owned()
+                    } else {
+                        a.clone()
+                    }
+                }));
+        } else {
+            command
+                .current_dir(cwd)
+                .args(make_args)
+                .arg(arg.to_slash_lossy().as_ref())
+                .args(extra_args);
+        }
+        command.creation_flags(winapi::um::winbase::CREATE_NO_WINDOW);
+        command
+    };
+    let mut cmdline = shell_escape::escape(command.get_program().to_string_lossy()).into_owned();
+    for arg in command.get_args() {
+        cmdline.push(' ');
+        cmdline.push_str(shell_escape::escape(arg.to_string_lossy()).as_ref());
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow!("Failed to execute build: {e}"))?;
+    let stdout = Arc::new(Mutex::new(String::new()));
+    let stderr = Arc::new(Mutex::new(String::new()));
+    let readers = [
+        read_pipe(child.stdout.take(), stdout.clone(), output.clone().map(|tx| (tx, false))),
+        read_pipe(child.stderr.take(), stderr.clone(), output.map(|tx| (tx, true))),
+    ];
+    let deadline = config.timeout.map(|timeout| Instant::now() + timeout);
+    let stopped = loop {
+        if let Some(status) = child.try_wait()? {
+            break Ok(status);
+        }
+        if config.cancel.as_ref().is_some_and(|cancel| cancel.load(Ordering::Relaxed)) {
+            break Err("Build cancelled".to_string());
+        }
+        if let (Some(deadline), Some(timeout)) = (deadline, config.timeout) {
+            if Instant::now() >= deadline {
+                break Err(format!("Build timed out after {}s", timeout.as_secs()));
+            }
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+    let success = match stopped {
+        Ok(status) => {
+            for reader in readers.into_iter().flatten() {
+                reader.join().ok();
+            }
+            status.success()
+        }
+        Err(message) => {
+            // Processes started by the build may keep the pipes open, so don't wait for them
+            kill_child(&mut child);
+            let mut stderr = stderr.lock().unwrap();
+            if !stderr.is_empty() && !stderr.ends_with('\n') {
+                stderr.push('\n');
+            }
+            stderr.push_str(&message);
+            false
+        }
+    };
+    let stdout = std::mem::take(&mut *stdout.lock().unwrap());
+    let stderr = std::mem::take(&mut *stderr.lock().unwrap());
+    Ok(BuildStatus { success, cmdline, stdout, stderr })
+}
+
+/// How often a running build is checked for exit, timeout and cancellation.
+pub const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Collects a child's output on a background thread, forwarding each line to `sender` with
+/// whether it came from stderr.
+fn read_pipe<R: Read + Send + 'static>(
+    pipe: Option<R>,
+    output: Arc<Mutex<String>>,
+    sender: Option<(Sender<BuildOutputLine>, bool)>,
+) -> Option<JoinHandle<()>> {
+    let mut reader = BufReader::new(pipe?);
+    Some(std::thread::spawn(move || {
+        let mut line = vec![];
+        while matches!(reader.read_until(b'\n', &mut line), Ok(n) if n > 0) {
+            let text = String::from_utf8_lossy(&line).into_owned();
+            output.lock().unwrap().push_str(&text);
+            if let Some((sender, stderr)) = &sender {
+                sender.send(BuildOutputLine { text, stderr: *stderr }).ok();
+            }
+            line.clear();
+        }
+    }))
+}
+
+fn kill_child(child: &mut Child) {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+
+        // Kill the whole process tree, so that nothing started through wsl or make survives
+        Command::new("taskkill")
+            .args(["/T", "/F", "/PID"])
+            .arg(child.id().to_string())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .creation_flags(winapi::um::winbase::CREATE_NO_WINDOW)
+            .status()
+            .ok();
+    }
+    child.kill().ok();
+    child.wait().ok();
+}
+
+/// Overrides the exit code's verdict with the object's build status patterns.
+pub fn apply_build_status(status: &mut BuildStatus, config: &BuildStatusConfig) {
+    if status.cmdline.is_empty() {
+        // The build didn't run
+        return;
+    }
+    match config.build_success(status.success, &status.stdout, &status.stderr) {
+        Ok(success) => status.success = success,
+        Err(e) => {
+            status.success = false;
+            status.stderr.push_str(&format!("\n{e:#}"));
+        }
+    }
+}
@@ -1,13 +1,20 @@
 use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
     fs::File,
     io::Read,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
 };
 
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use filetime::FileTime;
 use globset::{Glob, GlobSet, GlobSetBuilder};
 
+use crate::{
+    arch::{CustomRelocation, InstructionRule},
+    diff::{Demangler, SymbolNameRule},
+};
+
 #[inline]
 fn bool_true() -> bool { true }
 
@@ -27,10 +34,64 @@ pub struct ProjectConfig {
     pub build_base: bool,
     #[serde(default)]
     pub build_target: bool,
+    /// Build the target and base objects at the same time
+    #[serde(default = "bool_true")]
+    pub build_parallel: bool,
     #[serde(default)]
     pub watch_patterns: Option<Vec<Glob>>,
     #[serde(default, alias = "units")]
     pub objects: Vec<ProjectObject>,
+    /// Globs matched against paths in `target_dir` and `base_dir` to discover objects
+    #[serde(default)]
+    pub object_patterns: Option<Vec<Glob>>,
+    /// Additional relocation types, keyed by architecture (`ppc`, `mips`, `x86`, `arm`)
+    #[serde(default)]
+    pub custom_relocations: Option<BTreeMap<String, Vec<CustomRelocation>>>,
+    /// Symbol name rewrites applied before pairing symbols between objects
+    #[serde(default)]
+    pub symbol_name_rules: Option<Vec<SymbolNameRule>>,
+    /// Additional instruction normalization rules, keyed by architecture
+    #[serde(default)]
+    pub instruction_rules: Option<BTreeMap<String, Vec<InstructionRule>>>,
+    /// Directories searched for separate debug files, relative to the project directory
+    #[serde(default)]
+    pub debug_search_paths: Option<Vec<PathBuf>>,
+    /// Further config files, relative to this one, merged into this config
+    #[serde(default)]
+    pub include: Option<Vec<PathBuf>>,
+    /// How fields set by more than one of the merged configs are resolved
+    #[serde(default)]
+    pub conflict_policy: ConflictPolicy,
+    /// Demangler for symbol names, see [`Demangler::from_name`]
+    #[serde(default)]
+    pub demangler: Option<String>,
+}
+
+impl ProjectConfig {
+    /// The configured demangler. Unknown names fall back to the default with a warning.
+    pub fn demangler(&self) -> Demangler {
+        let Some(name) = &self.demangler else {
+            return Demangler::default();
+        };
+        Demangler::from_name(name).unwrap_or_else(|| {
+            log::warn!("Unknown demangler '{name}', expected itanium, msvc, mwcc or none");
+            Demangler::default()
+        })
+    }
+
+    /// Checks every object's files, see [`ProjectObject::validate`].
+    pub fn validate(&self, project_dir: &Path) -> Vec<String> {
+        let target_dir = self.target_dir.as_ref().map(|p| project_dir.join(p));
+        let base_dir = self.base_dir.as_ref().map(|p| project_dir.join(p));
+        self.objects
+            .iter()
+            .flat_map(|object| {
+                let mut object = object.clone();
+                object.resolve_paths(project_dir, target_dir.as_deref(), base_dir.as_deref());
+                object.validate(project_dir)
+            })
+            .collect()
+    }
 }
 
 #[derive(Default, Clone, serde::Deserialize)]
@@ -49,9 +110,26 @@ pub struct ProjectObject {
     pub complete: Option<bool>,
     #[serde(default)]
     pub scratch: Option<ScratchConfig>,
+    #[serde(default)]
+    pub source_path: Option<PathBuf>,
+    #[serde(default)]
+    pub build_status: Option<BuildStatusConfig>,
+    /// Extra build arguments passed after the object path
+    #[serde(default)]
+    pub make_args: Option<Vec<String>>,
+    /// Pairs of (target name, base name) for symbols that should be diffed against each other
+    /// despite their names differing, e.g. auto-named symbols in the target
+    #[serde(default)]
+    pub symbol_mappings: Option<Vec<(String, String)>>,
+    /// Category the object belongs to, e.g. the game module or library it's part of
+    #[serde(default)]
+    pub category: Option<String>,
 }
 
 impl ProjectObject {
+    /// Whether the object is marked complete, i.e. fully matched.
+    pub fn is_complete(&self) -> bool { self.complete == Some(true) }
+
     pub fn name(&self) -> &str {
         if let Some(name) = &self.name {
             name
@@ -68,18 +146,92 @@ impl ProjectObject {
         target_obj_dir: Option<&Path>,
         base_obj_dir: Option<&Path>,
     ) {
-        if let (Some(target_obj_dir), Some(path), None) =
-            (target_obj_dir, &self.path, &self.target_path)
+        let path = self.path.as_deref().map(expand_path);
+        if let (Some(target_obj_dir), Some(path), None) = (target_obj_dir, &path, &self.target_path)
         {
             self.target_path = Some(target_obj_dir.join(path));
         } else if let Some(path) = &self.target_path {
-            self.target_path = Some(project_dir.join(path));
+            self.target_path = Some(project_dir.join(expand_path(path)));
         }
-        if let (Some(base_obj_dir), Some(path), None) = (base_obj_dir, &self.path, &self.base_path)
-        {
+        if let (Some(base_obj_dir), Some(path), None) = (base_obj_dir, &path, &self.base_path) {
             self.base_path = Some(base_obj_dir.join(path));
         } else if let Some(path) = &self.base_path {
-            self.base_path = Some(project_dir.join(path));
+            self.base_path = Some(project_dir.join(expand_path(path)));
+        }
+        if let Some(path) = &self.source_path {
+            self.source_path = Some(project_dir.join(expand_path(path)));
+        }
+        if let Some(ctx_path) = self.scratch.as_mut().and_then(|s| s.ctx_path.as_mut()) {
+            *ctx_path = expand_path(ctx_path);
+        }
+    }
+
+    /// Checks that the (resolved) target and base objects exist, describing each problem.
+    pub fn validate(&self, project_dir: &Path) -> Vec<String> {
+        let exists = |path: &Option<PathBuf>| path.as_ref().map(|p| project_dir.join(p).is_file());
+        let display = |path: &Option<PathBuf>| {
+            path.as_ref().map(|p| p.display().to_string()).unwrap_or_default()
+        };
+        let name = self.name();
+        match (exists(&self.target_path), exists(&self.base_path)) {
+            (Some(false), Some(false)) => vec![format!(
+                "{name}: neither {} nor {} exists, check the object's paths",
+                display(&self.target_path),
+                display(&self.base_path)
+            )],
+            (Some(false), _) => {
+                vec![format!("{name}: target {} not built yet", display(&self.target_path))]
+            }
+            (_, Some(false)) => {
+                vec![format!("{name}: base {} not built yet", display(&self.base_path))]
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Whether the source file was modified after the given build time. A source path that
+    /// hasn't been through [`ProjectObject::resolve_paths`] is relative to `project_dir`.
+    /// Objects without a (readable) source file are never considered stale.
+    pub fn is_stale(&self, project_dir: &Path, last_build: FileTime) -> bool {
+        let Some(metadata) = self
+            .source_path
+            .as_ref()
+            .and_then(|p| fs::metadata(project_dir.join(expand_path(p))).ok())
+        else {
+            return false;
+        };
+        FileTime::from_last_modification_time(&metadata) > last_build
+    }
+}
+
+/// Patterns in the build output that decide whether a build succeeded, for build tools
+/// whose exit code is unreliable.
+#[derive(Default, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct BuildStatusConfig {
+    /// Output matching this regex means the build failed, whatever the exit code
+    #[serde(default)]
+    pub failure_pattern: Option<String>,
+    /// Output matching this regex means the build succeeded, whatever the exit code
+    #[serde(default)]
+    pub success_pattern: Option<String>,
+}
+
+impl BuildStatusConfig {
+    /// Decides whether a build succeeded from its output, falling back to the exit code when
+    /// neither pattern matches. The failure pattern takes precedence.
+    pub fn build_success(&self, exit_success: bool, stdout: &str, stderr: &str) -> Result<bool> {
+        let matches = |pattern: &Option<String>| -> Result<bool> {
+            let Some(pattern) = pattern else { return Ok(false) };
+            let regex = regex::Regex::new(pattern)
+                .with_context(|| format!("Invalid build status pattern '{pattern}'"))?;
+            Ok(regex.is_match(stdout) || regex.is_match(stderr))
+        };
+        if matches(&self.failure_pattern)? {
+            Ok(false)
+        } else if matches(&self.success_pattern)? {
+            Ok(true)
+        } else {
+            Ok(exit_success)
         }
     }
 }
@@ -92,13 +244,21 @@ pub struct ScratchConfig {
     pub compiler: Option<String>,
     #[serde(default)]
     pub c_flags: Option<String>,
+    /// Context file, relative to the object's source directory or else the project directory
     #[serde(default)]
     pub ctx_path: Option<PathBuf>,
     #[serde(default)]
     pub build_ctx: bool,
+    /// Program and arguments printing the context, run from the project directory when
+    /// `build_ctx` is set, e.g. a preprocessor. Its output is written to `ctx_path`, and it's
+    /// rerun once the source file, its quoted includes or the command's file arguments change.
+    /// Without it, `make <ctx_path>` builds the context.
+    #[serde(default)]
+    pub ctx_command: Option<Vec<String>>,
 }
 
-pub const CONFIG_FILENAMES: [&str; 3] = ["objdiff.json", "objdiff.yml", "objdiff.yaml"];
+pub const CONFIG_FILENAMES: [&str; 4] =
+    ["objdiff.json", "objdiff.yml", "objdiff.yaml", "objdiff.toml"];
 
 pub const DEFAULT_WATCH_PATTERNS: &[&str] = &[
     "*.c", "*.cp", "*.cpp", "*.cxx", "*.h", "*.hp", "*.hpp", "*.hxx", "*.s", "*.S", "*.asm",
@@ -109,6 +269,8 @@ pub const DEFAULT_WATCH_PATTERNS: &[&str] = &[
 pub struct ProjectConfigInfo {
     pub path: PathBuf,
     pub timestamp: FileTime,
+    /// Included config files read while loading, with their modification times
+    pub includes: Vec<(PathBuf, FileTime)>,
 }
 
 pub fn try_project_config(dir: &Path) -> Option<(Result<ProjectConfig>, ProjectConfigInfo)> {
@@ -123,31 +285,171 @@ pub fn try_project_config(dir: &Path) -> Option<(Result<ProjectConfig>, ProjectC
                 continue;
             }
             let ts = FileTime::from_last_modification_time(&metadata);
-            let mut result = match filename.contains("json") {
-                true => read_json_config(&mut file),
-                false => read_yml_config(&mut file),
-            };
-            if let Ok(config) = &result {
-                // Validate min_version if present
-                if let Err(e) = validate_min_version(config) {
-                    result = Err(e);
+            let mut includes = Vec::new();
+            let result = read_project_config(&mut file, &config_path, &mut |path| {
+                let data = fs::read(path)?;
+                if let Ok(metadata) = fs::metadata(path) {
+                    let ts = FileTime::from_last_modification_time(&metadata);
+                    includes.push((path.to_path_buf(), ts));
                 }
-            }
-            return Some((result, ProjectConfigInfo { path: config_path, timestamp: ts }));
+                Ok(data)
+            });
+            let info = ProjectConfigInfo { path: config_path, timestamp: ts, includes };
+            return Some((result, info));
         }
     }
     None
 }
 
+/// Parses a project config, choosing the format by the extension of `path`, and merges in the
+/// configs it includes, see [`merge_project_configs`]. Includes are resolved relative to the
+/// including config's path and loaded with `read_include`. Objects are then discovered in the
+/// config's directory, see [`discover_objects`].
+pub fn read_project_config<R: Read>(
+    reader: &mut R,
+    path: &Path,
+    read_include: &mut dyn FnMut(&Path) -> Result<Vec<u8>>,
+) -> Result<ProjectConfig> {
+    let mut config = read_config_by_extension(reader, &path.to_string_lossy())?;
+    validate_min_version(&config)?;
+    let policy = config.conflict_policy;
+    let mut ancestors = vec![include_key(path)];
+    include_configs(&mut config, path, policy, read_include, &mut ancestors)?;
+    discover_objects(&mut config, path.parent().unwrap_or(Path::new("")))?;
+    Ok(config)
+}
+
+fn include_configs(
+    config: &mut ProjectConfig,
+    config_path: &Path,
+    policy: ConflictPolicy,
+    read_include: &mut dyn FnMut(&Path) -> Result<Vec<u8>>,
+    ancestors: &mut Vec<PathBuf>,
+) -> Result<()> {
+    let Some(includes) = config.include.take() else { return Ok(()) };
+    let dir = config_path.parent().unwrap_or(Path::new(""));
+    for include in includes {
+        let path = dir.join(expand_path(&include));
+        // Only an include of a config currently being loaded is a cycle, so the same file
+        // may still be included from several places
+        let key = include_key(&path);
+        if ancestors.contains(&key) {
+            bail!("Config include cycle at {}", path.display());
+        }
+        let data = read_include(&path)
+            .with_context(|| format!("Failed to open included config {}", path.display()))?;
+        let mut included = read_config_by_extension(&mut data.as_slice(), &path.to_string_lossy())
+            .with_context(|| format!("Failed to parse included config {}", path.display()))?;
+        // Each file's min_version applies to itself rather than conflicting with the others
+        validate_min_version(&included)?;
+        included.min_version = None;
+        ancestors.push(key);
+        let result = include_configs(&mut included, &path, policy, read_include, ancestors);
+        ancestors.pop();
+        result?;
+        merge_project_configs(config, included, policy)
+            .with_context(|| format!("Failed to merge included config {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Identifies a config file for cycle detection: its canonical path if it exists locally,
+/// otherwise the path with `.` and `..` components resolved.
+fn include_key(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| {
+        let mut out = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    out.pop();
+                }
+                component => out.push(component),
+            }
+        }
+        out
+    })
+}
+
+fn read_config_by_extension<R: Read>(reader: &mut R, name: &str) -> Result<ProjectConfig> {
+    let mut config = match Path::new(name).extension().and_then(|ext| ext.to_str()) {
+        Some("json") => read_json_config(reader)?,
+        Some("yml" | "yaml") => read_yml_config(reader)?,
+        Some("toml") => read_toml_config(reader)?,
+        _ => bail!("Unknown project config format: {name}"),
+    };
+    for dir in [&mut config.target_dir, &mut config.base_dir].into_iter().flatten() {
+        *dir = expand_path(dir);
+    }
+    Ok(config)
+}
+
+/// Expands `$VAR` and `${VAR}` in each path component from the environment. Unknown
+/// variables and an unterminated `${` are left as written.
+pub fn expand_path(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component.as_os_str().to_str() {
+            Some(s) if s.contains('$') => out.push(expand_vars(s)),
+            _ => out.push(component),
+        }
+    }
+    out
+}
+
+fn expand_vars(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(idx) = rest.find('$') {
+        out.push_str(&rest[..idx]);
+        let after = &rest[idx + 1..];
+        let (name, len) = if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                .unwrap_or(after.len());
+            (&after[..end], end)
+        };
+        if name.is_empty() {
+            out.push('$');
+            rest = after;
+            continue;
+        }
+        match std::env::var(name) {
+            Ok(value) => out.push_str(&value),
+            Err(_) => {
+                log::warn!("Environment variable {name} is not set, leaving it unexpanded");
+                out.push_str(&rest[idx..idx + 1 + len]);
+            }
+        }
+        rest = &after[len..];
+    }
+    out.push_str(rest);
+    out
+}
+
 fn validate_min_version(config: &ProjectConfig) -> Result<()> {
-    let Some(min_version) = &config.min_version else { return Ok(()) };
     let version = semver::Version::parse(env!("CARGO_PKG_VERSION"))
         .context("Failed to parse package version")?;
-    let min_version = semver::Version::parse(min_version).context("Failed to parse min_version")?;
-    if version >= min_version {
+    check_min_version(config, &version)
+}
+
+/// Fails if the project's `min_version` is newer than `current`.
+pub fn check_min_version(config: &ProjectConfig, current: &semver::Version) -> Result<()> {
+    let Some(min_version) = &config.min_version else { return Ok(()) };
+    let min_version = semver::Version::parse(min_version)
+        .with_context(|| format!("Failed to parse min_version '{min_version}'"))?;
+    if *current >= min_version {
         Ok(())
     } else {
-        Err(anyhow!("Project requires objdiff version {min_version} or higher"))
+        Err(anyhow!(
+            "Project requires objdiff version {min_version} or higher, but this is version \
+             {current}. Please update objdiff."
+        ))
     }
 }
 
@@ -159,6 +461,137 @@ fn read_json_config<R: Read>(reader: &mut R) -> Result<ProjectConfig> {
     Ok(serde_json::from_reader(reader)?)
 }
 
+fn read_toml_config<R: Read>(reader: &mut R) -> Result<ProjectConfig> {
+    let mut buf = String::new();
+    reader.read_to_string(&mut buf)?;
+    Ok(toml::from_str(&buf)?)
+}
+
+/// Adds objects matching `object_patterns` found in the target and base directories.
+///
+/// Discovered objects are named after their path without the extension. Explicit objects
+/// with the same name or path take precedence.
+pub fn discover_objects(config: &mut ProjectConfig, project_dir: &Path) -> Result<()> {
+    let Some(patterns) = &config.object_patterns else { return Ok(()) };
+    let globset = build_globset(patterns).context("Failed to parse object_patterns")?;
+    let mut paths = BTreeSet::new();
+    for dir in [&config.target_dir, &config.base_dir].into_iter().flatten() {
+        let dir = project_dir.join(dir);
+        if dir.is_dir() {
+            collect_files(&dir, Path::new(""), &mut paths)
+                .with_context(|| format!("Failed to read directory {}", dir.display()))?;
+        }
+    }
+    for path in paths.into_iter().filter(|p| globset.is_match(p)) {
+        let name = path.with_extension("").to_string_lossy().replace('\\', "/");
+        if config.objects.iter().any(|o| {
+            o.name.as_deref() == Some(name.as_str()) || o.path.as_deref() == Some(path.as_path())
+        }) {
+            continue;
+        }
+        config.objects.push(ProjectObject {
+            name: Some(name),
+            path: Some(path),
+            ..Default::default()
+        });
+    }
+    Ok(())
+}
+
+fn collect_files(dir: &Path, rel_dir: &Path, out: &mut BTreeSet<PathBuf>) -> std::io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let rel_path = rel_dir.join(entry.file_name());
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), &rel_path, out)?;
+        } else {
+            out.insert(rel_path);
+        }
+    }
+    Ok(())
+}
+
+/// How conflicting fields are resolved when merging project configs.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictPolicy {
+    /// Fail when both configs set a field to different values.
+    #[default]
+    Error,
+    /// Use the value from the config merged last.
+    LastWins,
+    /// Keep the value from the config merged first.
+    FirstWins,
+}
+
+fn merge_field<T: PartialEq>(
+    name: &str,
+    first: &mut Option<T>,
+    last: Option<T>,
+    policy: ConflictPolicy,
+) -> Result<()> {
+    match (first.as_ref(), last) {
+        (_, None) => {}
+        (None, last) => *first = last,
+        (Some(a), Some(b)) if *a == b => {}
+        (Some(_), Some(b)) => match policy {
+            ConflictPolicy::Error => bail!("Conflicting values for '{name}'"),
+            ConflictPolicy::LastWins => *first = Some(b),
+            ConflictPolicy::FirstWins => {}
+        },
+    }
+    Ok(())
+}
+
+/// Merges `last` into `first`.
+///
+/// Optional fields conflict when both configs set them to different values, and are resolved
+/// with `policy`. `build_base`, `build_target` and `build_parallel` take whichever value differs
+/// from the default.
+/// Objects are merged by name, with duplicates resolved the same way.
+pub fn merge_project_configs(
+    first: &mut ProjectConfig,
+    last: ProjectConfig,
+    policy: ConflictPolicy,
+) -> Result<()> {
+    merge_field("min_version", &mut first.min_version, last.min_version, policy)?;
+    merge_field("custom_make", &mut first.custom_make, last.custom_make, policy)?;
+    merge_field("custom_args", &mut first.custom_args, last.custom_args, policy)?;
+    merge_field("target_dir", &mut first.target_dir, last.target_dir, policy)?;
+    merge_field("base_dir", &mut first.base_dir, last.base_dir, policy)?;
+    merge_field("watch_patterns", &mut first.watch_patterns, last.watch_patterns, policy)?;
+    merge_field("object_patterns", &mut first.object_patterns, last.object_patterns, policy)?;
+    merge_field(
+        "custom_relocations",
+        &mut first.custom_relocations,
+        last.custom_relocations,
+        policy,
+    )?;
+    merge_field("symbol_name_rules", &mut first.symbol_name_rules, last.symbol_name_rules, policy)?;
+    merge_field("instruction_rules", &mut first.instruction_rules, last.instruction_rules, policy)?;
+    merge_field(
+        "debug_search_paths",
+        &mut first.debug_search_paths,
+        last.debug_search_paths,
+        policy,
+    )?;
+    merge_field("demangler", &mut first.demangler, last.demangler, policy)?;
+    first.build_base &= last.build_base;
+    first.build_target |= last.build_target;
+    first.build_parallel &= last.build_parallel;
+    for object in last.objects {
+        match first.objects.iter_mut().find(|o| o.name() == object.name()) {
+            Some(existing) => match policy {
+                ConflictPolicy::Error => bail!("Duplicate object '{}'", object.name()),
+                ConflictPolicy::LastWins => *existing = object,
+                ConflictPolicy::FirstWins => {}
+            },
+            None => first.objects.push(object),
+        }
+    }
+    Ok(())
+}
+
 pub fn build_globset(vec: &[Glob]) -> std::result::Result<GlobSet, globset::Error> {
     let mut builder = GlobSetBuilder::new();
     for glob in vec {
@@ -166,3 +599,53 @@ pub fn build_globset(vec: &[Glob]) -> std::result::Result<GlobSet, globset::Erro
     }
     builder.build()
 }
+
+/// Watch patterns, where patterns starting with `!` exclude paths matched by the others.
+pub struct WatchGlobSet {
+    include: GlobSet,
+    exclude: GlobSet,
+}
+
+impl WatchGlobSet {
+    pub fn is_watched(&self, path: &Path) -> bool {
+        self.include.is_match(path) && !self.exclude.is_match(path)
+    }
+}
+
+pub fn build_watch_globset(vec: &[Glob]) -> std::result::Result<WatchGlobSet, globset::Error> {
+    let mut include = GlobSetBuilder::new();
+    let mut exclude = GlobSetBuilder::new();
+    for glob in vec {
+        match glob.glob().strip_prefix('!') {
+            Some(pattern) => exclude.add(Glob::new(pattern)?),
+            None => include.add(glob.clone()),
+        };
+    }
+    Ok(WatchGlobSet { include: include.build()?, exclude: exclude.build()? })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_when_source_newer_than_build() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("main.c"), "").unwrap();
+        let modified = FileTime::from_unix_time(1_700_000_000, 0);
+        filetime::set_file_mtime(dir.path().join("main.c"), modified).unwrap();
+        let object =
+            ProjectObject { source_path: Some(PathBuf::from("main.c")), ..Default::default() };
+
+        let before = FileTime::from_unix_time(1_699_999_999, 0);
+        let after = FileTime::from_unix_time(1_700_000_001, 0);
+        assert!(object.is_stale(dir.path(), before));
+        assert!(!object.is_stale(dir.path(), after));
+        // Resolved paths are absolute and unaffected by the project directory
+        let mut resolved = object.clone();
+        resolved.resolve_paths(dir.path(), None, None);
+        assert!(resolved.is_stale(Path::new("elsewhere"), before));
+        // Missing source files are never stale
+        assert!(!object.is_stale(Path::new("elsewhere"), before));
+    }
+}
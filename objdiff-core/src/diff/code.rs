@@ -1,19 +1,23 @@
 use std::{
     cmp::max,
     collections::BTreeMap,
+    fmt::Write,
+    ops::Range,
     time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, Result};
-use similar::{capture_diff_slices_deadline, Algorithm};
+use similar::{capture_diff_slices_deadline, Algorithm, DiffTag};
 
 use crate::{
-    arch::ProcessCodeResult,
+    arch::{comparison_text, ObjArch, ProcessCodeResult},
     diff::{
-        DiffObjConfig, ObjInsArgDiff, ObjInsBranchFrom, ObjInsBranchTo, ObjInsDiff, ObjInsDiffKind,
-        ObjSymbolDiff,
+        CodeDiffGranularity, DiffAlignment, DiffObjConfig, ObjInsArgDiff, ObjInsBranchFrom,
+        ObjInsBranchTo, ObjInsDiff, ObjInsDiffKind, ObjSymbolDiff,
+    },
+    obj::{
+        ObjInfo, ObjIns, ObjInsArg, ObjInsArgValue, ObjReloc, ObjSymbol, ObjSymbolFlags, SymbolRef,
     },
-    obj::{ObjInfo, ObjInsArg, ObjReloc, ObjSymbol, ObjSymbolFlags, SymbolRef},
 };
 
 pub fn process_code_symbol(
@@ -25,14 +29,126 @@ pub fn process_code_symbol(
     let section = section.ok_or_else(|| anyhow!("Code symbol section not found"))?;
     let code = &section.data
         [symbol.section_address as usize..(symbol.section_address + symbol.size) as usize];
-    obj.arch.process_code(
+    if config.code_diff_granularity == CodeDiffGranularity::Byte {
+        return Ok(process_code_bytes(
+            symbol.address,
+            code,
+            &section.relocations,
+            &section.line_info,
+        ));
+    }
+    let mut result = obj.arch.process_code(
         symbol.address,
         code,
         section.orig_index,
         &section.relocations,
         &section.line_info,
         config,
-    )
+    )?;
+    if !config.ignored_instructions.is_empty() {
+        let rules: Vec<_> =
+            config.ignored_instructions.iter().map(|rule| IgnoreRule::parse(rule)).collect();
+        let (ops, insts) = result
+            .ops
+            .into_iter()
+            .zip(result.insts)
+            .filter(|(_, ins)| !rules.iter().any(|rule| rule.matches(ins)))
+            .unzip();
+        result = ProcessCodeResult { ops, insts };
+    }
+    for (op, ins) in result.ops.iter_mut().zip(&mut result.insts) {
+        *op = obj.ins_normalizer.normalize(ins);
+    }
+    Ok(result)
+}
+
+/// An entry of [`DiffObjConfig::ignored_instructions`]: a mnemonic, optionally followed by the
+/// operands an instruction must also have, e.g. `bl mcount`.
+struct IgnoreRule<'a> {
+    mnemonic: &'a str,
+    /// Operands without whitespace
+    operands: Option<String>,
+}
+
+impl<'a> IgnoreRule<'a> {
+    fn parse(rule: &'a str) -> Self {
+        let rule = rule.trim();
+        match rule.split_once(char::is_whitespace) {
+            Some((mnemonic, operands)) => {
+                let operands = operands.chars().filter(|c| !c.is_whitespace()).collect();
+                Self { mnemonic, operands: Some(operands) }
+            }
+            None => Self { mnemonic: rule, operands: None },
+        }
+    }
+
+    fn matches(&self, ins: &ObjIns) -> bool {
+        ins.mnemonic == self.mnemonic
+            && self.operands.as_ref().map_or(true, |operands| *operands == compact_operands(ins))
+    }
+}
+
+/// The instruction's operands without whitespace, with relocations shown as the target's name.
+fn compact_operands(ins: &ObjIns) -> String {
+    let mut text = String::new();
+    for arg in &ins.args {
+        match arg {
+            ObjInsArg::PlainText(s) => text.push_str(s),
+            ObjInsArg::Arg(v) => write!(text, "{v}").unwrap(),
+            ObjInsArg::Reloc => {
+                if let Some(reloc) = &ins.reloc {
+                    text.push_str(&reloc.target.name);
+                }
+            }
+            ObjInsArg::BranchDest(dest) => write!(text, "{dest:x}").unwrap(),
+        }
+    }
+    text.retain(|c| !c.is_whitespace());
+    text
+}
+
+/// Splits code into one pseudo-instruction per byte, see [`CodeDiffGranularity::Byte`].
+/// The first byte of each relocation holds the relocation in place of its value.
+fn process_code_bytes(
+    address: u64,
+    code: &[u8],
+    relocations: &[ObjReloc],
+    line_info: &BTreeMap<u64, u64>,
+) -> ProcessCodeResult {
+    let mut result = ProcessCodeResult { ops: Vec::with_capacity(code.len()), insts: vec![] };
+    let end_address = address + code.len() as u64;
+    let mut relocs = relocations
+        .iter()
+        .filter(|r| r.address >= address && r.address < end_address)
+        .collect::<Vec<_>>();
+    relocs.sort_by_key(|r| r.address);
+    let mut relocs = relocs.into_iter().peekable();
+    for (i, &byte) in code.iter().enumerate() {
+        let address = address + i as u64;
+        // Skip further relocations at addresses already passed
+        while relocs.next_if(|r| r.address < address).is_some() {}
+        let reloc = relocs.next_if(|r| r.address == address);
+        let (op, arg) = match reloc {
+            // Distinct from every byte value, so relocations only align with each other
+            Some(_) => (u8::MAX as u16 + 1, ObjInsArg::Reloc),
+            None => (byte as u16, ObjInsArg::Arg(ObjInsArgValue::Unsigned(byte as u64))),
+        };
+        result.ops.push(op);
+        result.insts.push(ObjIns {
+            address,
+            size: 1,
+            op,
+            mnemonic: ".byte".to_string(),
+            args: vec![arg],
+            reloc: reloc.cloned(),
+            branch_dest: None,
+            line: line_info.range(..=address).last().map(|(_, &l)| l),
+            formatted: format!(".byte {byte:#04x}"),
+            orig: None,
+            canonical: None,
+        });
+    }
+    result
 }
 
 pub fn no_diff_code(out: &ProcessCodeResult, symbol_ref: SymbolRef) -> Result<ObjSymbolDiff> {
@@ -45,10 +161,17 @@ pub fn no_diff_code(out: &ProcessCodeResult, symbol_ref: SymbolRef) -> Result<Ob
         });
     }
     resolve_branches(&mut diff);
-    Ok(ObjSymbolDiff { symbol_ref, diff_symbol: None, instructions: diff, match_percent: None })
+    Ok(ObjSymbolDiff {
+        symbol_ref,
+        diff_symbol: None,
+        instructions: diff,
+        match_percent: None,
+        regalloc_match_percent: None,
+    })
 }
 
 pub fn diff_code(
+    arch: &dyn ObjArch,
     left_out: &ProcessCodeResult,
     right_out: &ProcessCodeResult,
     left_symbol_ref: SymbolRef,
@@ -57,26 +180,53 @@ pub fn diff_code(
 ) -> Result<(ObjSymbolDiff, ObjSymbolDiff)> {
     let mut left_diff = Vec::<ObjInsDiff>::new();
     let mut right_diff = Vec::<ObjInsDiff>::new();
-    diff_instructions(&mut left_diff, &mut right_diff, left_out, right_out)?;
+    diff_instructions(&mut left_diff, &mut right_diff, left_out, right_out, config)?;
 
     resolve_branches(&mut left_diff);
     resolve_branches(&mut right_diff);
 
+    let padding_start = if config.ignore_padding_nops {
+        trailing_padding_start(arch, &left_diff, &right_diff)
+    } else {
+        left_diff.len()
+    };
     let mut diff_state = InsDiffState::default();
-    for (left, right) in left_diff.iter_mut().zip(right_diff.iter_mut()) {
-        let result = compare_ins(config, left, right, &mut diff_state)?;
+    let mut total = left_out.insts.len();
+    let insts = (left_out.insts.as_slice(), right_out.insts.as_slice());
+    // Index of each row's instruction in `insts`
+    let mut ins_idx = (0, 0);
+    for (i, (left, right)) in left_diff.iter_mut().zip(right_diff.iter_mut()).enumerate() {
+        let row_ins_idx = ins_idx;
+        ins_idx.0 += left.ins.is_some() as usize;
+        ins_idx.1 += right.ins.is_some() as usize;
+        if i >= padding_start && diff_state.diff_count == 0 && diff_state.reloc_diff_count == 0 {
+            // Padding after a matching function is neutral and excluded from the score
+            if left.ins.is_some() {
+                total -= 1;
+            }
+            continue;
+        }
+        let result = compare_ins(arch, config, left, right, insts, row_ins_idx, &mut diff_state)?;
         left.kind = result.kind;
         right.kind = result.kind;
         left.arg_diff = result.left_args_diff;
         right.arg_diff = result.right_args_diff;
     }
+    if config.detect_moved_blocks {
+        mark_moved_blocks(&mut left_diff, &mut right_diff);
+    }
 
-    let total = left_out.insts.len();
-    let percent = if diff_state.diff_count >= total {
-        0.0
-    } else {
-        ((total - diff_state.diff_count) as f32 / total as f32) * 100.0
+    let diff_count = diff_state.diff_count as f32
+        + diff_state.reloc_diff_count as f32 * config.reloc_diff_score.weight();
+    let score = |diff_count: f32| {
+        if diff_count >= total as f32 {
+            0.0
+        } else {
+            ((total as f32 - diff_count) / total as f32) * 100.0
+        }
     };
+    let percent = score(diff_count);
+    let regalloc_percent = score(diff_count - diff_state.register_diff_count as f32);
 
     Ok((
         ObjSymbolDiff {
@@ -84,55 +234,229 @@ pub fn diff_code(
             diff_symbol: Some(right_symbol_ref),
             instructions: left_diff,
             match_percent: Some(percent),
+            regalloc_match_percent: Some(regalloc_percent),
         },
         ObjSymbolDiff {
             symbol_ref: right_symbol_ref,
             diff_symbol: Some(left_symbol_ref),
             instructions: right_diff,
             match_percent: Some(percent),
+            regalloc_match_percent: Some(regalloc_percent),
         },
     ))
 }
 
+/// Finds the first row of the trailing run where both sides are padding NOPs (or empty).
+/// A function consisting only of padding is left as-is.
+fn trailing_padding_start(
+    arch: &dyn ObjArch,
+    left_diff: &[ObjInsDiff],
+    right_diff: &[ObjInsDiff],
+) -> usize {
+    let is_padding = |d: &ObjInsDiff| d.ins.as_ref().map_or(true, |ins| arch.is_padding_nop(ins));
+    left_diff
+        .iter()
+        .zip(right_diff)
+        .rposition(|(left, right)| !is_padding(left) || !is_padding(right))
+        .map_or(left_diff.len(), |i| i + 1)
+}
+
+/// Minimum number of instructions in a moved block, so that a lone common instruction
+/// elsewhere in the function isn't reported as moved.
+const MIN_MOVED_BLOCK_LEN: usize = 2;
+
+/// Rows of each maximal run of `kind`.
+fn runs_of(diff: &[ObjInsDiff], kind: ObjInsDiffKind) -> Vec<Range<usize>> {
+    let mut runs = vec![];
+    let mut start = None;
+    for (i, ins_diff) in diff.iter().enumerate() {
+        match (ins_diff.kind == kind, start) {
+            (true, None) => start = Some(i),
+            (false, Some(s)) => {
+                runs.push(s..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        runs.push(s..diff.len());
+    }
+    runs
+}
+
+/// Compares instructions by mnemonic, operands and relocation target, ignoring branch
+/// destinations, which change when a block moves.
+fn moved_key(ins_diff: &ObjInsDiff) -> Option<String> {
+    let ins = ins_diff.ins.as_ref()?;
+    let mut key = ins.mnemonic.clone();
+    for arg in &ins.args {
+        key.push(' ');
+        match arg {
+            ObjInsArg::PlainText(s) => key.push_str(s),
+            ObjInsArg::Arg(v) => key.push_str(&v.to_string()),
+            ObjInsArg::Reloc => {
+                key.push_str(ins.reloc.as_ref().map_or("", |r| r.target.name.as_str()))
+            }
+            ObjInsArg::BranchDest(_) => {}
+        }
+    }
+    Some(key)
+}
+
+/// Marks blocks deleted on the left that are inserted unchanged elsewhere on the right.
+fn mark_moved_blocks(left_diff: &mut [ObjInsDiff], right_diff: &mut [ObjInsDiff]) {
+    let keys = |diff: &[ObjInsDiff], range: &Range<usize>| -> Vec<Option<String>> {
+        diff[range.clone()].iter().map(moved_key).collect()
+    };
+    let deleted = runs_of(left_diff, ObjInsDiffKind::Delete);
+    let mut inserted: Vec<_> = runs_of(right_diff, ObjInsDiffKind::Insert)
+        .into_iter()
+        .filter(|r| r.len() >= MIN_MOVED_BLOCK_LEN)
+        .map(|r| {
+            let keys = keys(right_diff, &r);
+            (r, keys)
+        })
+        .collect();
+    for left_range in deleted.into_iter().filter(|r| r.len() >= MIN_MOVED_BLOCK_LEN) {
+        let left_keys = keys(left_diff, &left_range);
+        let Some(pos) = inserted.iter().position(|(_, keys)| *keys == left_keys) else {
+            continue;
+        };
+        let (right_range, _) = inserted.swap_remove(pos);
+        for i in left_range.chain(right_range) {
+            left_diff[i].kind = ObjInsDiffKind::Moved;
+            right_diff[i].kind = ObjInsDiffKind::Moved;
+        }
+    }
+}
+
 fn diff_instructions(
     left_diff: &mut Vec<ObjInsDiff>,
     right_diff: &mut Vec<ObjInsDiff>,
     left_code: &ProcessCodeResult,
     right_code: &ProcessCodeResult,
+    config: &DiffObjConfig,
 ) -> Result<()> {
     let deadline = Instant::now() + Duration::from_secs(5);
+    let left_len = left_code.insts.len();
+    let right_len = right_code.insts.len();
+    let anchors = match config.diff_alignment {
+        DiffAlignment::Opcode => vec![],
+        DiffAlignment::RelocationAnchors => find_anchors(left_code, right_code, deadline),
+    };
+
+    // Diff each run of instructions between anchors independently, so that
+    // a difference can't cause the alignment to drift past an anchor.
+    let mut left_start = 0;
+    let mut right_start = 0;
+    for (left_idx, right_idx) in anchors {
+        diff_range(
+            left_diff,
+            right_diff,
+            left_code,
+            right_code,
+            left_start..left_idx,
+            right_start..right_idx,
+            deadline,
+        );
+        left_diff.push(ObjInsDiff {
+            ins: Some(left_code.insts[left_idx].clone()),
+            ..Default::default()
+        });
+        right_diff.push(ObjInsDiff {
+            ins: Some(right_code.insts[right_idx].clone()),
+            ..Default::default()
+        });
+        left_start = left_idx + 1;
+        right_start = right_idx + 1;
+    }
+    diff_range(
+        left_diff,
+        right_diff,
+        left_code,
+        right_code,
+        left_start..left_len,
+        right_start..right_len,
+        deadline,
+    );
+    Ok(())
+}
+
+/// Finds pairs of instruction indices that reference the same relocation target
+/// with the same opcode, in order on both sides.
+fn find_anchors(
+    left_code: &ProcessCodeResult,
+    right_code: &ProcessCodeResult,
+    deadline: Instant,
+) -> Vec<(usize, usize)> {
+    fn anchor_keys(code: &ProcessCodeResult) -> (Vec<usize>, Vec<(u16, &str)>) {
+        code.insts
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, ins)| {
+                ins.reloc.as_ref().map(|reloc| (idx, (ins.op, reloc.target.name.as_str())))
+            })
+            .unzip()
+    }
+    let (left_indices, left_keys) = anchor_keys(left_code);
+    let (right_indices, right_keys) = anchor_keys(right_code);
+    let ops =
+        capture_diff_slices_deadline(Algorithm::Patience, &left_keys, &right_keys, Some(deadline));
+    let mut anchors = vec![];
+    for op in ops {
+        let (tag, left_range, right_range) = op.as_tag_tuple();
+        if tag != DiffTag::Equal {
+            continue;
+        }
+        for (l, r) in left_range.zip(right_range) {
+            anchors.push((left_indices[l], right_indices[r]));
+        }
+    }
+    anchors
+}
+
+fn diff_range(
+    left_diff: &mut Vec<ObjInsDiff>,
+    right_diff: &mut Vec<ObjInsDiff>,
+    left_code: &ProcessCodeResult,
+    right_code: &ProcessCodeResult,
+    left_range: Range<usize>,
+    right_range: Range<usize>,
+    deadline: Instant,
+) {
+    let left_offset = left_range.start;
+    let right_offset = right_range.start;
     let ops = capture_diff_slices_deadline(
         Algorithm::Patience,
-        &left_code.ops,
-        &right_code.ops,
+        &left_code.ops[left_range.clone()],
+        &right_code.ops[right_range.clone()],
         Some(deadline),
     );
     if ops.is_empty() {
         left_diff.extend(
-            left_code
-                .insts
+            left_code.insts[left_range]
                 .iter()
                 .map(|i| ObjInsDiff { ins: Some(i.clone()), ..Default::default() }),
         );
         right_diff.extend(
-            right_code
-                .insts
+            right_code.insts[right_range]
                 .iter()
                 .map(|i| ObjInsDiff { ins: Some(i.clone()), ..Default::default() }),
         );
-        return Ok(());
+        return;
     }
 
     for op in ops {
         let (_tag, left_range, right_range) = op.as_tag_tuple();
         let len = max(left_range.len(), right_range.len());
         left_diff.extend(
-            left_code.insts[left_range.clone()]
+            left_code.insts[left_range.start + left_offset..left_range.end + left_offset]
                 .iter()
                 .map(|i| ObjInsDiff { ins: Some(i.clone()), ..Default::default() }),
         );
         right_diff.extend(
-            right_code.insts[right_range.clone()]
+            right_code.insts[right_range.start + right_offset..right_range.end + right_offset]
                 .iter()
                 .map(|i| ObjInsDiff { ins: Some(i.clone()), ..Default::default() }),
         );
@@ -143,8 +467,6 @@ fn diff_instructions(
             right_diff.extend((right_range.len()..len).map(|_| ObjInsDiff::default()));
         }
     }
-
-    Ok(())
 }
 
 fn resolve_branches(vec: &mut [ObjInsDiff]) {
@@ -183,7 +505,7 @@ fn address_eq(left: &ObjSymbol, right: &ObjSymbol) -> bool {
     left.address as i64 + left.addend == right.address as i64 + right.addend
 }
 
-fn reloc_eq(
+pub(crate) fn reloc_eq(
     config: &DiffObjConfig,
     left_reloc: Option<&ObjReloc>,
     right_reloc: Option<&ObjReloc>,
@@ -197,6 +519,9 @@ fn reloc_eq(
     if config.relax_reloc_diffs {
         return true;
     }
+    if config.raw_reloc_diffs {
+        return address_eq(&left.target, &right.target);
+    }
 
     let name_matches = left.target.name == right.target.name;
     match (&left.target_section, &right.target_section) {
@@ -214,19 +539,30 @@ fn reloc_eq(
 }
 
 fn arg_eq(
+    arch: &dyn ObjArch,
     config: &DiffObjConfig,
-    left: &ObjInsArg,
-    right: &ObjInsArg,
+    (left_idx, right_idx): (usize, usize),
     left_diff: &ObjInsDiff,
     right_diff: &ObjInsDiff,
+    (left_insts, right_insts): (&[ObjIns], &[ObjIns]),
+    (left_ins_idx, right_ins_idx): (usize, usize),
 ) -> bool {
+    let (Some(left_ins), Some(right_ins)) = (&left_diff.ins, &right_diff.ins) else {
+        return false;
+    };
+    let (left, right) = (&left_ins.args[left_idx], &right_ins.args[right_idx]);
     return match left {
         ObjInsArg::PlainText(l) => match right {
             ObjInsArg::PlainText(r) => l == r,
             _ => false,
         },
         ObjInsArg::Arg(l) => match right {
-            ObjInsArg::Arg(r) => l == r,
+            ObjInsArg::Arg(r) => {
+                l == r
+                    || (config.ignore_address_args
+                        && arch.is_address_arg(left_insts, left_ins_idx, left_idx)
+                        && arch.is_address_arg(right_insts, right_ins_idx, right_idx))
+            }
             // If relocations are relaxed, match if left is a constant and right is a reloc
             // Useful for instances where the target object is created without relocations
             ObjInsArg::Reloc => config.relax_reloc_diffs,
@@ -234,11 +570,7 @@ fn arg_eq(
         },
         ObjInsArg::Reloc => {
             matches!(right, ObjInsArg::Reloc)
-                && reloc_eq(
-                    config,
-                    left_diff.ins.as_ref().and_then(|i| i.reloc.as_ref()),
-                    right_diff.ins.as_ref().and_then(|i| i.reloc.as_ref()),
-                )
+                && reloc_eq(config, left_ins.reloc.as_ref(), right_ins.reloc.as_ref())
         }
         ObjInsArg::BranchDest(_) => {
             // Compare dest instruction idx after diffing
@@ -251,6 +583,10 @@ fn arg_eq(
 #[derive(Default)]
 struct InsDiffState {
     diff_count: usize,
+    /// Instructions differing only in relocation targets, scored separately
+    reloc_diff_count: usize,
+    /// Instructions differing only in register operands, forgiven by the register allocation score
+    register_diff_count: usize,
     left_arg_idx: usize,
     right_arg_idx: usize,
     left_args_idx: BTreeMap<String, usize>,
@@ -265,22 +601,29 @@ struct InsDiffResult {
 }
 
 fn compare_ins(
+    arch: &dyn ObjArch,
     config: &DiffObjConfig,
     left: &ObjInsDiff,
     right: &ObjInsDiff,
+    insts: (&[ObjIns], &[ObjIns]),
+    ins_idx: (usize, usize),
     state: &mut InsDiffState,
 ) -> Result<InsDiffResult> {
     let mut result = InsDiffResult::default();
     if let (Some(left_ins), Some(right_ins)) = (&left.ins, &right.ins) {
-        if left_ins.args.len() != right_ins.args.len()
-            || left_ins.op != right_ins.op
-            // Check if any PlainText segments differ (punctuation and spacing)
-            // This indicates a more significant difference than a simple arg mismatch
-            || !left_ins.args.iter().zip(&right_ins.args).all(|(a, b)| match (a, b) {
-                (ObjInsArg::PlainText(l), ObjInsArg::PlainText(r)) => l == r,
-                _ => true,
-            })
+        // Operands are paired by position, skipping punctuation, so that e.g. a relocation
+        // in place of a constant only flags that operand
+        let left_operands = operand_indices(left_ins);
+        let right_operands = operand_indices(right_ins);
+        if (left_ins.canonical.is_some() || right_ins.canonical.is_some())
+            && comparison_text(left_ins) == comparison_text(right_ins)
         {
+            // Equivalent under an instruction rule
+            result.left_args_diff = vec![None; left_ins.args.len()];
+            result.right_args_diff = vec![None; right_ins.args.len()];
+            return Ok(result);
+        }
+        if left_ins.op != right_ins.op || left_operands.len() != right_operands.len() {
             // Totally different op
             result.kind = ObjInsDiffKind::Replace;
             state.diff_count += 1;
@@ -291,11 +634,16 @@ fn compare_ins(
             result.kind = ObjInsDiffKind::OpMismatch;
             state.diff_count += 1;
         }
-        for (a, b) in left_ins.args.iter().zip(&right_ins.args) {
-            if arg_eq(config, a, b, left, right) {
-                result.left_args_diff.push(None);
-                result.right_args_diff.push(None);
-            } else {
+        result.left_args_diff = vec![None; left_ins.args.len()];
+        result.right_args_diff = vec![None; right_ins.args.len()];
+        let mut reloc_only = true;
+        let mut register_only = true;
+        for (&l, &r) in left_operands.iter().zip(&right_operands) {
+            let (a, b) = (&left_ins.args[l], &right_ins.args[r]);
+            if !arg_eq(arch, config, (l, r), left, right, insts, ins_idx) {
+                reloc_only &= matches!((a, b), (ObjInsArg::Reloc, ObjInsArg::Reloc));
+                register_only &=
+                    arch.is_register_arg(left_ins, l) && arch.is_register_arg(right_ins, r);
                 if result.kind == ObjInsDiffKind::None {
                     result.kind = ObjInsDiffKind::ArgMismatch;
                     state.diff_count += 1;
@@ -328,10 +676,22 @@ fn compare_ins(
                     state.right_arg_idx += 1;
                     ObjInsArgDiff { idx }
                 };
-                result.left_args_diff.push(Some(a_diff));
-                result.right_args_diff.push(Some(b_diff));
+                result.left_args_diff[l] = Some(a_diff);
+                result.right_args_diff[r] = Some(b_diff);
             }
         }
+        if result.kind == ObjInsDiffKind::None && plain_text(left_ins) != plain_text(right_ins) {
+            // Same operands with different punctuation (e.g. addressing mode)
+            result.kind = ObjInsDiffKind::Replace;
+            state.diff_count += 1;
+            return Ok(result);
+        }
+        if result.kind == ObjInsDiffKind::ArgMismatch && reloc_only {
+            state.diff_count -= 1;
+            state.reloc_diff_count += 1;
+        } else if result.kind == ObjInsDiffKind::ArgMismatch && register_only {
+            state.register_diff_count += 1;
+        }
     } else if left.ins.is_some() {
         result.kind = ObjInsDiffKind::Delete;
         state.diff_count += 1;
@@ -341,3 +701,282 @@ fn compare_ins(
     }
     Ok(result)
 }
+
+fn operand_indices(ins: &ObjIns) -> Vec<usize> {
+    ins.args
+        .iter()
+        .enumerate()
+        .filter(|(_, arg)| !matches!(arg, ObjInsArg::PlainText(_)))
+        .map(|(i, _)| i)
+        .collect()
+}
+
+fn plain_text(ins: &ObjIns) -> Vec<&str> {
+    ins.args
+        .iter()
+        .filter_map(|arg| match arg {
+            ObjInsArg::PlainText(s) => Some(s.as_ref()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// One component (opcode, operand or relocation) of a pair of diffed instructions.
+#[derive(Debug, Clone)]
+pub struct InsComponentDiff {
+    pub name: String,
+    pub left: Option<String>,
+    pub right: Option<String>,
+    pub differs: bool,
+}
+
+fn operand_text(ins: &ObjIns, arg: &ObjInsArg) -> String {
+    match arg {
+        ObjInsArg::PlainText(s) => s.to_string(),
+        ObjInsArg::Arg(v) => v.to_string(),
+        ObjInsArg::Reloc => ins.reloc.as_ref().map(reloc_text).unwrap_or_default(),
+        ObjInsArg::BranchDest(dest) => format!("{dest:x}"),
+    }
+}
+
+fn reloc_text(reloc: &ObjReloc) -> String {
+    let name = reloc.target.demangled_name.as_ref().unwrap_or(&reloc.target.name);
+    match &reloc.target_section {
+        Some(section) => format!("{name} ({section}+{:#x})", reloc.target.address),
+        None => format!("{name} (extern)"),
+    }
+}
+
+/// Breaks a pair of diffed instructions into their components, flagging those that differ.
+///
+/// Operands are paired by position, ignoring punctuation.
+pub fn instruction_components(left: &ObjInsDiff, right: &ObjInsDiff) -> Vec<InsComponentDiff> {
+    let (left_ins, right_ins) = (left.ins.as_ref(), right.ins.as_ref());
+    // Replaced instructions have no per-argument diffs, so every component differs
+    let replaced = matches!(
+        left.kind,
+        ObjInsDiffKind::Replace
+            | ObjInsDiffKind::Delete
+            | ObjInsDiffKind::Insert
+            | ObjInsDiffKind::Moved
+    );
+    let mut out = vec![InsComponentDiff {
+        name: "Opcode".to_string(),
+        left: left_ins.map(|i| i.mnemonic.clone()),
+        right: right_ins.map(|i| i.mnemonic.clone()),
+        differs: left_ins.map(|i| (i.op, &i.mnemonic)) != right_ins.map(|i| (i.op, &i.mnemonic)),
+    }];
+
+    let operands = |diff: &ObjInsDiff| -> Vec<(String, bool)> {
+        let Some(ins) = &diff.ins else { return vec![] };
+        ins.args
+            .iter()
+            .enumerate()
+            .filter(|(_, arg)| !matches!(arg, ObjInsArg::PlainText(_)))
+            .map(|(i, arg)| {
+                (operand_text(ins, arg), diff.arg_diff.get(i).is_some_and(|d| d.is_some()))
+            })
+            .collect()
+    };
+    let left_operands = operands(left);
+    let right_operands = operands(right);
+    for i in 0..left_operands.len().max(right_operands.len()) {
+        let (l, r) = (left_operands.get(i), right_operands.get(i));
+        out.push(InsComponentDiff {
+            name: format!("Operand {}", i + 1),
+            left: l.map(|(s, _)| s.clone()),
+            right: r.map(|(s, _)| s.clone()),
+            differs: replaced
+                || l.is_none()
+                || r.is_none()
+                || l.is_some_and(|(_, d)| *d)
+                || r.is_some_and(|(_, d)| *d),
+        });
+    }
+
+    let left_reloc = left_ins.and_then(|i| i.reloc.as_ref());
+    let right_reloc = right_ins.and_then(|i| i.reloc.as_ref());
+    if left_reloc.is_some() || right_reloc.is_some() {
+        let reloc_differs = |diff: &ObjInsDiff| {
+            diff.ins.as_ref().is_some_and(|ins| {
+                ins.args
+                    .iter()
+                    .zip(&diff.arg_diff)
+                    .any(|(arg, d)| matches!(arg, ObjInsArg::Reloc) && d.is_some())
+            })
+        };
+        out.push(InsComponentDiff {
+            name: "Relocation".to_string(),
+            left: left_reloc.map(reloc_text),
+            right: right_reloc.map(reloc_text),
+            differs: replaced
+                || left_reloc.is_none()
+                || right_reloc.is_none()
+                || reloc_differs(left)
+                || reloc_differs(right),
+        });
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use object::RelocationFlags;
+
+    use super::*;
+
+    /// Builds code from (op, relocation target) pairs.
+    fn code(insts: &[(u16, Option<&str>)]) -> ProcessCodeResult {
+        let insts = insts
+            .iter()
+            .enumerate()
+            .map(|(idx, &(op, target))| ObjIns {
+                address: idx as u64 * 4,
+                size: 4,
+                op,
+                mnemonic: format!("op{op}"),
+                args: vec![],
+                reloc: target.map(|name| ObjReloc {
+                    flags: RelocationFlags::Elf { r_type: 0 },
+                    address: idx as u64 * 4,
+                    target: ObjSymbol {
+                        name: name.to_string(),
+                        demangled_name: None,
+                        has_extab: false,
+                        extab_name: None,
+                        extabindex_name: None,
+                        address: 0,
+                        section_address: 0,
+                        size: 0,
+                        size_known: false,
+                        flags: Default::default(),
+                        addend: 0,
+                        virtual_address: None,
+                        normalized_name: None,
+                    },
+                    target_section: None,
+                }),
+                branch_dest: None,
+                line: None,
+                formatted: String::new(),
+                orig: None,
+                canonical: None,
+            })
+            .collect::<Vec<_>>();
+        ProcessCodeResult { ops: insts.iter().map(|i| i.op).collect(), insts }
+    }
+
+    fn deadline() -> Instant { Instant::now() + Duration::from_secs(5) }
+
+    /// Diffs with relocation anchors, returning the rows' (left, right) addresses.
+    fn diff_rows(
+        left: &ProcessCodeResult,
+        right: &ProcessCodeResult,
+    ) -> Vec<(Option<u64>, Option<u64>)> {
+        let config = DiffObjConfig {
+            diff_alignment: DiffAlignment::RelocationAnchors,
+            ..Default::default()
+        };
+        let (mut left_diff, mut right_diff) = (vec![], vec![]);
+        diff_instructions(&mut left_diff, &mut right_diff, left, right, &config).unwrap();
+        assert_eq!(left_diff.len(), right_diff.len());
+        let address = |d: &ObjInsDiff| d.ins.as_ref().map(|i| i.address);
+        left_diff.iter().map(address).zip(right_diff.iter().map(address)).collect()
+    }
+
+    #[test]
+    fn shared_anchor_stays_aligned() {
+        // The right side is missing an instruction before the call and has an extra one after
+        let left = code(&[(1, None), (2, None), (9, Some("foo")), (3, None)]);
+        let right = code(&[(1, None), (9, Some("foo")), (3, None), (3, None)]);
+        assert_eq!(find_anchors(&left, &right, deadline()), vec![(2, 1)]);
+        assert_eq!(diff_rows(&left, &right), vec![
+            (Some(0), Some(0)),
+            (Some(4), None),
+            (Some(8), Some(4)),
+            (Some(12), Some(8)),
+            (None, Some(12)),
+        ]);
+    }
+
+    #[test]
+    fn no_anchors_diffs_whole_range() {
+        let left = code(&[(1, None), (2, None), (3, None)]);
+        let right = code(&[(1, None), (3, None)]);
+        assert!(find_anchors(&left, &right, deadline()).is_empty());
+        // Same opcode but a different target isn't an anchor
+        let left_reloc = code(&[(9, Some("foo"))]);
+        let right_reloc = code(&[(9, Some("bar"))]);
+        assert!(find_anchors(&left_reloc, &right_reloc, deadline()).is_empty());
+        assert_eq!(diff_rows(&left, &right), vec![
+            (Some(0), Some(0)),
+            (Some(4), None),
+            (Some(8), Some(4)),
+        ]);
+    }
+
+    #[test]
+    fn reordered_anchors_keep_order() {
+        let left = code(&[(9, Some("foo")), (1, None), (9, Some("bar"))]);
+        let right = code(&[(9, Some("bar")), (1, None), (9, Some("foo"))]);
+        // Only one of the swapped calls can be an anchor without crossing the other
+        let anchors = find_anchors(&left, &right, deadline());
+        assert_eq!(anchors.len(), 1);
+        let (l, r) = anchors[0];
+        assert_eq!(
+            left.insts[l].reloc.as_ref().unwrap().target.name,
+            right.insts[r].reloc.as_ref().unwrap().target.name
+        );
+        // The anchor shares a row, and every instruction appears once on its side
+        let rows = diff_rows(&left, &right);
+        assert!(rows.contains(&(Some(left.insts[l].address), Some(right.insts[r].address))));
+        assert_eq!(rows.iter().filter(|row| row.0.is_some()).count(), 3);
+        assert_eq!(rows.iter().filter(|row| row.1.is_some()).count(), 3);
+    }
+
+    /// Compares two single PowerPC instructions after applying the instruction rules.
+    #[cfg(feature = "ppc")]
+    fn compare_ppc(config: &DiffObjConfig, left: u32, right: u32) -> ObjInsDiffKind {
+        use object::Architecture;
+
+        use crate::arch::{ppc::ObjArchPpc, InsNormalizer};
+
+        let arch = ObjArchPpc::with_custom_relocations(vec![]);
+        let normalizer = InsNormalizer::new(Architecture::PowerPc, &arch, config).unwrap();
+        let process = |code: u32| {
+            let mut result = arch
+                .process_code(0, &code.to_be_bytes(), 0, &[], &BTreeMap::new(), config)
+                .unwrap();
+            for ins in &mut result.insts {
+                normalizer.normalize(ins);
+            }
+            result.insts
+        };
+        let (left, right) = (process(left), process(right));
+        let diff = |ins: &ObjIns| ObjInsDiff { ins: Some(ins.clone()), ..Default::default() };
+        let mut state = InsDiffState::default();
+        compare_ins(
+            &arch,
+            config,
+            &diff(&left[0]),
+            &diff(&right[0]),
+            (&left, &right),
+            (0, 0),
+            &mut state,
+        )
+        .unwrap()
+        .kind
+    }
+
+    #[test]
+    #[cfg(feature = "ppc")]
+    fn instruction_rules_match_equivalent_moves() {
+        // addi r3, r4, 0x0 and mr r3, r4
+        let (addi, mr) = (0x38640000, 0x7c832378);
+        let mut config = DiffObjConfig::default();
+        assert_eq!(compare_ppc(&config, addi, mr), ObjInsDiffKind::None);
+        // Setting the architecture's rules replaces the defaults
+        config.instruction_rules.insert("ppc".to_string(), vec![]);
+        assert_eq!(compare_ppc(&config, addi, mr), ObjInsDiffKind::Replace);
+    }
+}
@@ -1,16 +1,153 @@
 use std::{
     cmp::{max, min, Ordering},
+    hash::{Hash, Hasher},
+    ops::Range,
     time::{Duration, Instant},
 };
 
 use anyhow::{anyhow, Result};
-use similar::{capture_diff_slices_deadline, get_diff_ratio, Algorithm};
+use similar::{capture_diff_slices_deadline, get_diff_ratio, Algorithm, DiffOp};
 
 use crate::{
-    diff::{ObjDataDiff, ObjDataDiffKind, ObjSectionDiff, ObjSymbolDiff},
-    obj::{ObjInfo, ObjSection, SymbolRef},
+    diff::{
+        code::reloc_eq, DiffObjConfig, ObjDataDiff, ObjDataDiffKind, ObjSectionDiff, ObjSymbolDiff,
+        SymbolVisibilityFilter,
+    },
+    obj::{ObjInfo, ObjReloc, ObjSection, SymbolRef},
 };
 
+/// Largest gap between relocations that can still be grouped, e.g. pointers within a struct array.
+const MAX_RELOC_GROUP_STRIDE: u64 = 16;
+
+/// A run of relocations of the same kind at a constant stride, such as a pointer table.
+#[derive(Debug, Clone)]
+pub struct ObjDataRelocGroup<'a> {
+    /// Address of the first relocation
+    pub address: u64,
+    /// Distance between consecutive relocations, or 0 for a single relocation
+    pub stride: u64,
+    pub relocs: Vec<&'a ObjReloc>,
+}
+
+/// Groups consecutive data relocations of the same kind that are evenly spaced.
+pub fn group_data_relocs(relocations: &[ObjReloc]) -> Vec<ObjDataRelocGroup<'_>> {
+    let mut relocs = relocations.iter().collect::<Vec<_>>();
+    relocs.sort_by_key(|r| r.address);
+    let mut groups = Vec::<ObjDataRelocGroup>::new();
+    for reloc in relocs {
+        if let Some(group) = groups.last_mut() {
+            let last = group.relocs.last().unwrap();
+            let gap = reloc.address - last.address;
+            let stride_matches = if group.relocs.len() == 1 {
+                gap > 0 && gap <= MAX_RELOC_GROUP_STRIDE
+            } else {
+                gap == group.stride
+            };
+            if stride_matches && reloc.flags == last.flags {
+                group.stride = gap;
+                group.relocs.push(reloc);
+                continue;
+            }
+        }
+        groups.push(ObjDataRelocGroup { address: reloc.address, stride: 0, relocs: vec![reloc] });
+    }
+    groups
+}
+
+/// A byte of data, with the relocation starting at it. Tokens only compare by byte and whether
+/// a relocation starts there, see [`diff_data_tokens`] for how relocations are compared.
+#[derive(Copy, Clone)]
+struct DataToken<'a> {
+    byte: u8,
+    reloc: Option<&'a ObjReloc>,
+}
+
+impl DataToken<'_> {
+    fn key(&self) -> (u8, bool) { (self.byte, self.reloc.is_some()) }
+}
+
+impl PartialEq for DataToken<'_> {
+    fn eq(&self, other: &Self) -> bool { self.key() == other.key() }
+}
+
+impl Eq for DataToken<'_> {}
+
+impl PartialOrd for DataToken<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for DataToken<'_> {
+    fn cmp(&self, other: &Self) -> Ordering { self.key().cmp(&other.key()) }
+}
+
+impl Hash for DataToken<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.key().hash(state) }
+}
+
+/// Tokenizes a range of section data for diffing.
+fn data_tokens(section: &ObjSection, range: Range<usize>) -> Vec<DataToken<'_>> {
+    let mut tokens = section.data[range.clone()]
+        .iter()
+        .map(|&byte| DataToken { byte, reloc: None })
+        .collect::<Vec<_>>();
+    for reloc in &section.relocations {
+        let address = reloc.address as usize;
+        if range.contains(&address) {
+            tokens[address - range.start].reloc = Some(reloc);
+        }
+    }
+    tokens
+}
+
+/// Diffs tokenized data, then marks equal bytes starting relocations that differ as replaced.
+/// Relocations are compared the same way as in code diffs, so that pointers to different symbols
+/// differ even when the bytes, usually zero in relocatable objects, are equal.
+fn diff_data_tokens(
+    left: &[DataToken],
+    right: &[DataToken],
+    config: &DiffObjConfig,
+    deadline: Instant,
+) -> Vec<DiffOp> {
+    let ops = capture_diff_slices_deadline(Algorithm::Patience, left, right, Some(deadline));
+    let mut out = Vec::with_capacity(ops.len());
+    for op in ops {
+        let DiffOp::Equal { old_index, new_index, len } = op else {
+            out.push(op);
+            continue;
+        };
+        let mut start = 0;
+        for i in 0..len {
+            let (left_reloc, right_reloc) = (left[old_index + i].reloc, right[new_index + i].reloc);
+            if left_reloc.is_none() || reloc_eq(config, left_reloc, right_reloc) {
+                continue;
+            }
+            if i > start {
+                let len = i - start;
+                out.push(DiffOp::Equal {
+                    old_index: old_index + start,
+                    new_index: new_index + start,
+                    len,
+                });
+            }
+            out.push(DiffOp::Replace {
+                old_index: old_index + i,
+                old_len: 1,
+                new_index: new_index + i,
+                new_len: 1,
+            });
+            start = i + 1;
+        }
+        if len > start {
+            out.push(DiffOp::Equal {
+                old_index: old_index + start,
+                new_index: new_index + start,
+                len: len - start,
+            });
+        }
+    }
+    out
+}
+
 pub fn diff_bss_symbol(
     left_obj: &ObjInfo,
     right_obj: &ObjInfo,
@@ -26,18 +163,26 @@ pub fn diff_bss_symbol(
             diff_symbol: Some(right_symbol_ref),
             instructions: vec![],
             match_percent: Some(percent),
+            regalloc_match_percent: None,
         },
         ObjSymbolDiff {
             symbol_ref: right_symbol_ref,
             diff_symbol: Some(left_symbol_ref),
             instructions: vec![],
             match_percent: Some(percent),
+            regalloc_match_percent: None,
         },
     ))
 }
 
 pub fn no_diff_symbol(_obj: &ObjInfo, symbol_ref: SymbolRef) -> ObjSymbolDiff {
-    ObjSymbolDiff { symbol_ref, diff_symbol: None, instructions: vec![], match_percent: None }
+    ObjSymbolDiff {
+        symbol_ref,
+        diff_symbol: None,
+        instructions: vec![],
+        match_percent: None,
+        regalloc_match_percent: None,
+    }
 }
 
 /// Compare the data sections of two object files.
@@ -46,16 +191,16 @@ pub fn diff_data_section(
     right: &ObjSection,
     left_section_diff: &ObjSectionDiff,
     right_section_diff: &ObjSectionDiff,
+    config: &DiffObjConfig,
 ) -> Result<(ObjSectionDiff, ObjSectionDiff)> {
     let deadline = Instant::now() + Duration::from_secs(5);
     let left_max =
         left.symbols.iter().map(|s| s.section_address + s.size).max().unwrap_or(0).min(left.size);
     let right_max =
         right.symbols.iter().map(|s| s.section_address + s.size).max().unwrap_or(0).min(right.size);
-    let left_data = &left.data[..left_max as usize];
-    let right_data = &right.data[..right_max as usize];
-    let ops =
-        capture_diff_slices_deadline(Algorithm::Patience, left_data, right_data, Some(deadline));
+    let left_data = data_tokens(left, 0..left_max as usize);
+    let right_data = data_tokens(right, 0..right_max as usize);
+    let ops = diff_data_tokens(&left_data, &right_data, config, deadline);
     let match_percent = get_diff_ratio(&ops, left_data.len(), right_data.len()) * 100.0;
 
     let mut left_diff = Vec::<ObjDataDiff>::new();
@@ -126,8 +271,13 @@ pub fn diff_data_section(
         }
     }
 
-    let (mut left_section_diff, mut right_section_diff) =
-        diff_generic_section(left, right, left_section_diff, right_section_diff)?;
+    let (mut left_section_diff, mut right_section_diff) = diff_generic_section(
+        left,
+        right,
+        left_section_diff,
+        right_section_diff,
+        SymbolVisibilityFilter::All,
+    )?;
     left_section_diff.data_diff = left_diff;
     right_section_diff.data_diff = right_diff;
     // Use the highest match percent between two options:
@@ -145,6 +295,7 @@ pub fn diff_data_symbol(
     right_obj: &ObjInfo,
     left_symbol_ref: SymbolRef,
     right_symbol_ref: SymbolRef,
+    config: &DiffObjConfig,
 ) -> Result<(ObjSymbolDiff, ObjSymbolDiff)> {
     let (left_section, left_symbol) = left_obj.section_symbol(left_symbol_ref);
     let (right_section, right_symbol) = right_obj.section_symbol(right_symbol_ref);
@@ -152,14 +303,19 @@ pub fn diff_data_symbol(
     let left_section = left_section.ok_or_else(|| anyhow!("Data symbol section not found"))?;
     let right_section = right_section.ok_or_else(|| anyhow!("Data symbol section not found"))?;
 
-    let left_data = &left_section.data[left_symbol.section_address as usize
-        ..(left_symbol.section_address + left_symbol.size) as usize];
-    let right_data = &right_section.data[right_symbol.section_address as usize
-        ..(right_symbol.section_address + right_symbol.size) as usize];
+    let left_data = data_tokens(
+        left_section,
+        left_symbol.section_address as usize
+            ..(left_symbol.section_address + left_symbol.size) as usize,
+    );
+    let right_data = data_tokens(
+        right_section,
+        right_symbol.section_address as usize
+            ..(right_symbol.section_address + right_symbol.size) as usize,
+    );
 
     let deadline = Instant::now() + Duration::from_secs(5);
-    let ops =
-        capture_diff_slices_deadline(Algorithm::Patience, left_data, right_data, Some(deadline));
+    let ops = diff_data_tokens(&left_data, &right_data, config, deadline);
     let match_percent = get_diff_ratio(&ops, left_data.len(), right_data.len()) * 100.0;
 
     Ok((
@@ -168,12 +324,14 @@ pub fn diff_data_symbol(
             diff_symbol: Some(right_symbol_ref),
             instructions: vec![],
             match_percent: Some(match_percent),
+            regalloc_match_percent: None,
         },
         ObjSymbolDiff {
             symbol_ref: right_symbol_ref,
             diff_symbol: Some(left_symbol_ref),
             instructions: vec![],
             match_percent: Some(match_percent),
+            regalloc_match_percent: None,
         },
     ))
 }
@@ -185,16 +343,21 @@ pub fn diff_generic_section(
     _right: &ObjSection,
     left_diff: &ObjSectionDiff,
     _right_diff: &ObjSectionDiff,
+    visibility: SymbolVisibilityFilter,
 ) -> Result<(ObjSectionDiff, ObjSectionDiff)> {
-    let match_percent = if left_diff.symbols.iter().all(|d| d.match_percent == Some(100.0)) {
+    let symbols = || {
+        left.symbols.iter().zip(left_diff.symbols.iter()).filter(|(s, _)| visibility.includes(s))
+    };
+    let total_size = match visibility {
+        SymbolVisibilityFilter::All => left.size,
+        _ => symbols().map(|(s, _)| s.size).sum(),
+    };
+    let match_percent = if total_size == 0 || symbols().all(|(_, d)| d.match_percent == Some(100.0))
+    {
         100.0 // Avoid fp precision issues
     } else {
-        left.symbols
-            .iter()
-            .zip(left_diff.symbols.iter())
-            .map(|(s, d)| d.match_percent.unwrap_or(0.0) * s.size as f32)
-            .sum::<f32>()
-            / left.size as f32
+        symbols().map(|(s, d)| d.match_percent.unwrap_or(0.0) * s.size as f32).sum::<f32>()
+            / total_size as f32
     };
     Ok((
         ObjSectionDiff { symbols: vec![], data_diff: vec![], match_percent: Some(match_percent) },
@@ -223,7 +386,8 @@ pub fn diff_bss_section(
     // Use the highest match percent between two options:
     // - Left symbols matching right symbols by name
     // - Diff of the addresses and sizes of each symbol
-    let (generic_diff, _) = diff_generic_section(left, right, left_diff, right_diff)?;
+    let (generic_diff, _) =
+        diff_generic_section(left, right, left_diff, right_diff, SymbolVisibilityFilter::All)?;
     if generic_diff.match_percent.unwrap_or(-1.0) > match_percent {
         match_percent = generic_diff.match_percent.unwrap();
     }
@@ -2,9 +2,69 @@ use std::cmp::Ordering;
 
 use crate::{
     diff::{ObjInsArgDiff, ObjInsDiff},
-    obj::{ObjInsArg, ObjInsArgValue, ObjReloc, ObjSymbol},
+    obj::{ObjInsArg, ObjInsArgValue, ObjReloc, ObjSection, ObjSymbol},
 };
 
+/// How instruction and branch target addresses are displayed.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Eq,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+)]
+pub enum AddressMode {
+    #[default]
+    #[strum(
+        message = "Function-relative (default)",
+        detailed_message = "Offset from the function start."
+    )]
+    FunctionRelative,
+    #[strum(message = "Section-relative", detailed_message = "Offset from the section start.")]
+    SectionRelative,
+    #[strum(
+        message = "Symbol-relative",
+        detailed_message = "Like function-relative, with branch targets shown as foo+0x1c."
+    )]
+    SymbolRelative,
+    #[strum(
+        message = "Virtual",
+        detailed_message = "Virtual address from the project's split metadata, if available."
+    )]
+    Virtual,
+}
+
+impl AddressMode {
+    /// The `base_addr` to pass to [`display_diff`] for this mode.
+    pub fn base_address(self, section: &ObjSection, symbol: &ObjSymbol) -> u64 {
+        match self {
+            AddressMode::FunctionRelative | AddressMode::SymbolRelative => symbol.address,
+            AddressMode::SectionRelative => section.address,
+            AddressMode::Virtual => match symbol.virtual_address {
+                // Wraps when the virtual address is above the symbol's address
+                Some(virtual_address) => symbol.address.wrapping_sub(virtual_address),
+                None => symbol.address,
+            },
+        }
+    }
+
+    /// Formats a branch target, already relative to [`AddressMode::base_address`].
+    pub fn branch_dest_text(self, symbol: &ObjSymbol, dest: u64) -> String {
+        match self {
+            AddressMode::SymbolRelative => {
+                let name = symbol.demangled_name.as_ref().unwrap_or(&symbol.name);
+                format!("{name}+0x{dest:x}")
+            }
+            _ => format!("{dest:x}"),
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub enum DiffText<'a> {
     /// Basic text
@@ -21,8 +81,8 @@ pub enum DiffText<'a> {
     Argument(&'a ObjInsArgValue, Option<&'a ObjInsArgDiff>),
     /// Branch destination
     BranchDest(u64, Option<&'a ObjInsArgDiff>),
-    /// Symbol name
-    Symbol(&'a ObjSymbol),
+    /// Relocation target symbol name
+    Symbol(&'a ObjSymbol, Option<&'a ObjInsArgDiff>),
     /// Number of spaces
     Spacing(usize),
     /// End of line
@@ -51,7 +111,7 @@ pub fn display_diff<E>(
     if let Some(line) = ins.line {
         cb(DiffText::Line(line as usize))?;
     }
-    cb(DiffText::Address(ins.address - base_addr))?;
+    cb(DiffText::Address(ins.address.wrapping_sub(base_addr)))?;
     if let Some(branch) = &ins_diff.branch_from {
         cb(DiffText::BasicColor(" ~> ", branch.branch_idx))?;
     } else {
@@ -71,10 +131,12 @@ pub fn display_diff<E>(
                 cb(DiffText::Argument(v, diff))?;
             }
             ObjInsArg::Reloc => {
-                display_reloc_name(ins.reloc.as_ref().unwrap(), &mut cb)?;
+                display_reloc_name(ins.reloc.as_ref().unwrap(), diff, &mut cb)?;
             }
             ObjInsArg::BranchDest(dest) => {
-                if let Some(dest) = dest.checked_sub(base_addr) {
+                // Relative to the same base as the instruction address
+                let dest = dest.wrapping_sub(base_addr);
+                if (dest as i64) >= 0 {
                     cb(DiffText::BranchDest(dest, diff))?;
                 } else {
                     cb(DiffText::Basic("<unknown>"))?;
@@ -91,9 +153,10 @@ pub fn display_diff<E>(
 
 fn display_reloc_name<E>(
     reloc: &ObjReloc,
+    diff: Option<&ObjInsArgDiff>,
     mut cb: impl FnMut(DiffText) -> Result<(), E>,
 ) -> Result<(), E> {
-    cb(DiffText::Symbol(&reloc.target))?;
+    cb(DiffText::Symbol(&reloc.target, diff))?;
     match reloc.target.addend.cmp(&0i64) {
         Ordering::Greater => cb(DiffText::Basic(&format!("+{:#x}", reloc.target.addend))),
         Ordering::Less => cb(DiffText::Basic(&format!("-{:#x}", -reloc.target.addend))),
@@ -106,7 +169,7 @@ impl PartialEq<DiffText<'_>> for HighlightKind {
         match (self, other) {
             (HighlightKind::Opcode(a), DiffText::Opcode(_, b)) => a == b,
             (HighlightKind::Arg(a), DiffText::Argument(b, _)) => a.loose_eq(b),
-            (HighlightKind::Symbol(a), DiffText::Symbol(b)) => a == &b.name,
+            (HighlightKind::Symbol(a), DiffText::Symbol(b, _)) => a == &b.name,
             (HighlightKind::Address(a), DiffText::Address(b) | DiffText::BranchDest(b, _)) => {
                 a == b
             }
@@ -124,9 +187,87 @@ impl From<DiffText<'_>> for HighlightKind {
         match value {
             DiffText::Opcode(_, op) => HighlightKind::Opcode(op),
             DiffText::Argument(arg, _) => HighlightKind::Arg(arg.clone()),
-            DiffText::Symbol(sym) => HighlightKind::Symbol(sym.name.to_string()),
+            DiffText::Symbol(sym, _) => HighlightKind::Symbol(sym.name.to_string()),
             DiffText::Address(addr) | DiffText::BranchDest(addr, _) => HighlightKind::Address(addr),
             _ => HighlightKind::None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::ObjSectionKind;
+
+    fn section() -> ObjSection {
+        ObjSection {
+            name: ".text".to_string(),
+            kind: ObjSectionKind::Code,
+            address: 0x1000,
+            size: 0x100,
+            data: vec![],
+            orig_index: 0,
+            symbols: vec![],
+            relocations: vec![],
+            virtual_address: Some(0x80004000),
+            line_info: Default::default(),
+            inline_ranges: vec![],
+        }
+    }
+
+    fn symbol(virtual_address: Option<u64>) -> ObjSymbol {
+        ObjSymbol {
+            name: "foo".to_string(),
+            demangled_name: Some("foo()".to_string()),
+            has_extab: false,
+            extab_name: None,
+            extabindex_name: None,
+            address: 0x1040,
+            section_address: 0x40,
+            size: 0x20,
+            size_known: true,
+            flags: Default::default(),
+            addend: 0,
+            virtual_address,
+            normalized_name: None,
+        }
+    }
+
+    /// Displayed address of the instruction 0x8 into the symbol.
+    fn displayed(mode: AddressMode, symbol: &ObjSymbol) -> u64 {
+        (symbol.address + 0x8).wrapping_sub(mode.base_address(&section(), symbol))
+    }
+
+    #[test]
+    fn function_relative() {
+        let symbol = symbol(None);
+        assert_eq!(displayed(AddressMode::FunctionRelative, &symbol), 0x8);
+        assert_eq!(AddressMode::FunctionRelative.branch_dest_text(&symbol, 0x1c), "1c");
+    }
+
+    #[test]
+    fn section_relative() {
+        let symbol = symbol(None);
+        assert_eq!(displayed(AddressMode::SectionRelative, &symbol), 0x48);
+        assert_eq!(AddressMode::SectionRelative.branch_dest_text(&symbol, 0x5c), "5c");
+    }
+
+    #[test]
+    fn symbol_relative() {
+        let symbol = symbol(None);
+        assert_eq!(displayed(AddressMode::SymbolRelative, &symbol), 0x8);
+        assert_eq!(AddressMode::SymbolRelative.branch_dest_text(&symbol, 0x1c), "foo()+0x1c");
+    }
+
+    #[test]
+    fn virtual_address() {
+        // Above the symbol's address, where the base address wraps
+        let above = symbol(Some(0x80004040));
+        assert_eq!(displayed(AddressMode::Virtual, &above), 0x80004048);
+        assert_eq!(AddressMode::Virtual.branch_dest_text(&above, 0x8000405c), "8000405c");
+        // Below the symbol's address
+        assert_eq!(displayed(AddressMode::Virtual, &symbol(Some(0x40))), 0x48);
+        // Without split metadata, falls back to function-relative
+        assert_eq!(displayed(AddressMode::Virtual, &symbol(None)), 0x8);
+    }
+}
@@ -1,8 +1,16 @@
-use std::collections::HashSet;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet, HashSet},
+    path::PathBuf,
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use regex::Regex;
 
 use crate::{
+    arch::{CustomRelocation, InstructionRule},
     diff::{
         code::{diff_code, no_diff_code, process_code_symbol},
         data::{
@@ -10,12 +18,58 @@ use crate::{
             diff_generic_section, no_diff_symbol,
         },
     },
-    obj::{ObjInfo, ObjIns, ObjSection, ObjSectionKind, ObjSymbol, SymbolRef},
+    obj::{
+        ObjInfo, ObjIns, ObjSection, ObjSectionKind, ObjSymbol, ObjSymbolFlags, SymbolIdentity,
+        SymbolIdentityPolicy, SymbolRef,
+    },
 };
 
 pub mod code;
 pub mod data;
 pub mod display;
+pub mod text;
+
+/// Demangler filling in symbols' demangled names.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Eq,
+    PartialEq,
+    Hash,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+)]
+pub enum Demangler {
+    #[default]
+    #[strum(message = "Auto (default)", detailed_message = "Chosen by the architecture.")]
+    Auto,
+    #[strum(message = "None", detailed_message = "Show mangled names.")]
+    None,
+    #[strum(message = "Itanium", detailed_message = "GCC and Clang C++ names.")]
+    Itanium,
+    #[strum(message = "MSVC", detailed_message = "Visual C++ names.")]
+    Msvc,
+    #[strum(message = "MWCC", detailed_message = "Metrowerks CodeWarrior C++ names.")]
+    Mwcc,
+}
+
+impl Demangler {
+    /// Parses a demangler name as used in project configs.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "auto" => Demangler::Auto,
+            "none" => Demangler::None,
+            "itanium" | "gcc" => Demangler::Itanium,
+            "msvc" => Demangler::Msvc,
+            "mwcc" | "codewarrior" => Demangler::Mwcc,
+            _ => return None,
+        })
+    }
+}
 
 #[derive(
     Debug,
@@ -145,6 +199,231 @@ pub enum ArmR9Usage {
     Tr,
 }
 
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Eq,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+)]
+pub enum DiffAlignment {
+    #[default]
+    #[strum(
+        message = "Opcode (default)",
+        detailed_message = "Align instructions by opcode across the whole function."
+    )]
+    Opcode,
+    #[strum(
+        message = "Relocation anchors",
+        detailed_message = "Anchor alignment on instructions referencing the same symbol, \
+        limiting drift after a local difference."
+    )]
+    RelocationAnchors,
+}
+
+/// The unit that code is compared in.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Eq,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+)]
+pub enum CodeDiffGranularity {
+    #[default]
+    #[strum(
+        message = "Instructions (default)",
+        detailed_message = "Disassemble and compare instructions."
+    )]
+    Instruction,
+    #[strum(
+        message = "Bytes",
+        detailed_message = "Compare raw bytes, for when instruction alignment misbehaves."
+    )]
+    Byte,
+}
+
+/// How instructions differing only in their relocation targets count towards the score.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Eq,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+)]
+pub enum RelocDiffScore {
+    #[default]
+    #[strum(
+        message = "Mismatch (default)",
+        detailed_message = "Count relocation-only differences as full mismatches."
+    )]
+    Mismatch,
+    #[strum(
+        message = "Half mismatch",
+        detailed_message = "Count relocation-only differences as half a mismatch."
+    )]
+    Half,
+    #[strum(
+        message = "Ignore",
+        detailed_message = "Don't count relocation-only differences. They're still highlighted."
+    )]
+    Ignore,
+}
+
+impl RelocDiffScore {
+    /// Fraction of a mismatch each relocation-only difference counts as.
+    pub fn weight(self) -> f32 {
+        match self {
+            RelocDiffScore::Mismatch => 1.0,
+            RelocDiffScore::Half => 0.5,
+            RelocDiffScore::Ignore => 0.0,
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Eq,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+    strum::EnumString,
+)]
+#[strum(serialize_all = "snake_case")]
+pub enum SymbolVisibilityFilter {
+    #[default]
+    #[strum(message = "All symbols (default)", detailed_message = "Count every code symbol.")]
+    All,
+    #[strum(
+        message = "External symbols",
+        detailed_message = "Count only global and weak symbols, ignoring local helpers."
+    )]
+    External,
+    #[strum(message = "Global symbols", detailed_message = "Count only global symbols.")]
+    Global,
+}
+
+impl SymbolVisibilityFilter {
+    /// Whether the symbol counts towards aggregate match percentages.
+    pub fn includes(self, symbol: &ObjSymbol) -> bool {
+        let flags = symbol.flags.0;
+        match self {
+            SymbolVisibilityFilter::All => true,
+            SymbolVisibilityFilter::External => {
+                flags.contains(ObjSymbolFlags::Global) || flags.contains(ObjSymbolFlags::Weak)
+            }
+            SymbolVisibilityFilter::Global => flags.contains(ObjSymbolFlags::Global),
+        }
+    }
+}
+
+/// Rewrites symbol names before pairing them across objects. Displayed names are unchanged.
+#[derive(Debug, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolNameRule {
+    StripPrefix(String),
+    StripSuffix(String),
+    /// Regex replacement, with `$1`-style capture group references
+    Replace {
+        pattern: String,
+        replacement: String,
+    },
+}
+
+enum CompiledNameRule<'a> {
+    StripPrefix(&'a str),
+    StripSuffix(&'a str),
+    Replace(Regex, &'a str),
+}
+
+/// Applies [`SymbolNameRule`]s in order.
+struct SymbolNameNormalizer<'a> {
+    rules: Vec<CompiledNameRule<'a>>,
+}
+
+impl<'a> SymbolNameNormalizer<'a> {
+    fn new(rules: &'a [SymbolNameRule]) -> Result<Self> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                Ok(match rule {
+                    SymbolNameRule::StripPrefix(s) => CompiledNameRule::StripPrefix(s),
+                    SymbolNameRule::StripSuffix(s) => CompiledNameRule::StripSuffix(s),
+                    SymbolNameRule::Replace { pattern, replacement } => CompiledNameRule::Replace(
+                        Regex::new(pattern)
+                            .with_context(|| format!("Invalid symbol name pattern '{pattern}'"))?,
+                        replacement,
+                    ),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    fn normalize<'n>(&self, name: &'n str) -> Cow<'n, str> {
+        let mut name = Cow::Borrowed(name);
+        for rule in &self.rules {
+            name = match rule {
+                CompiledNameRule::StripPrefix(prefix) => match name.strip_prefix(prefix) {
+                    Some(s) => Cow::Owned(s.to_string()),
+                    None => name,
+                },
+                CompiledNameRule::StripSuffix(suffix) => match name.strip_suffix(suffix) {
+                    Some(s) => Cow::Owned(s.to_string()),
+                    None => name,
+                },
+                CompiledNameRule::Replace(regex, replacement) => {
+                    match regex.replace_all(&name, *replacement) {
+                        Cow::Borrowed(_) => name,
+                        Cow::Owned(s) => Cow::Owned(s),
+                    }
+                }
+            };
+        }
+        name
+    }
+}
+
+/// Records each symbol's [`ObjSymbol::normalized_name`] according to `rules`.
+pub(crate) fn apply_symbol_name_rules(
+    sections: &mut [ObjSection],
+    common: &mut [ObjSymbol],
+    rules: &[SymbolNameRule],
+) -> Result<()> {
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let normalizer = SymbolNameNormalizer::new(rules)?;
+    for symbol in sections.iter_mut().flat_map(|s| s.symbols.iter_mut()).chain(common.iter_mut()) {
+        if let Cow::Owned(name) = normalizer.normalize(&symbol.name) {
+            if name != symbol.name {
+                symbol.normalized_name = Some(name);
+            }
+        }
+    }
+    Ok(())
+}
+
 #[inline]
 const fn default_true() -> bool { true }
 
@@ -155,19 +434,68 @@ pub struct DiffObjConfig {
     #[serde(default = "default_true")]
     pub space_between_args: bool,
     pub combine_data_sections: bool,
-    // x86
+    pub diff_alignment: DiffAlignment,
+    pub reloc_diff_score: RelocDiffScore,
+    pub code_diff_granularity: CodeDiffGranularity,
+    /// Instructions matching these rules are removed from both sides before diffing. A rule is
+    /// a mnemonic, optionally followed by operands that must match too (e.g. `bl mcount`).
+    pub ignored_instructions: Vec<String>,
+    /// Exclude trailing padding NOPs from the diff when the rest of the function matches
+    pub ignore_padding_nops: bool,
+    /// Treat unrelocated absolute-address operands as equal, see [`ObjArch::is_address_arg`]
+    ///
+    /// [`ObjArch::is_address_arg`]: crate::arch::ObjArch::is_address_arg
+    pub ignore_address_args: bool,
+    /// Mark reordered blocks of equal instructions as moved instead of deleted and inserted
+    pub detect_moved_blocks: bool,
+    /// Compare relocations by their resolved target address instead of the target's name
+    pub raw_reloc_diffs: bool,
+    /// Demangler for symbol names, or the architecture's usual one for [`Demangler::Auto`]
+    pub demangler: Demangler,
+    /// Symbols counted in section match percentages
+    pub match_visibility: SymbolVisibilityFilter,
+    /// Defer diffing code symbols until requested with [`diff_pending_symbol`]
+    pub lazy_code_diff: bool,
+    // PowerPC
+    /// Display simplified mnemonics (e.g. `li`) instead of the basic forms (`addi`)
+    #[serde(default = "default_true")]
+    pub ppc_simplified_mnemonics: bool,
+    // x86 (iced-x86 has no pseudo-instruction forms to switch between)
     pub x86_formatter: X86Formatter,
     // MIPS
     pub mips_abi: MipsAbi,
     pub mips_instr_category: MipsInstrCategory,
+    /// Display pseudo-instructions (e.g. `move`, `b`) instead of the real instructions
+    /// (`or`, `beq`)
+    #[serde(default = "default_true")]
+    pub mips_pseudo_instructions: bool,
     // ARM
     pub arm_arch_version: ArmArchVersion,
+    /// Disassemble as unified assembly language, which is ARM's counterpart to simplified
+    /// mnemonics
     pub arm_unified_syntax: bool,
     pub arm_av_registers: bool,
     pub arm_r9_usage: ArmR9Usage,
     pub arm_sl_usage: bool,
     pub arm_fp_usage: bool,
     pub arm_ip_usage: bool,
+    /// Project-provided relocation types, keyed by architecture
+    #[serde(skip)]
+    pub custom_relocations: BTreeMap<String, Vec<CustomRelocation>>,
+    /// Project-provided symbol name rewrites used when pairing symbols
+    #[serde(skip)]
+    pub symbol_name_rules: Vec<SymbolNameRule>,
+    /// The object's (target name, base name) symbol pairings, taking precedence over pairing
+    /// by name
+    #[serde(skip)]
+    pub symbol_mappings: Vec<(String, String)>,
+    /// Project-provided instruction normalization rules, keyed by architecture. These replace
+    /// the architecture's default rules.
+    #[serde(skip)]
+    pub instruction_rules: BTreeMap<String, Vec<InstructionRule>>,
+    /// Directories searched for `.gnu_debuglink` and `.dwo` debug files
+    #[serde(skip)]
+    pub debug_search_paths: Vec<PathBuf>,
 }
 
 impl Default for DiffObjConfig {
@@ -176,9 +504,22 @@ impl Default for DiffObjConfig {
             relax_reloc_diffs: false,
             space_between_args: true,
             combine_data_sections: false,
+            diff_alignment: Default::default(),
+            reloc_diff_score: Default::default(),
+            code_diff_granularity: Default::default(),
+            ignored_instructions: Default::default(),
+            ignore_padding_nops: false,
+            ignore_address_args: false,
+            detect_moved_blocks: false,
+            raw_reloc_diffs: false,
+            demangler: Default::default(),
+            match_visibility: Default::default(),
+            lazy_code_diff: false,
+            ppc_simplified_mnemonics: true,
             x86_formatter: Default::default(),
             mips_abi: Default::default(),
             mips_instr_category: Default::default(),
+            mips_pseudo_instructions: true,
             arm_arch_version: Default::default(),
             arm_unified_syntax: true,
             arm_av_registers: false,
@@ -186,6 +527,11 @@ impl Default for DiffObjConfig {
             arm_sl_usage: false,
             arm_fp_usage: false,
             arm_ip_usage: false,
+            custom_relocations: Default::default(),
+            symbol_name_rules: Default::default(),
+            instruction_rules: Default::default(),
+            symbol_mappings: Default::default(),
+            debug_search_paths: Default::default(),
         }
     }
 }
@@ -213,6 +559,40 @@ impl ObjSectionDiff {
         self.data_diff = other.data_diff;
         self.match_percent = other.match_percent;
     }
+
+    /// Pairs this section's symbols with those of `follower`, the diff of section
+    /// `follower_section_idx` on the other side, into rows of (own, follower) symbol indices in
+    /// this section's order. Unpaired follower symbols are placed after the row of the
+    /// preceding paired symbol.
+    pub fn aligned_rows(
+        &self,
+        follower: &ObjSectionDiff,
+        follower_section_idx: usize,
+    ) -> Vec<(Option<usize>, Option<usize>)> {
+        // Slot 0 holds follower symbols preceding any paired symbol
+        let mut slots = vec![vec![]; self.symbols.len() + 1];
+        let mut follower_rows = vec![None; follower.symbols.len()];
+        for (i, symbol_diff) in self.symbols.iter().enumerate() {
+            let pair = symbol_diff
+                .diff_symbol
+                .filter(|r| {
+                    r.section_idx == follower_section_idx && r.symbol_idx < follower_rows.len()
+                })
+                .map(|r| r.symbol_idx);
+            if let Some(j) = pair {
+                follower_rows[j] = Some(i);
+            }
+            slots[i + 1].push((Some(i), pair));
+        }
+        let mut slot = 0;
+        for (j, row) in follower_rows.into_iter().enumerate() {
+            match row {
+                Some(i) => slot = i + 1,
+                None => slots[slot].push((None, Some(j))),
+            }
+        }
+        slots.into_iter().flatten().collect()
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -221,6 +601,9 @@ pub struct ObjSymbolDiff {
     pub diff_symbol: Option<SymbolRef>,
     pub instructions: Vec<ObjInsDiff>,
     pub match_percent: Option<f32>,
+    /// Like `match_percent`, but counting instructions that differ only in register operands as
+    /// matching. Only a hint; `match_percent` is authoritative.
+    pub regalloc_match_percent: Option<f32>,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -245,6 +628,8 @@ pub enum ObjInsDiffKind {
     Replace,
     Delete,
     Insert,
+    /// Present on one side only, but equal to a block elsewhere on the other side
+    Moved,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -290,6 +675,25 @@ pub struct ObjInsBranchTo {
 pub struct ObjDiff {
     pub sections: Vec<ObjSectionDiff>,
     pub common: Vec<ObjSymbolDiff>,
+    /// Code symbols not yet diffed, see [`DiffObjConfig::lazy_code_diff`]
+    pub pending: BTreeSet<SymbolRef>,
+    /// Previous build's counterpart of each pending symbol, see [`diff_pending_symbol`]
+    pub pending_prev: BTreeMap<SymbolRef, SymbolRef>,
+    /// How each paired symbol was matched with its counterpart
+    pub pair_confidence: BTreeMap<SymbolRef, PairConfidence>,
+}
+
+/// How trustworthy a symbol pairing is, based on how it was found.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum PairConfidence {
+    /// Paired by address alone (compiler-generated symbols)
+    Low,
+    /// Paired by name after normalization, e.g. symbol name rules or Metrowerks `$1234` suffixes
+    Medium,
+    /// Paired by exact name
+    High,
+    /// Paired by the object's symbol mappings
+    Mapped,
 }
 
 impl ObjDiff {
@@ -297,6 +701,9 @@ impl ObjDiff {
         let mut result = Self {
             sections: Vec::with_capacity(obj.sections.len()),
             common: Vec::with_capacity(obj.common.len()),
+            pending: BTreeSet::new(),
+            pending_prev: BTreeMap::new(),
+            pair_confidence: BTreeMap::new(),
         };
         for (section_idx, section) in obj.sections.iter().enumerate() {
             let mut symbols = Vec::with_capacity(section.symbols.len());
@@ -306,6 +713,7 @@ impl ObjDiff {
                     diff_symbol: None,
                     instructions: vec![],
                     match_percent: None,
+                    regalloc_match_percent: None,
                 });
             }
             result.sections.push(ObjSectionDiff {
@@ -325,6 +733,7 @@ impl ObjDiff {
                 diff_symbol: None,
                 instructions: vec![],
                 match_percent: None,
+                regalloc_match_percent: None,
             });
         }
         result
@@ -372,8 +781,35 @@ pub fn diff_objs(
     right: Option<&ObjInfo>,
     prev: Option<&ObjInfo>,
 ) -> Result<DiffObjsResult> {
-    let symbol_matches = matching_symbols(left, right, prev)?;
+    let symbol_matches = matching_symbols(left, right, prev, &config.symbol_mappings)?;
     let section_matches = matching_sections(left, right)?;
+
+    // Matched functions are diffed in parallel, collected in match order to stay deterministic.
+    // With lazy_code_diff, they're instead diffed once requested.
+    #[cfg(feature = "parallel")]
+    let symbol_match_iter = symbol_matches.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let symbol_match_iter = symbol_matches.iter();
+    let mut code_diffs = symbol_match_iter
+        .filter(|_| !config.lazy_code_diff)
+        .filter_map(|symbol_match| match *symbol_match {
+            SymbolMatch {
+                left: Some(left_symbol_ref),
+                right: Some(right_symbol_ref),
+                prev: prev_symbol_ref,
+                section_kind: ObjSectionKind::Code,
+                ..
+            } => Some(diff_code_match(
+                config,
+                (left.unwrap(), left_symbol_ref),
+                (right.unwrap(), right_symbol_ref),
+                prev.zip(prev_symbol_ref),
+            )),
+            _ => None,
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter();
+
     let mut left = left.map(|p| (p, ObjDiff::new_from_obj(p)));
     let mut right = right.map(|p| (p, ObjDiff::new_from_obj(p)));
     let mut prev = prev.map(|p| (p, ObjDiff::new_from_obj(p)));
@@ -385,33 +821,34 @@ pub fn diff_objs(
                 right: Some(right_symbol_ref),
                 prev: prev_symbol_ref,
                 section_kind,
+                confidence,
             } => {
                 let (left_obj, left_out) = left.as_mut().unwrap();
                 let (right_obj, right_out) = right.as_mut().unwrap();
+                if let Some(confidence) = confidence {
+                    left_out.pair_confidence.insert(left_symbol_ref, confidence);
+                    right_out.pair_confidence.insert(right_symbol_ref, confidence);
+                }
                 match section_kind {
+                    ObjSectionKind::Code if config.lazy_code_diff => {
+                        left_out.symbol_diff_mut(left_symbol_ref).diff_symbol =
+                            Some(right_symbol_ref);
+                        right_out.symbol_diff_mut(right_symbol_ref).diff_symbol =
+                            Some(left_symbol_ref);
+                        left_out.pending.insert(left_symbol_ref);
+                        right_out.pending.insert(right_symbol_ref);
+                        if let Some(prev_symbol_ref) = prev_symbol_ref {
+                            right_out.pending_prev.insert(right_symbol_ref, prev_symbol_ref);
+                        }
+                    }
                     ObjSectionKind::Code => {
-                        let left_code = process_code_symbol(left_obj, left_symbol_ref, config)?;
-                        let right_code = process_code_symbol(right_obj, right_symbol_ref, config)?;
-                        let (left_diff, right_diff) = diff_code(
-                            &left_code,
-                            &right_code,
-                            left_symbol_ref,
-                            right_symbol_ref,
-                            config,
-                        )?;
+                        let (left_diff, right_diff, prev_diff) = code_diffs.next().unwrap();
                         *left_out.symbol_diff_mut(left_symbol_ref) = left_diff;
                         *right_out.symbol_diff_mut(right_symbol_ref) = right_diff;
-
-                        if let Some(prev_symbol_ref) = prev_symbol_ref {
-                            let (prev_obj, prev_out) = prev.as_mut().unwrap();
-                            let prev_code = process_code_symbol(prev_obj, prev_symbol_ref, config)?;
-                            let (_, prev_diff) = diff_code(
-                                &right_code,
-                                &prev_code,
-                                right_symbol_ref,
-                                prev_symbol_ref,
-                                config,
-                            )?;
+                        if let (Some(prev_symbol_ref), Some(prev_diff)) =
+                            (prev_symbol_ref, prev_diff)
+                        {
+                            let (_, prev_out) = prev.as_mut().unwrap();
                             *prev_out.symbol_diff_mut(prev_symbol_ref) = prev_diff;
                         }
                     }
@@ -421,6 +858,7 @@ pub fn diff_objs(
                             right_obj,
                             left_symbol_ref,
                             right_symbol_ref,
+                            config,
                         )?;
                         *left_out.symbol_diff_mut(left_symbol_ref) = left_diff;
                         *right_out.symbol_diff_mut(right_symbol_ref) = right_diff;
@@ -437,9 +875,12 @@ pub fn diff_objs(
                     }
                 }
             }
-            SymbolMatch { left: Some(left_symbol_ref), right: None, prev: _, section_kind } => {
+            SymbolMatch { left: Some(left_symbol_ref), right: None, section_kind, .. } => {
                 let (left_obj, left_out) = left.as_mut().unwrap();
                 match section_kind {
+                    ObjSectionKind::Code if config.lazy_code_diff => {
+                        left_out.pending.insert(left_symbol_ref);
+                    }
                     ObjSectionKind::Code => {
                         let code = process_code_symbol(left_obj, left_symbol_ref, config)?;
                         *left_out.symbol_diff_mut(left_symbol_ref) =
@@ -451,9 +892,12 @@ pub fn diff_objs(
                     }
                 }
             }
-            SymbolMatch { left: None, right: Some(right_symbol_ref), prev: _, section_kind } => {
+            SymbolMatch { left: None, right: Some(right_symbol_ref), section_kind, .. } => {
                 let (right_obj, right_out) = right.as_mut().unwrap();
                 match section_kind {
+                    ObjSectionKind::Code if config.lazy_code_diff => {
+                        right_out.pending.insert(right_symbol_ref);
+                    }
                     ObjSectionKind::Code => {
                         let code = process_code_symbol(right_obj, right_symbol_ref, config)?;
                         *right_out.symbol_diff_mut(right_symbol_ref) =
@@ -483,6 +927,8 @@ pub fn diff_objs(
             let left_section = &left_obj.sections[left_section_idx];
             let right_section = &right_obj.sections[right_section_idx];
             match section_kind {
+                // Section totals aren't known until every symbol is diffed
+                ObjSectionKind::Code if config.lazy_code_diff => {}
                 ObjSectionKind::Code => {
                     let left_section_diff = left_out.section_diff(left_section_idx);
                     let right_section_diff = right_out.section_diff(right_section_idx);
@@ -491,6 +937,7 @@ pub fn diff_objs(
                         right_section,
                         left_section_diff,
                         right_section_diff,
+                        config.match_visibility,
                     )?;
                     left_out.section_diff_mut(left_section_idx).merge(left_diff);
                     right_out.section_diff_mut(right_section_idx).merge(right_diff);
@@ -503,6 +950,7 @@ pub fn diff_objs(
                         right_section,
                         left_section_diff,
                         right_section_diff,
+                        config,
                     )?;
                     left_out.section_diff_mut(left_section_idx).merge(left_diff);
                     right_out.section_diff_mut(right_section_idx).merge(right_diff);
@@ -536,6 +984,131 @@ struct SymbolMatch {
     right: Option<SymbolRef>,
     prev: Option<SymbolRef>,
     section_kind: ObjSectionKind,
+    /// How left was paired with right
+    confidence: Option<PairConfidence>,
+}
+
+/// Computes a code symbol diff deferred by [`DiffObjConfig::lazy_code_diff`], along with its
+/// counterpart and the previous build's version. `symbol_ref` refers to the left object if
+/// `left_side`, otherwise the right. Once a pair of code sections has no pending symbols left,
+/// their match percentage is computed as well.
+pub fn diff_pending_symbol<'a>(
+    config: &DiffObjConfig,
+    mut left: Option<(&'a ObjInfo, &'a mut ObjDiff)>,
+    mut right: Option<(&'a ObjInfo, &'a mut ObjDiff)>,
+    prev: Option<(&ObjInfo, &mut ObjDiff)>,
+    symbol_ref: SymbolRef,
+    left_side: bool,
+) -> Result<()> {
+    let (this, other) = if left_side { (&mut left, &mut right) } else { (&mut right, &mut left) };
+    let Some((this_obj, this_diff)) = this else {
+        return Ok(());
+    };
+    if !this_diff.pending.remove(&symbol_ref) {
+        return Ok(());
+    }
+    match (other, this_diff.symbol_diff(symbol_ref).diff_symbol) {
+        (Some((other_obj, other_diff)), Some(other_ref)) => {
+            other_diff.pending.remove(&other_ref);
+            let ((left_obj, left_ref), (right_obj, right_ref)) = if left_side {
+                ((*this_obj, symbol_ref), (*other_obj, other_ref))
+            } else {
+                ((*other_obj, other_ref), (*this_obj, symbol_ref))
+            };
+            let right_diff = if left_side { &mut **other_diff } else { &mut **this_diff };
+            let prev_ref = right_diff.pending_prev.remove(&right_ref);
+            let prev = prev.zip(prev_ref);
+            let (l, r, prev_new) = diff_code_match(
+                config,
+                (left_obj, left_ref),
+                (right_obj, right_ref),
+                prev.as_ref().map(|((prev_obj, _), prev_ref)| (*prev_obj, *prev_ref)),
+            )?;
+            if let (Some(((_, prev_diff), prev_ref)), Some(prev_new)) = (prev, prev_new) {
+                *prev_diff.symbol_diff_mut(prev_ref) = prev_new;
+            }
+            let (this_new, other_new) = if left_side { (l, r) } else { (r, l) };
+            *this_diff.symbol_diff_mut(symbol_ref) = this_new;
+            *other_diff.symbol_diff_mut(other_ref) = other_new;
+        }
+        _ => {
+            let code = process_code_symbol(this_obj, symbol_ref, config)?;
+            *this_diff.symbol_diff_mut(symbol_ref) = no_diff_code(&code, symbol_ref)?;
+        }
+    }
+    if let (Some(left), Some(right)) = (left, right) {
+        diff_resolved_code_sections(config, left, right)?;
+    }
+    Ok(())
+}
+
+/// Computes the match percentage of paired code sections that have no pending symbols left.
+fn diff_resolved_code_sections(
+    config: &DiffObjConfig,
+    (left_obj, left_out): (&ObjInfo, &mut ObjDiff),
+    (right_obj, right_out): (&ObjInfo, &mut ObjDiff),
+) -> Result<()> {
+    for section_match in matching_sections(Some(left_obj), Some(right_obj))? {
+        let SectionMatch {
+            left: Some(left_section_idx),
+            right: Some(right_section_idx),
+            section_kind: ObjSectionKind::Code,
+        } = section_match
+        else {
+            continue;
+        };
+        if left_out.section_diff(left_section_idx).match_percent.is_some()
+            || left_out.pending.iter().any(|s| s.section_idx == left_section_idx)
+            || right_out.pending.iter().any(|s| s.section_idx == right_section_idx)
+        {
+            continue;
+        }
+        let (left_diff, right_diff) = diff_generic_section(
+            &left_obj.sections[left_section_idx],
+            &right_obj.sections[right_section_idx],
+            left_out.section_diff(left_section_idx),
+            right_out.section_diff(right_section_idx),
+            config.match_visibility,
+        )?;
+        left_out.section_diff_mut(left_section_idx).merge(left_diff);
+        right_out.section_diff_mut(right_section_idx).merge(right_diff);
+    }
+    Ok(())
+}
+
+/// Diffs a pair of matched functions, and the previous build's version against the right side.
+fn diff_code_match(
+    config: &DiffObjConfig,
+    (left_obj, left_symbol_ref): (&ObjInfo, SymbolRef),
+    (right_obj, right_symbol_ref): (&ObjInfo, SymbolRef),
+    prev: Option<(&ObjInfo, SymbolRef)>,
+) -> Result<(ObjSymbolDiff, ObjSymbolDiff, Option<ObjSymbolDiff>)> {
+    let left_code = process_code_symbol(left_obj, left_symbol_ref, config)?;
+    let right_code = process_code_symbol(right_obj, right_symbol_ref, config)?;
+    let (left_diff, right_diff) = diff_code(
+        left_obj.arch.as_ref(),
+        &left_code,
+        &right_code,
+        left_symbol_ref,
+        right_symbol_ref,
+        config,
+    )?;
+    let prev_diff = match prev {
+        Some((prev_obj, prev_symbol_ref)) => {
+            let prev_code = process_code_symbol(prev_obj, prev_symbol_ref, config)?;
+            let (_, prev_diff) = diff_code(
+                right_obj.arch.as_ref(),
+                &right_code,
+                &prev_code,
+                right_symbol_ref,
+                prev_symbol_ref,
+                config,
+            )?;
+            Some(prev_diff)
+        }
+        None => None,
+    };
+    Ok((left_diff, right_diff, prev_diff))
 }
 
 #[derive(Copy, Clone, Eq, PartialEq)]
@@ -550,17 +1123,26 @@ fn matching_symbols(
     left: Option<&ObjInfo>,
     right: Option<&ObjInfo>,
     prev: Option<&ObjInfo>,
+    mappings: &[(String, String)],
 ) -> Result<Vec<SymbolMatch>> {
     let mut matches = Vec::new();
     let mut right_used = HashSet::new();
+    let mapped = mapped_symbols(left, right, mappings);
+    right_used.extend(mapped.values().copied());
     if let Some(left) = left {
         for (section_idx, section) in left.sections.iter().enumerate() {
             for (symbol_idx, symbol) in section.symbols.iter().enumerate() {
+                let left_ref = SymbolRef { section_idx, symbol_idx };
+                let right = match mapped.get(&left_ref) {
+                    Some(&right_ref) => Some((right_ref, PairConfidence::Mapped)),
+                    None => find_symbol(right, symbol, section, Some(&right_used)),
+                };
                 let symbol_match = SymbolMatch {
-                    left: Some(SymbolRef { section_idx, symbol_idx }),
-                    right: find_symbol(right, symbol, section, Some(&right_used)),
-                    prev: find_symbol(prev, symbol, section, None),
+                    left: Some(left_ref),
+                    right: right.map(|(r, _)| r),
+                    prev: find_symbol(prev, symbol, section, None).map(|(r, _)| r),
                     section_kind: section.kind,
+                    confidence: right.map(|(_, c)| c),
                 };
                 matches.push(symbol_match);
                 if let Some(right) = symbol_match.right {
@@ -569,11 +1151,13 @@ fn matching_symbols(
             }
         }
         for (symbol_idx, symbol) in left.common.iter().enumerate() {
+            let right = find_common_symbol(right, symbol);
             let symbol_match = SymbolMatch {
                 left: Some(SymbolRef { section_idx: left.sections.len(), symbol_idx }),
-                right: find_common_symbol(right, symbol),
+                right,
                 prev: find_common_symbol(prev, symbol),
                 section_kind: ObjSectionKind::Bss,
+                confidence: right.map(|_| PairConfidence::High),
             };
             matches.push(symbol_match);
             if let Some(right) = symbol_match.right {
@@ -591,8 +1175,9 @@ fn matching_symbols(
                 matches.push(SymbolMatch {
                     left: None,
                     right: Some(symbol_ref),
-                    prev: find_symbol(prev, symbol, section, None),
+                    prev: find_symbol(prev, symbol, section, None).map(|(r, _)| r),
                     section_kind: section.kind,
+                    confidence: None,
                 });
             }
         }
@@ -606,12 +1191,49 @@ fn matching_symbols(
                 right: Some(symbol_ref),
                 prev: find_common_symbol(prev, symbol),
                 section_kind: ObjSectionKind::Bss,
+                confidence: None,
             });
         }
     }
     Ok(matches)
 }
 
+/// Resolves symbol mappings to pairs of section symbols of the same kind. Mappings naming a
+/// missing symbol, or one that's already mapped, are ignored.
+fn mapped_symbols(
+    left: Option<&ObjInfo>,
+    right: Option<&ObjInfo>,
+    mappings: &[(String, String)],
+) -> BTreeMap<SymbolRef, SymbolRef> {
+    let mut mapped = BTreeMap::new();
+    let (Some(left), Some(right)) = (left, right) else {
+        return mapped;
+    };
+    let find = |obj: &ObjInfo, name: &str| {
+        let symbol_ref = obj.find_symbol(
+            SymbolIdentityPolicy::ByMangledName,
+            &SymbolIdentity::MangledName(name.to_string()),
+        )?;
+        let (section, _) = obj.section_symbol(symbol_ref);
+        Some((symbol_ref, section?.kind))
+    };
+    let mut right_used = HashSet::new();
+    for (left_name, right_name) in mappings {
+        let (Some((left_ref, left_kind)), Some((right_ref, right_kind))) =
+            (find(left, left_name), find(right, right_name))
+        else {
+            continue;
+        };
+        if left_kind == right_kind
+            && !mapped.contains_key(&left_ref)
+            && right_used.insert(right_ref)
+        {
+            mapped.insert(left_ref, right_ref);
+        }
+    }
+    mapped
+}
+
 fn unmatched_symbols<'section, 'used>(
     section: &'section ObjSection,
     section_idx: usize,
@@ -631,17 +1253,25 @@ fn find_symbol(
     in_symbol: &ObjSymbol,
     in_section: &ObjSection,
     used: Option<&HashSet<SymbolRef>>,
-) -> Option<SymbolRef> {
+) -> Option<(SymbolRef, PairConfidence)> {
     let obj = obj?;
     // Try to find an exact name match
+    let in_name = in_symbol.normalized_name.as_deref().unwrap_or(&in_symbol.name);
     for (section_idx, section) in obj.sections.iter().enumerate() {
         if section.kind != in_section.kind {
             continue;
         }
-        if let Some((symbol_idx, _)) = unmatched_symbols(section, section_idx, used)
-            .find(|(_, symbol)| symbol.name == in_symbol.name)
+        if let Some((symbol_idx, symbol)) =
+            unmatched_symbols(section, section_idx, used).find(|(_, symbol)| {
+                symbol.normalized_name.as_deref().unwrap_or(&symbol.name) == in_name
+            })
         {
-            return Some(SymbolRef { section_idx, symbol_idx });
+            let confidence = if symbol.name == in_symbol.name {
+                PairConfidence::High
+            } else {
+                PairConfidence::Medium
+            };
+            return Some((SymbolRef { section_idx, symbol_idx }, confidence));
         }
     }
     // Match compiler-generated symbols against each other (e.g. @251 -> @60)
@@ -657,7 +1287,7 @@ fn find_symbol(
                     symbol.address == in_symbol.address && symbol.name.starts_with('@')
                 })
             {
-                return Some(SymbolRef { section_idx, symbol_idx });
+                return Some((SymbolRef { section_idx, symbol_idx }, PairConfidence::Low));
             }
         }
     }
@@ -679,7 +1309,7 @@ fn find_symbol(
                     }
                 })
             {
-                return Some(SymbolRef { section_idx, symbol_idx });
+                return Some((SymbolRef { section_idx, symbol_idx }, PairConfidence::Medium));
             }
         }
     }
@@ -734,3 +1364,84 @@ fn find_section(obj: Option<&ObjInfo>, name: &str, section_kind: ObjSectionKind)
     }
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use flagset::FlagSet;
+
+    use super::*;
+    use crate::obj::ObjSymbolFlagSet;
+
+    fn symbol(flags: FlagSet<ObjSymbolFlags>) -> ObjSymbol {
+        ObjSymbol {
+            name: "sym".to_string(),
+            demangled_name: None,
+            has_extab: false,
+            extab_name: None,
+            extabindex_name: None,
+            address: 0,
+            section_address: 0,
+            size: 4,
+            size_known: true,
+            flags: ObjSymbolFlagSet(flags),
+            addend: 0,
+            virtual_address: None,
+            normalized_name: None,
+        }
+    }
+
+    fn section_diff(pairs: &[Option<SymbolRef>]) -> ObjSectionDiff {
+        let symbols = pairs
+            .iter()
+            .map(|&diff_symbol| ObjSymbolDiff { diff_symbol, ..Default::default() })
+            .collect();
+        ObjSectionDiff { symbols, data_diff: vec![], match_percent: None }
+    }
+
+    #[test]
+    fn aligned_rows_place_unpaired_followers() {
+        let pair = |symbol_idx| Some(SymbolRef { section_idx: 1, symbol_idx });
+        // Reference symbols 0 and 2 pair with follower symbols 1 and 3
+        let reference = section_diff(&[pair(1), None, pair(3)]);
+        let follower = section_diff(&[None; 5]);
+        assert_eq!(reference.aligned_rows(&follower, 1), vec![
+            // Unpaired at the start
+            (None, Some(0)),
+            (Some(0), Some(1)),
+            // Unpaired in the middle, after its preceding paired symbol
+            (None, Some(2)),
+            (Some(1), None),
+            (Some(2), Some(3)),
+            // Unpaired at the end
+            (None, Some(4)),
+        ]);
+    }
+
+    #[test]
+    fn aligned_rows_ignore_other_sections() {
+        let reference = section_diff(&[Some(SymbolRef { section_idx: 2, symbol_idx: 0 })]);
+        let follower = section_diff(&[None]);
+        assert_eq!(reference.aligned_rows(&follower, 1), vec![(None, Some(0)), (Some(0), None)]);
+        assert_eq!(section_diff(&[]).aligned_rows(&follower, 1), vec![(None, Some(0))]);
+    }
+
+    #[test]
+    fn visibility_filter_includes() {
+        use SymbolVisibilityFilter::*;
+        // Hidden visibility is recorded alongside the binding, which is what the filter checks
+        let cases = [
+            ("global", ObjSymbolFlags::Global.into(), [true, true, true]),
+            ("local", ObjSymbolFlags::Local.into(), [true, false, false]),
+            ("weak", ObjSymbolFlags::Weak.into(), [true, true, false]),
+            ("hidden", ObjSymbolFlags::Global | ObjSymbolFlags::Hidden, [true, true, true]),
+            ("hidden weak", ObjSymbolFlags::Weak | ObjSymbolFlags::Hidden, [true, true, false]),
+            ("unbound", FlagSet::default(), [true, false, false]),
+        ];
+        for (name, flags, expected) in cases {
+            let symbol = symbol(flags);
+            for (filter, expected) in [All, External, Global].into_iter().zip(expected) {
+                assert_eq!(filter.includes(&symbol), expected, "{name} symbol with {filter:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,290 @@
+use std::{fmt, fmt::Write, ops::Range};
+
+use crate::{
+    diff::{
+        display::{display_diff, DiffText},
+        ObjInsDiff, ObjInsDiffKind, ObjSymbolDiff,
+    },
+    obj::ObjSymbol,
+};
+
+fn truncated(s: &str, max_len: Option<usize>) -> String {
+    match max_len.and_then(|max_len| s.char_indices().nth(max_len)) {
+        Some((end, _)) => format!("{}…", &s[..end]),
+        None => s.to_string(),
+    }
+}
+
+/// Formats a single instruction row as plain text, without line numbers. With `max_arg_len`
+/// set, longer arguments and symbol names are cut short.
+pub fn instruction_text(
+    ins_diff: &ObjInsDiff,
+    base_addr: u64,
+    max_arg_len: Option<usize>,
+) -> String {
+    let mut out = String::new();
+    display_diff(ins_diff, base_addr, |text| {
+        match text {
+            DiffText::Basic(s) | DiffText::BasicColor(s, _) => out.push_str(s),
+            DiffText::Line(_) | DiffText::Eol => {}
+            DiffText::Address(addr) => write!(out, "{:x}:", addr)?,
+            DiffText::Opcode(mnemonic, _) => write!(out, "{} ", mnemonic)?,
+            DiffText::Argument(arg, _) => out.push_str(&truncated(&arg.to_string(), max_arg_len)),
+            DiffText::BranchDest(addr, _) => write!(out, "{:x}", addr)?,
+            DiffText::Symbol(sym, _) => out.push_str(&truncated(
+                sym.demangled_name.as_ref().unwrap_or(&sym.name),
+                max_arg_len,
+            )),
+            DiffText::Spacing(n) => out.extend(std::iter::repeat(' ').take(n)),
+        }
+        Ok::<_, fmt::Error>(())
+    })
+    .unwrap();
+    out.trim_end().to_string()
+}
+
+/// Writes a function diff in unified diff format.
+///
+/// With `context` set, runs of matching instructions further than `context` rows from a
+/// change are collapsed into `@@ -<left> +<right> @@` hunk headers, like `diff -U`. Arguments
+/// are truncated as in [`instruction_text`].
+pub fn write_unified_diff<W: Write>(
+    out: &mut W,
+    left: Option<(&ObjSymbol, &ObjSymbolDiff)>,
+    right: Option<(&ObjSymbol, &ObjSymbolDiff)>,
+    context: Option<usize>,
+    max_arg_len: Option<usize>,
+) -> fmt::Result {
+    let empty: &[ObjInsDiff] = &[];
+    let left_name = left.map(|(s, _)| s.demangled_name.as_ref().unwrap_or(&s.name));
+    let right_name = right.map(|(s, _)| s.demangled_name.as_ref().unwrap_or(&s.name));
+    writeln!(out, "--- {}", left_name.map(String::as_str).unwrap_or("/dev/null"))?;
+    writeln!(out, "+++ {}", right_name.map(String::as_str).unwrap_or("/dev/null"))?;
+
+    let left_rows = left.map(|(_, d)| d.instructions.as_slice()).unwrap_or(empty);
+    let right_rows = right.map(|(_, d)| d.instructions.as_slice()).unwrap_or(empty);
+    let left_base = left.map(|(s, _)| s.address).unwrap_or(0);
+    let right_base = right.map(|(s, _)| s.address).unwrap_or(0);
+    let num_rows = left_rows.len().max(right_rows.len());
+    let changed = |i: usize| {
+        let kind = |rows: &[ObjInsDiff]| rows.get(i).map(|d| d.kind).unwrap_or_default();
+        kind(left_rows) != ObjInsDiffKind::None || kind(right_rows) != ObjInsDiffKind::None
+    };
+
+    for hunk in hunks(num_rows, context, changed) {
+        if context.is_some() {
+            // A side with only blank rows in the hunk starts where its next instruction would
+            let addr = |rows: &[ObjInsDiff], base: u64| {
+                let start = hunk.start.min(rows.len());
+                rows[start..]
+                    .iter()
+                    .find_map(|d| d.ins.as_ref())
+                    .map(|ins| ins.address - base)
+                    .or_else(|| {
+                        rows[..start]
+                            .iter()
+                            .rev()
+                            .find_map(|d| d.ins.as_ref())
+                            .map(|ins| ins.address + ins.size as u64 - base)
+                    })
+                    .unwrap_or(0)
+            };
+            writeln!(
+                out,
+                "@@ -{:x} +{:x} @@",
+                addr(left_rows, left_base),
+                addr(right_rows, right_base)
+            )?;
+        }
+        let mut i = hunk.start;
+        while i < hunk.end {
+            if !changed(i) {
+                let row = left_rows
+                    .get(i)
+                    .map(|d| (d, left_base))
+                    .or_else(|| right_rows.get(i).map(|d| (d, right_base)));
+                if let Some((ins_diff, base_addr)) = row {
+                    writeln!(out, " {}", instruction_text(ins_diff, base_addr, max_arg_len))?;
+                }
+                i += 1;
+                continue;
+            }
+            // Group consecutive changed rows, removals first
+            let start = i;
+            while i < hunk.end && changed(i) {
+                i += 1;
+            }
+            for ins_diff in left_rows[start.min(left_rows.len())..i.min(left_rows.len())].iter() {
+                if ins_diff.ins.is_some() {
+                    writeln!(out, "-{}", instruction_text(ins_diff, left_base, max_arg_len))?;
+                }
+            }
+            for ins_diff in right_rows[start.min(right_rows.len())..i.min(right_rows.len())].iter()
+            {
+                if ins_diff.ins.is_some() {
+                    writeln!(out, "+{}", instruction_text(ins_diff, right_base, max_arg_len))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Computes the row ranges to display, merging changes closer than `2 * context` rows.
+fn hunks(
+    num_rows: usize,
+    context: Option<usize>,
+    changed: impl Fn(usize) -> bool,
+) -> Vec<Range<usize>> {
+    let Some(context) = context else {
+        return (num_rows > 0).then_some(0..num_rows).into_iter().collect();
+    };
+    let mut hunks: Vec<Range<usize>> = vec![];
+    for i in (0..num_rows).filter(|&i| changed(i)) {
+        let start = i.saturating_sub(context);
+        let end = (i + context + 1).min(num_rows);
+        match hunks.last_mut() {
+            Some(last) if start <= last.end => last.end = end,
+            _ => hunks.push(start..end),
+        }
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::obj::{ObjIns, ObjInsArg, ObjInsArgValue, SymbolRef};
+
+    fn symbol(name: &str, address: u64) -> ObjSymbol {
+        ObjSymbol {
+            name: name.to_string(),
+            demangled_name: None,
+            has_extab: false,
+            extab_name: None,
+            extabindex_name: None,
+            address,
+            section_address: address,
+            size: 0,
+            size_known: false,
+            flags: Default::default(),
+            addend: 0,
+            virtual_address: None,
+            normalized_name: None,
+        }
+    }
+
+    /// Builds matching rows from (address, mnemonic, immediate), where `None` is a blank row.
+    fn rows(rows: &[Option<(u64, &str, i64)>]) -> ObjSymbolDiff {
+        let instructions = rows
+            .iter()
+            .map(|row| ObjInsDiff {
+                ins: row.map(|(address, mnemonic, imm)| ObjIns {
+                    address,
+                    size: 4,
+                    op: 0,
+                    mnemonic: mnemonic.to_string(),
+                    args: vec![
+                        ObjInsArg::Arg(ObjInsArgValue::Opaque("r3".into())),
+                        ObjInsArg::PlainText(", ".into()),
+                        ObjInsArg::Arg(ObjInsArgValue::Signed(imm)),
+                    ],
+                    reloc: None,
+                    branch_dest: None,
+                    line: None,
+                    formatted: String::new(),
+                    orig: None,
+                    canonical: None,
+                }),
+                ..Default::default()
+            })
+            .collect();
+        ObjSymbolDiff { symbol_ref: SymbolRef::default(), instructions, ..Default::default() }
+    }
+
+    fn unified(context: Option<usize>) -> String {
+        let (left_sym, right_sym) = (symbol("func", 0x100), symbol("func", 0x200));
+        let mut left = rows(&[
+            Some((0x100, "li", 0)),
+            Some((0x104, "li", 1)),
+            Some((0x108, "li", 2)),
+            Some((0x10c, "li", 3)),
+            Some((0x110, "li", 4)),
+            Some((0x114, "li", 5)),
+        ]);
+        let mut right = rows(&[
+            Some((0x200, "li", 0)),
+            None,
+            Some((0x204, "li", 2)),
+            Some((0x208, "li", 3)),
+            Some((0x20c, "li", 4)),
+            Some((0x210, "li", 6)),
+        ]);
+        left.instructions[1].kind = ObjInsDiffKind::Delete;
+        right.instructions[1].kind = ObjInsDiffKind::Delete;
+        left.instructions[5].kind = ObjInsDiffKind::Replace;
+        right.instructions[5].kind = ObjInsDiffKind::Replace;
+        let mut out = String::new();
+        write_unified_diff(
+            &mut out,
+            Some((&left_sym, &left)),
+            Some((&right_sym, &right)),
+            context,
+            None,
+        )
+        .unwrap();
+        out
+    }
+
+    #[test]
+    fn unified_diff_without_context() {
+        assert_eq!(
+            unified(None),
+            "\
+--- func
++++ func
+ 0:    li  r3, 0x0
+-4:    li  r3, 0x1
+ 8:    li  r3, 0x2
+ c:    li  r3, 0x3
+ 10:    li  r3, 0x4
+-14:    li  r3, 0x5
++10:    li  r3, 0x6
+"
+        );
+    }
+
+    #[test]
+    fn unified_diff_collapses_unchanged_rows() {
+        assert_eq!(
+            unified(Some(1)),
+            "\
+--- func
++++ func
+@@ -0 +0 @@
+ 0:    li  r3, 0x0
+-4:    li  r3, 0x1
+ 8:    li  r3, 0x2
+@@ -10 +c @@
+ 10:    li  r3, 0x4
+-14:    li  r3, 0x5
++10:    li  r3, 0x6
+"
+        );
+        // With no context, the deletion's hunk starts at the right side's next instruction
+        assert_eq!(
+            unified(Some(0)),
+            "\
+--- func
++++ func
+@@ -4 +4 @@
+-4:    li  r3, 0x1
+@@ -14 +10 @@
+-14:    li  r3, 0x5
++10:    li  r3, 0x6
+"
+        );
+        // Context wide enough to merge both changes into one hunk
+        assert_eq!(unified(Some(2)).matches("@@ -").count(), 1);
+    }
+}
@@ -1,4 +1,6 @@
 pub mod arch;
+#[cfg(feature = "build")]
+pub mod build;
 #[cfg(feature = "config")]
 pub mod config;
 pub mod diff;
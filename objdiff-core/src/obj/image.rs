@@ -0,0 +1,362 @@
+//! Linked GameCube/Wii images: DOL executables and REL modules.
+//!
+//! Neither format carries symbols, so symbols are synthesized: one at the start of each
+//! section, at the module's prolog/epilog/unresolved functions, and at every branch target
+//! within a REL module. Targets are named `lbl_{module}_{section}_{offset:X}` (or
+//! `lbl_{address:08X}` in the DOL), so identical layouts pair up when diffed.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{Cursor, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use anyhow::{bail, ensure, Context, Result};
+use byteorder::{BigEndian, ReadBytesExt};
+use filetime::FileTime;
+use object::{elf, Architecture, RelocationFlags};
+
+use crate::{
+    arch::{new_ppc_arch, InsNormalizer},
+    diff::{apply_symbol_name_rules, DiffObjConfig},
+    obj::{
+        ObjInfo, ObjReloc, ObjSection, ObjSectionKind, ObjSymbol, ObjSymbolFlagSet, ObjSymbolFlags,
+    },
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ImageKind {
+    Dol,
+    Rel,
+}
+
+impl ImageKind {
+    /// Recognizes images by their file extension, as DOLs have no magic number.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?;
+        if ext.eq_ignore_ascii_case("dol") {
+            Some(Self::Dol)
+        } else if ext.eq_ignore_ascii_case("rel") {
+            Some(Self::Rel)
+        } else {
+            None
+        }
+    }
+}
+
+const DOL_TEXT_SECTIONS: usize = 7;
+const DOL_DATA_SECTIONS: usize = 11;
+
+const R_DOLPHIN_NOP: u8 = 201;
+const R_DOLPHIN_SECTION: u8 = 202;
+const R_DOLPHIN_END: u8 = 203;
+const R_DOLPHIN_MRKREF: u8 = 204;
+
+/// Conventional names of REL sections by index.
+const REL_SECTION_NAMES: [&str; 7] = ["", ".text", ".ctors", ".dtors", ".rodata", ".data", ".bss"];
+
+pub fn read(path: &Path, kind: ImageKind, config: &DiffObjConfig) -> Result<ObjInfo> {
+    let data = fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let timestamp = FileTime::from_last_modification_time(&fs::metadata(path)?);
+    let mut sections = match kind {
+        ImageKind::Dol => read_dol(&data),
+        ImageKind::Rel => read_rel(&data),
+    }
+    .with_context(|| format!("Failed to parse {}", path.display()))?;
+    let arch = new_ppc_arch(config);
+    let ins_normalizer = InsNormalizer::new(Architecture::PowerPc, arch.as_ref(), config)?;
+    apply_symbol_name_rules(&mut sections, &mut [], &config.symbol_name_rules)?;
+    Ok(ObjInfo {
+        arch,
+        ins_normalizer,
+        path: path.to_owned(),
+        timestamp,
+        sections,
+        common: vec![],
+        extab: None,
+        split_meta: None,
+        source_file: None,
+    })
+}
+
+fn read_u32s(reader: &mut Cursor<&[u8]>, count: usize) -> Result<Vec<u32>> {
+    (0..count).map(|_| Ok(reader.read_u32::<BigEndian>()?)).collect()
+}
+
+fn new_section(
+    name: String,
+    kind: ObjSectionKind,
+    address: u64,
+    data: Vec<u8>,
+    size: u64,
+    orig_index: usize,
+) -> ObjSection {
+    ObjSection {
+        name,
+        kind,
+        address,
+        size,
+        data,
+        orig_index,
+        symbols: vec![],
+        relocations: vec![],
+        virtual_address: None,
+        line_info: BTreeMap::new(),
+        inline_ranges: vec![],
+    }
+}
+
+fn new_symbol(name: String, address: u64, section_address: u64, size: u64) -> ObjSymbol {
+    ObjSymbol {
+        name,
+        demangled_name: None,
+        has_extab: false,
+        extab_name: None,
+        extabindex_name: None,
+        address,
+        section_address,
+        size,
+        size_known: size != 0,
+        flags: ObjSymbolFlagSet(ObjSymbolFlags::Global.into()),
+        addend: 0,
+        virtual_address: None,
+        normalized_name: None,
+    }
+}
+
+/// Creates a symbol at each section offset in `names`, spanning to the next one.
+fn section_symbols(section: &ObjSection, names: &BTreeMap<u64, String>) -> Vec<ObjSymbol> {
+    let offsets: Vec<u64> = names.keys().copied().filter(|&o| o < section.size).collect();
+    offsets
+        .iter()
+        .enumerate()
+        .map(|(i, &offset)| {
+            let end = offsets.get(i + 1).copied().unwrap_or(section.size);
+            new_symbol(names[&offset].clone(), section.address + offset, offset, end - offset)
+        })
+        .collect()
+}
+
+fn slice(data: &[u8], offset: u32, size: u32) -> Result<&[u8]> {
+    let (start, end) = (offset as usize, offset as usize + size as usize);
+    ensure!(end <= data.len(), "Section at {offset:#x} ({size:#x} bytes) is out of bounds");
+    Ok(&data[start..end])
+}
+
+fn read_dol(data: &[u8]) -> Result<Vec<ObjSection>> {
+    let mut reader = Cursor::new(data);
+    let count = DOL_TEXT_SECTIONS + DOL_DATA_SECTIONS;
+    let offsets = read_u32s(&mut reader, count)?;
+    let addresses = read_u32s(&mut reader, count)?;
+    let sizes = read_u32s(&mut reader, count)?;
+    let bss_address = reader.read_u32::<BigEndian>()?;
+    let bss_size = reader.read_u32::<BigEndian>()?;
+    let entry = reader.read_u32::<BigEndian>()? as u64;
+
+    let mut sections = vec![];
+    for i in 0..count {
+        if sizes[i] == 0 {
+            continue;
+        }
+        let (name, kind) = if i < DOL_TEXT_SECTIONS {
+            (format!(".text{i}"), ObjSectionKind::Code)
+        } else {
+            (format!(".data{}", i - DOL_TEXT_SECTIONS), ObjSectionKind::Data)
+        };
+        let section_data = slice(data, offsets[i], sizes[i])?.to_vec();
+        sections.push(new_section(
+            name,
+            kind,
+            addresses[i] as u64,
+            section_data,
+            sizes[i] as u64,
+            i,
+        ));
+    }
+    if bss_size != 0 {
+        sections.push(new_section(
+            ".bss".to_string(),
+            ObjSectionKind::Bss,
+            bss_address as u64,
+            vec![],
+            bss_size as u64,
+            count,
+        ));
+    }
+    for section in &mut sections {
+        let mut names = BTreeMap::new();
+        names.insert(0, format!("lbl_{:08X}", section.address));
+        if (section.address..section.address + section.size).contains(&entry) {
+            names.insert(entry - section.address, "__start".to_string());
+        }
+        section.symbols = section_symbols(section, &names);
+    }
+    Ok(sections)
+}
+
+struct RelReloc {
+    section: u8,
+    offset: u32,
+    r_type: u8,
+    target_module: u32,
+    target_section: u8,
+    addend: u32,
+}
+
+fn rel_section_name(index: usize) -> String {
+    match REL_SECTION_NAMES.get(index) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => format!(".section{index}"),
+    }
+}
+
+fn rel_label(module: u32, section: u8, offset: u32) -> String {
+    let section = rel_section_name(section as usize);
+    format!("lbl_{module}_{}_{offset:X}", section.trim_start_matches('.'))
+}
+
+fn read_rel_relocations(
+    reader: &mut Cursor<&[u8]>,
+    imp_offset: u32,
+    imp_size: u32,
+) -> Result<Vec<RelReloc>> {
+    reader.seek(SeekFrom::Start(imp_offset as u64))?;
+    let imps = read_u32s(reader, imp_size as usize / 4)?;
+    let mut relocations = vec![];
+    for imp in imps.chunks_exact(2) {
+        let (target_module, offset) = (imp[0], imp[1]);
+        reader.seek(SeekFrom::Start(offset as u64))?;
+        let (mut section, mut address) = (0u8, 0u32);
+        loop {
+            let delta = reader.read_u16::<BigEndian>()?;
+            let r_type = reader.read_u8()?;
+            let target_section = reader.read_u8()?;
+            let addend = reader.read_u32::<BigEndian>()?;
+            address = address.wrapping_add(delta as u32);
+            match r_type {
+                R_DOLPHIN_NOP | R_DOLPHIN_MRKREF => {}
+                R_DOLPHIN_SECTION => {
+                    section = target_section;
+                    address = 0;
+                }
+                R_DOLPHIN_END => break,
+                _ => relocations.push(RelReloc {
+                    section,
+                    offset: address,
+                    r_type,
+                    target_module,
+                    target_section,
+                    addend,
+                }),
+            }
+        }
+    }
+    Ok(relocations)
+}
+
+fn read_rel(data: &[u8]) -> Result<Vec<ObjSection>> {
+    let mut reader = Cursor::new(data);
+    let module_id = reader.read_u32::<BigEndian>()?;
+    reader.seek(SeekFrom::Current(8))?; // Module list links
+    let num_sections = reader.read_u32::<BigEndian>()?;
+    let section_info_offset = reader.read_u32::<BigEndian>()?;
+    reader.seek(SeekFrom::Current(8))?; // Name offset and size
+    let version = reader.read_u32::<BigEndian>()?;
+    ensure!((1..=3).contains(&version), "Unsupported REL version {version}");
+    let bss_size = reader.read_u32::<BigEndian>()?;
+    reader.seek(SeekFrom::Current(4))?; // Relocation offset
+    let imp_offset = reader.read_u32::<BigEndian>()?;
+    let imp_size = reader.read_u32::<BigEndian>()?;
+    let mut special_sections = [0u8; 4];
+    reader.read_exact(&mut special_sections)?;
+    let special_offsets = read_u32s(&mut reader, 3)?;
+
+    reader.seek(SeekFrom::Start(section_info_offset as u64))?;
+    let section_info = read_u32s(&mut reader, num_sections as usize * 2)?;
+    let mut sections = vec![];
+    // Offsets of the synthesized symbols, by REL section index
+    let mut names: BTreeMap<u8, BTreeMap<u64, String>> = BTreeMap::new();
+    for (index, info) in section_info.chunks_exact(2).enumerate() {
+        let offset = info[0] & !1;
+        // The BSS section's size may only be in the header
+        let size = if offset == 0 && info[1] == 0 && index == special_sections[3] as usize {
+            bss_size
+        } else {
+            info[1]
+        };
+        if size == 0 {
+            continue;
+        }
+        let (kind, section_data) = if offset == 0 {
+            (ObjSectionKind::Bss, vec![])
+        } else if info[0] & 1 != 0 {
+            (ObjSectionKind::Code, slice(data, offset, size)?.to_vec())
+        } else {
+            (ObjSectionKind::Data, slice(data, offset, size)?.to_vec())
+        };
+        sections.push(new_section(
+            rel_section_name(index),
+            kind,
+            0,
+            section_data,
+            size as u64,
+            index,
+        ));
+        names.entry(index as u8).or_default().insert(0, rel_label(module_id, index as u8, 0));
+    }
+    for (&section, (&offset, name)) in special_sections[..3]
+        .iter()
+        .zip(special_offsets.iter().zip(["_prolog", "_epilog", "_unresolved"]))
+    {
+        if section != 0 {
+            names.entry(section).or_default().insert(offset as u64, name.to_string());
+        }
+    }
+
+    let relocations = read_rel_relocations(&mut reader, imp_offset, imp_size)?;
+    // Branch targets within the module start functions
+    for reloc in &relocations {
+        if reloc.target_module == module_id && reloc.r_type as u32 == elf::R_PPC_REL24 {
+            names
+                .entry(reloc.target_section)
+                .or_default()
+                .entry(reloc.addend as u64)
+                .or_insert_with(|| rel_label(module_id, reloc.target_section, reloc.addend));
+        }
+    }
+    for section in &mut sections {
+        if let Some(names) = names.get(&(section.orig_index as u8)) {
+            section.symbols = section_symbols(section, names);
+        }
+    }
+    for reloc in relocations {
+        let Some(section) = sections.iter_mut().find(|s| s.orig_index == reloc.section as usize)
+        else {
+            bail!("Relocation in unknown section {}", reloc.section);
+        };
+        let (name, target_section) = if reloc.target_module == 0 {
+            (format!("lbl_{:08X}", reloc.addend), None)
+        } else if reloc.target_module == module_id {
+            let name = names
+                .get(&reloc.target_section)
+                .and_then(|n| n.get(&(reloc.addend as u64)))
+                .cloned()
+                .unwrap_or_else(|| rel_label(module_id, reloc.target_section, reloc.addend));
+            (name, Some(rel_section_name(reloc.target_section as usize)))
+        } else {
+            (rel_label(reloc.target_module, reloc.target_section, reloc.addend), None)
+        };
+        let address = reloc.addend as u64;
+        section.relocations.push(ObjReloc {
+            flags: RelocationFlags::Elf { r_type: reloc.r_type as u32 },
+            address: reloc.offset as u64,
+            target: new_symbol(name, address, address, 0),
+            target_section,
+        });
+    }
+    for section in &mut sections {
+        section.relocations.sort_by_key(|r| r.address);
+    }
+    Ok(sections)
+}
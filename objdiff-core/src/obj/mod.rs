@@ -1,3 +1,5 @@
+#[cfg(feature = "ppc")]
+pub mod image;
 pub mod read;
 pub mod split_meta;
 
@@ -9,7 +11,10 @@ use flagset::{flags, FlagSet};
 use object::RelocationFlags;
 use split_meta::SplitMeta;
 
-use crate::{arch::ObjArch, util::ReallySigned};
+use crate::{
+    arch::{InsNormalizer, ObjArch},
+    util::ReallySigned,
+};
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum ObjSectionKind {
@@ -24,6 +29,7 @@ flags! {
         Weak,
         Common,
         Hidden,
+        Protected,
     }
 }
 #[derive(Debug, Copy, Clone, Default)]
@@ -42,6 +48,18 @@ pub struct ObjSection {
     pub virtual_address: Option<u64>,
     /// Line number info (.line or .debug_line section)
     pub line_info: BTreeMap<u64, u64>,
+    /// Inlined function scopes (DWARF 2+)
+    pub inline_ranges: Vec<ObjInlineRange>,
+}
+
+/// Address range of a function inlined into the section's code.
+#[derive(Debug, Clone)]
+pub struct ObjInlineRange {
+    pub name: String,
+    pub start: u64,
+    pub end: u64,
+    /// Nesting level, starting at 0 for functions inlined directly into a symbol
+    pub depth: u32,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -108,6 +126,8 @@ pub struct ObjIns {
     pub formatted: String,
     /// Original (unsimplified) instruction
     pub orig: Option<String>,
+    /// Equivalent form compared in place of the instruction, from an instruction rule
+    pub canonical: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -125,6 +145,8 @@ pub struct ObjSymbol {
     pub addend: i64,
     /// Original virtual address (from .note.split section)
     pub virtual_address: Option<u64>,
+    /// Name used for pairing, if symbol name rules changed it
+    pub normalized_name: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -136,6 +158,8 @@ pub struct ObjExtab {
 
 pub struct ObjInfo {
     pub arch: Box<dyn ObjArch>,
+    /// Instruction rules for the object's architecture
+    pub ins_normalizer: InsNormalizer,
     pub path: PathBuf,
     pub timestamp: FileTime,
     pub sections: Vec<ObjSection>,
@@ -145,6 +169,8 @@ pub struct ObjInfo {
     pub extab: Option<Vec<ObjExtab>>,
     /// Split object metadata (.note.split section)
     pub split_meta: Option<SplitMeta>,
+    /// Source file name from debug info
+    pub source_file: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -161,6 +187,60 @@ pub struct SymbolRef {
     pub symbol_idx: usize,
 }
 
+/// How a symbol is identified across rebuilds, e.g. to keep it selected.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Default,
+    Eq,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+)]
+pub enum SymbolIdentityPolicy {
+    #[default]
+    #[strum(
+        message = "Name (default)",
+        detailed_message = "Demangled name, falling back to the symbol name."
+    )]
+    ByName,
+    #[strum(
+        message = "Mangled name",
+        detailed_message = "Symbol name as it appears in the object file."
+    )]
+    ByMangledName,
+    #[strum(
+        message = "Section offset",
+        detailed_message = "Section name and offset within the section. \
+        Distinguishes local symbols sharing a name."
+    )]
+    BySectionOffset,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum SymbolIdentity {
+    Name(String),
+    MangledName(String),
+    SectionOffset(String, u64),
+}
+
+impl SymbolIdentityPolicy {
+    pub fn identity(self, section_name: &str, symbol: &ObjSymbol) -> SymbolIdentity {
+        match self {
+            SymbolIdentityPolicy::ByName => {
+                SymbolIdentity::Name(symbol.demangled_name.as_ref().unwrap_or(&symbol.name).clone())
+            }
+            SymbolIdentityPolicy::ByMangledName => SymbolIdentity::MangledName(symbol.name.clone()),
+            SymbolIdentityPolicy::BySectionOffset => {
+                SymbolIdentity::SectionOffset(section_name.to_string(), symbol.section_address)
+            }
+        }
+    }
+}
+
 impl ObjInfo {
     pub fn section_symbol(&self, symbol_ref: SymbolRef) -> (Option<&ObjSection>, &ObjSymbol) {
         if symbol_ref.section_idx == self.sections.len() {
@@ -171,4 +251,28 @@ impl ObjInfo {
         let symbol = &section.symbols[symbol_ref.symbol_idx];
         (Some(section), symbol)
     }
+
+    /// Returns the source file and first line number of a symbol, if debug info is available.
+    pub fn symbol_source(&self, section: &ObjSection, symbol: &ObjSymbol) -> Option<(&str, u64)> {
+        let file = self.source_file.as_deref()?;
+        let end = symbol.address + symbol.size.max(1);
+        let (_, &line) = section.line_info.range(symbol.address..end).next()?;
+        Some((file, line))
+    }
+
+    /// Finds the first symbol with the given identity under `policy`.
+    pub fn find_symbol(
+        &self,
+        policy: SymbolIdentityPolicy,
+        identity: &SymbolIdentity,
+    ) -> Option<SymbolRef> {
+        for (section_idx, section) in self.sections.iter().enumerate() {
+            for (symbol_idx, symbol) in section.symbols.iter().enumerate() {
+                if &policy.identity(&section.name, symbol) == identity {
+                    return Some(SymbolRef { section_idx, symbol_idx });
+                }
+            }
+        }
+        None
+    }
 }
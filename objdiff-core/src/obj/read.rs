@@ -1,22 +1,30 @@
-use std::{collections::HashSet, fs, io::Cursor, path::Path};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{BufRead, Cursor},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock},
+};
 
 use anyhow::{anyhow, bail, ensure, Context, Result};
-use byteorder::{BigEndian, ReadBytesExt};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
 use cwextab::decode_extab;
 use filetime::FileTime;
 use flagset::Flags;
 use object::{
-    Architecture, BinaryFormat, File, Object, ObjectSection, ObjectSymbol, RelocationTarget,
-    SectionIndex, SectionKind, Symbol, SymbolKind, SymbolScope, SymbolSection,
+    elf, Architecture, BinaryFormat, File, Object, ObjectSection, ObjectSymbol, RelocationTarget,
+    SectionIndex, SectionKind, Symbol, SymbolFlags, SymbolKind, SymbolScope, SymbolSection,
 };
 
+#[cfg(feature = "ppc")]
+use crate::obj::image;
 use crate::{
-    arch::{new_arch, ObjArch},
-    diff::DiffObjConfig,
+    arch::{new_arch, InsNormalizer, ObjArch},
+    diff::{apply_symbol_name_rules, Demangler, DiffObjConfig},
     obj::{
         split_meta::{SplitMeta, SPLITMETA_SECTION},
-        ObjExtab, ObjInfo, ObjReloc, ObjSection, ObjSectionKind, ObjSymbol, ObjSymbolFlagSet,
-        ObjSymbolFlags,
+        ObjExtab, ObjInfo, ObjInlineRange, ObjReloc, ObjSection, ObjSectionKind, ObjSymbol,
+        ObjSymbolFlagSet, ObjSymbolFlags,
     },
 };
 
@@ -29,8 +37,60 @@ fn to_obj_section_kind(kind: SectionKind) -> Option<ObjSectionKind> {
     }
 }
 
+type DemangleCache = HashMap<(Architecture, Demangler), HashMap<String, Option<String>>>;
+
+/// Demangled names shared across every object read in this process, since the same
+/// (often long, templated) names appear in both objects and in many relocations.
+static DEMANGLE_CACHE: OnceLock<Mutex<DemangleCache>> = OnceLock::new();
+
+/// Demangles a name, at most once per architecture, demangler and name.
+fn demangle_cached(
+    arch: &dyn ObjArch,
+    architecture: Architecture,
+    demangler: Demangler,
+    name: &str,
+) -> Option<String> {
+    let cache = DEMANGLE_CACHE.get_or_init(Default::default);
+    if let Some(names) = cache.lock().unwrap().get(&(architecture, demangler)) {
+        if let Some(demangled) = names.get(name) {
+            return demangled.clone();
+        }
+    }
+    // Demangle without holding the lock, as objects may be read in parallel
+    let demangled = demangle(arch, demangler, name)
+        // Keep the raw name rather than an empty or unchanged result
+        .filter(|demangled| !demangled.is_empty() && demangled != name);
+    cache
+        .lock()
+        .unwrap()
+        .entry((architecture, demangler))
+        .or_default()
+        .insert(name.to_string(), demangled.clone());
+    demangled
+}
+
+fn demangle(arch: &dyn ObjArch, demangler: Demangler, name: &str) -> Option<String> {
+    match demangler {
+        Demangler::Auto => arch.demangle(name),
+        Demangler::None => None,
+        #[cfg(feature = "cpp_demangle")]
+        Demangler::Itanium => cpp_demangle::Symbol::new(name)
+            .ok()
+            .and_then(|s| s.demangle(&cpp_demangle::DemangleOptions::default()).ok()),
+        #[cfg(feature = "msvc-demangler")]
+        Demangler::Msvc => {
+            msvc_demangler::demangle(name, msvc_demangler::DemangleFlags::llvm()).ok()
+        }
+        #[cfg(feature = "cwdemangle")]
+        Demangler::Mwcc => cwdemangle::demangle(name, &cwdemangle::DemangleOptions::default()),
+        #[allow(unreachable_patterns)]
+        _ => arch.demangle(name),
+    }
+}
+
 fn to_obj_symbol(
     arch: &dyn ObjArch,
+    demangler: Demangler,
     obj_file: &File<'_>,
     symbol: &Symbol<'_, '_>,
     addend: i64,
@@ -56,6 +116,10 @@ fn to_obj_symbol(
     }
     if obj_file.format() == BinaryFormat::Elf && symbol.scope() == SymbolScope::Linkage {
         flags = ObjSymbolFlagSet(flags.0 | ObjSymbolFlags::Hidden);
+    } else if let SymbolFlags::Elf { st_other, .. } = symbol.flags() {
+        if st_other & 0x3 == elf::STV_PROTECTED {
+            flags = ObjSymbolFlagSet(flags.0 | ObjSymbolFlags::Protected);
+        }
     }
     let address = arch.symbol_address(symbol);
     let section_address = if let Some(section) =
@@ -65,7 +129,7 @@ fn to_obj_symbol(
     } else {
         address
     };
-    let demangled_name = arch.demangle(name);
+    let demangled_name = demangle_cached(arch, obj_file.architecture(), demangler, name);
     // Find the virtual address for the symbol if available
     let virtual_address = split_meta
         .and_then(|m| m.virtual_addresses.as_ref())
@@ -83,6 +147,7 @@ fn to_obj_symbol(
         flags,
         addend,
         virtual_address,
+        normalized_name: None,
     })
 }
 
@@ -119,6 +184,7 @@ fn filter_sections(obj_file: &File<'_>, split_meta: Option<&SplitMeta>) -> Resul
             relocations: Vec::new(),
             virtual_address,
             line_info: Default::default(),
+            inline_ranges: Vec::new(),
         });
     }
     result.sort_by(|a, b| a.name.cmp(&b.name));
@@ -127,6 +193,7 @@ fn filter_sections(obj_file: &File<'_>, split_meta: Option<&SplitMeta>) -> Resul
 
 fn symbols_by_section(
     arch: &dyn ObjArch,
+    demangler: Demangler,
     obj_file: &File<'_>,
     section: &ObjSection,
     split_meta: Option<&SplitMeta>,
@@ -145,7 +212,7 @@ fn symbols_by_section(
                         continue;
                     }
                 }
-                result.push(to_obj_symbol(arch, obj_file, &symbol, 0, split_meta)?);
+                result.push(to_obj_symbol(arch, demangler, obj_file, &symbol, 0, split_meta)?);
             }
         }
     }
@@ -175,6 +242,7 @@ fn symbols_by_section(
             flags: Default::default(),
             addend: 0,
             virtual_address: None,
+            normalized_name: None,
         });
     }
     Ok(result)
@@ -182,13 +250,14 @@ fn symbols_by_section(
 
 fn common_symbols(
     arch: &dyn ObjArch,
+    demangler: Demangler,
     obj_file: &File<'_>,
     split_meta: Option<&SplitMeta>,
 ) -> Result<Vec<ObjSymbol>> {
     obj_file
         .symbols()
         .filter(Symbol::is_common)
-        .map(|symbol| to_obj_symbol(arch, obj_file, &symbol, 0, split_meta))
+        .map(|symbol| to_obj_symbol(arch, demangler, obj_file, &symbol, 0, split_meta))
         .collect::<Result<Vec<ObjSymbol>>>()
 }
 
@@ -299,6 +368,7 @@ fn exception_tables(
 
 fn find_section_symbol(
     arch: &dyn ObjArch,
+    demangler: Demangler,
     obj_file: &File<'_>,
     target: &Symbol<'_, '_>,
     address: u64,
@@ -322,7 +392,7 @@ fn find_section_symbol(
             }
             continue;
         }
-        return to_obj_symbol(arch, obj_file, &symbol, 0, split_meta);
+        return to_obj_symbol(arch, demangler, obj_file, &symbol, 0, split_meta);
     }
     let (name, offset) = closest_symbol
         .and_then(|s| s.name().map(|n| (n, s.address())).ok())
@@ -342,11 +412,13 @@ fn find_section_symbol(
         flags: Default::default(),
         addend: offset_addr as i64,
         virtual_address: None,
+        normalized_name: None,
     })
 }
 
 fn relocations_by_section(
     arch: &dyn ObjArch,
+    demangler: Demangler,
     obj_file: &File<'_>,
     section: &ObjSection,
     split_meta: Option<&SplitMeta>,
@@ -388,11 +460,11 @@ fn relocations_by_section(
         // println!("Reloc: {reloc:?}, symbol: {symbol:?}, addend: {addend:#x}");
         let target = match symbol.kind() {
             SymbolKind::Text | SymbolKind::Data | SymbolKind::Label | SymbolKind::Unknown => {
-                to_obj_symbol(arch, obj_file, &symbol, addend, split_meta)
+                to_obj_symbol(arch, demangler, obj_file, &symbol, addend, split_meta)
             }
             SymbolKind::Section => {
                 ensure!(addend >= 0, "Negative addend in reloc: {addend}");
-                find_section_symbol(arch, obj_file, &symbol, addend as u64, split_meta)
+                find_section_symbol(arch, demangler, obj_file, &symbol, addend as u64, split_meta)
             }
             kind => Err(anyhow!("Unhandled relocation symbol type {kind:?}")),
         }?;
@@ -401,7 +473,140 @@ fn relocations_by_section(
     Ok(relocations)
 }
 
-fn line_info(obj_file: &File<'_>, sections: &mut [ObjSection]) -> Result<()> {
+/// Finds a companion debug file next to the object, in its `.debug` directory or in one of
+/// the search paths.
+#[cfg(feature = "dwarf")]
+fn find_companion(obj_path: &Path, name: &Path, search_paths: &[PathBuf]) -> Option<PathBuf> {
+    companion_paths(obj_path, name, search_paths).next()
+}
+
+#[cfg(feature = "dwarf")]
+fn companion_paths<'a>(
+    obj_path: &Path,
+    name: &'a Path,
+    search_paths: &'a [PathBuf],
+) -> impl Iterator<Item = PathBuf> + 'a {
+    let dir = obj_path.parent().unwrap_or(Path::new(""));
+    [dir.to_path_buf(), dir.join(".debug")]
+        .into_iter()
+        .chain(search_paths.iter().cloned())
+        .map(move |dir| dir.join(name))
+        .filter(|path| path.is_file())
+}
+
+#[cfg(feature = "dwarf")]
+fn map_companion(path: &Path) -> Option<memmap2::Mmap> {
+    let file = fs::File::open(path).ok()?;
+    match unsafe { memmap2::Mmap::map(&file) } {
+        Ok(data) => Some(data),
+        Err(e) => {
+            log::warn!("Failed to map debug file {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Maps the separate debug file named by `.gnu_debuglink`, if any. Candidates whose CRC doesn't
+/// match the link are skipped.
+#[cfg(feature = "dwarf")]
+fn debuglink_file(
+    obj_file: &File<'_>,
+    obj_path: &Path,
+    search_paths: &[PathBuf],
+) -> Option<memmap2::Mmap> {
+    let (name, crc) = obj_file.gnu_debuglink().ok()??;
+    let name = Path::new(std::str::from_utf8(name).ok()?);
+    let mut found = false;
+    for path in companion_paths(obj_path, name, search_paths) {
+        found = true;
+        let Some(data) = map_companion(&path) else { continue };
+        if crc32fast::hash(&data) != crc {
+            log::warn!("Ignoring debug file {}: CRC does not match", path.display());
+            continue;
+        }
+        log::debug!("Loading debug info from {}", path.display());
+        return Some(data);
+    }
+    if !found {
+        log::warn!("Debug file {} not found for {}", name.display(), obj_path.display());
+    }
+    None
+}
+
+/// A compile unit's source file, relative to its compilation directory unless it's absolute.
+fn source_path(name: &str, comp_dir: Option<&str>) -> String {
+    match comp_dir {
+        Some(comp_dir) if !Path::new(name).is_absolute() => {
+            Path::new(comp_dir).join(name).to_string_lossy().into_owned()
+        }
+        _ => name.to_string(),
+    }
+}
+
+/// Reads the source file of the compile unit entry in DWARF 1.1 `.debug` data.
+fn dwarf1_source_file<E: ByteOrder>(data: &[u8]) -> Option<String> {
+    const TAG_COMPILE_UNIT: u16 = 0x0011;
+    const AT_NAME: u16 = 0x0038;
+    const AT_COMP_DIR: u16 = 0x01b8;
+
+    let mut offset = 0;
+    while offset + 4 <= data.len() {
+        let length = E::read_u32(&data[offset..]) as usize;
+        // Entries shorter than a tag are padding
+        if length < 6 {
+            offset += length.max(4);
+            continue;
+        }
+        let entry = data.get(offset + 4..offset + length)?;
+        offset += length;
+        if E::read_u16(entry) != TAG_COMPILE_UNIT {
+            continue;
+        }
+        let mut reader = Cursor::new(&entry[2..]);
+        let (mut name, mut comp_dir) = (None, None);
+        while (reader.position() as usize) < entry.len() - 2 {
+            let attr = reader.read_u16::<E>().ok()?;
+            // The low 4 bits are the form
+            let size = match attr & 0xf {
+                0x1 | 0x2 | 0x6 => 4,
+                0x3 => reader.read_u16::<E>().ok()? as u64,
+                0x4 => reader.read_u32::<E>().ok()? as u64,
+                0x5 => 2,
+                0x7 => 8,
+                0x8 => {
+                    let mut value = vec![];
+                    reader.read_until(0, &mut value).ok()?;
+                    value.pop();
+                    let value = String::from_utf8_lossy(&value).into_owned();
+                    match attr {
+                        AT_NAME => name = Some(value),
+                        AT_COMP_DIR => comp_dir = Some(value),
+                        _ => {}
+                    }
+                    0
+                }
+                _ => break,
+            };
+            reader.set_position(reader.position() + size);
+        }
+        return name.map(|name| source_path(&name, comp_dir.as_deref()));
+    }
+    None
+}
+
+/// Reads line info into each code section, returning the source file name when available.
+///
+/// DWARF may come from a separate file linked with `.gnu_debuglink`, and inline info from a
+/// split `.dwo` file. Missing companion files leave the object without that debug info.
+#[cfg_attr(not(feature = "dwarf"), allow(unused_mut, unused_variables))]
+fn line_info(
+    obj_file: &File<'_>,
+    obj_path: &Path,
+    sections: &mut [ObjSection],
+    search_paths: &[PathBuf],
+) -> Result<Option<String>> {
+    let mut source_file = None;
+
     // DWARF 1.1
     if let Some(section) = obj_file.section_by_name(".line") {
         let data = section.uncompressed_data()?;
@@ -437,26 +642,51 @@ fn line_info(obj_file: &File<'_>, sections: &mut [ObjSection]) -> Result<()> {
             }
         }
     }
+    if let Some(section) = obj_file.section_by_name(".debug") {
+        let data = section.uncompressed_data()?;
+        source_file = match obj_file.endianness() {
+            object::Endianness::Little => dwarf1_source_file::<LittleEndian>(&data),
+            object::Endianness::Big => dwarf1_source_file::<BigEndian>(&data),
+        };
+    }
 
     // DWARF 2+
     #[cfg(feature = "dwarf")]
     {
+        let debug_data = obj_file
+            .section_by_name(".debug_info")
+            .is_none()
+            .then(|| debuglink_file(obj_file, obj_path, search_paths))
+            .flatten();
+        let debug_file = debug_data.as_ref().and_then(|data| File::parse(&**data).ok());
+        let dwarf_file = debug_file.as_ref().unwrap_or(obj_file);
+        let layout = CodeLayout::new(obj_file, sections);
         let dwarf_cow = gimli::DwarfSections::load(|id| {
-            Ok::<_, gimli::Error>(
-                obj_file
-                    .section_by_name(id.name())
-                    .and_then(|section| section.uncompressed_data().ok())
-                    .unwrap_or(std::borrow::Cow::Borrowed(&[][..])),
-            )
+            let section = dwarf_file.section_by_name(id.name());
+            let data = section
+                .as_ref()
+                .and_then(|section| section.uncompressed_data().ok())
+                .unwrap_or(std::borrow::Cow::Borrowed(&[][..]));
+            let relocations = match &section {
+                Some(section) if dwarf_file.kind() == object::ObjectKind::Relocatable => {
+                    DwarfRelocations::new(dwarf_file, section, &layout)
+                }
+                _ => DwarfRelocations::default(),
+            };
+            Ok::<_, gimli::Error>((data, relocations))
         })?;
         let endian = match obj_file.endianness() {
             object::Endianness::Little => gimli::RunTimeEndian::Little,
             object::Endianness::Big => gimli::RunTimeEndian::Big,
         };
-        let dwarf = dwarf_cow.borrow(|section| gimli::EndianSlice::new(section, endian));
+        let dwarf = dwarf_cow.borrow(|(section, _)| gimli::EndianSlice::new(section, endian));
         let mut iter = dwarf.units();
         if let Some(header) = iter.next()? {
             let unit = dwarf.unit(header)?;
+            if let Some(name) = unit.name {
+                let comp_dir = unit.comp_dir.map(|dir| dir.to_string_lossy());
+                source_file = Some(source_path(&name.to_string_lossy(), comp_dir.as_deref()));
+            }
             if let Some(program) = unit.line_program.clone() {
                 let mut text_sections =
                     obj_file.sections().filter(|s| s.kind() == SectionKind::Text);
@@ -486,11 +716,260 @@ fn line_info(obj_file: &File<'_>, sections: &mut [ObjSection]) -> Result<()> {
             }
         }
         if iter.next()?.is_some() {
-            log::warn!("Multiple units found in DWARF data, only processing the first");
+            log::warn!("Multiple units found in DWARF data, only reading line info from the first");
+        }
+
+        // Inlined functions are read from every unit, with addresses relocated into the layout
+        let dwarf = dwarf_cow.borrow(|(section, relocations)| {
+            gimli::RelocateReader::new(gimli::EndianSlice::new(section, endian), relocations)
+        });
+        let mut ranges = vec![];
+        let mut iter = dwarf.units();
+        while let Some(header) = iter.next()? {
+            let unit = dwarf.unit(header)?;
+            ranges.extend(unit_inline_ranges(&dwarf, &unit, endian, obj_path, search_paths)?);
+        }
+        for range in ranges.into_iter().filter(|r| r.end > r.start) {
+            let Some((section_idx, offset)) = layout.resolve(range.start) else { continue };
+            let section = &mut sections[section_idx];
+            // Stored as section addresses, like instruction addresses
+            let start = section.address + offset;
+            section.inline_ranges.push(ObjInlineRange {
+                start,
+                end: (start + (range.end - range.start)).min(section.address + section.size),
+                ..range
+            });
+        }
+        for section in sections.iter_mut() {
+            section.inline_ranges.sort_by_key(|r| (r.start, r.depth));
         }
     }
 
-    Ok(())
+    Ok(source_file)
+}
+
+/// Addresses of the code sections as DWARF sees them. In relocatable objects, each section is
+/// laid out at its own base so relocated addresses can be told apart.
+#[cfg(feature = "dwarf")]
+struct CodeLayout {
+    /// Base address, size and index into the object's sections
+    sections: Vec<(u64, u64, usize)>,
+    /// Base addresses by original section index
+    bases: HashMap<usize, u64>,
+}
+
+#[cfg(feature = "dwarf")]
+impl CodeLayout {
+    fn new(obj_file: &File<'_>, sections: &[ObjSection]) -> Self {
+        let relocatable = obj_file.kind() == object::ObjectKind::Relocatable;
+        let mut next = 0;
+        let mut layout = Self { sections: vec![], bases: HashMap::new() };
+        for (idx, section) in sections.iter().enumerate() {
+            if section.kind != ObjSectionKind::Code {
+                continue;
+            }
+            let base = if relocatable {
+                let base = next;
+                next = (next + section.size + 0xF) & !0xF;
+                base
+            } else {
+                section.address
+            };
+            layout.sections.push((base, section.size, idx));
+            layout.bases.insert(section.orig_index, base);
+        }
+        layout
+    }
+
+    /// Maps a DWARF address to a section index and the address within it.
+    fn resolve(&self, address: u64) -> Option<(usize, u64)> {
+        self.sections.iter().find_map(|&(base, size, idx)| {
+            (base..base + size).contains(&address).then_some((idx, address - base))
+        })
+    }
+}
+
+/// Relocations of a DWARF section in a relocatable object, by offset within the section.
+#[cfg(feature = "dwarf")]
+#[derive(Debug, Default)]
+struct DwarfRelocations(HashMap<usize, (u64, bool)>);
+
+#[cfg(feature = "dwarf")]
+impl DwarfRelocations {
+    fn new(obj_file: &File<'_>, section: &object::Section<'_, '_>, layout: &CodeLayout) -> Self {
+        let mut result = HashMap::new();
+        for (offset, reloc) in section.relocations() {
+            let (section_index, address) = match reloc.target() {
+                RelocationTarget::Symbol(idx) => match obj_file.symbol_by_index(idx) {
+                    Ok(symbol) => (symbol.section_index(), symbol.address()),
+                    Err(_) => continue,
+                },
+                RelocationTarget::Section(idx) => (Some(idx), 0),
+                _ => continue,
+            };
+            let base = section_index.and_then(|idx| layout.bases.get(&idx.0)).copied();
+            let value = base.unwrap_or(0).wrapping_add(address).wrapping_add(reloc.addend() as u64);
+            result.insert(offset as usize, (value, reloc.has_implicit_addend()));
+        }
+        Self(result)
+    }
+
+    fn relocate(&self, offset: usize, value: u64) -> u64 {
+        match self.0.get(&offset) {
+            Some(&(address, true)) => address.wrapping_add(value),
+            Some(&(address, false)) => address,
+            None => value,
+        }
+    }
+}
+
+#[cfg(feature = "dwarf")]
+impl gimli::read::Relocate for &DwarfRelocations {
+    fn relocate_address(&self, offset: usize, value: u64) -> gimli::Result<u64> {
+        Ok(self.relocate(offset, value))
+    }
+
+    fn relocate_offset(&self, offset: usize, value: usize) -> gimli::Result<usize> {
+        Ok(self.relocate(offset, value as u64) as usize)
+    }
+}
+
+#[cfg(feature = "dwarf")]
+type DwarfReader<'a> =
+    gimli::RelocateReader<gimli::EndianSlice<'a, gimli::RunTimeEndian>, &'a DwarfRelocations>;
+
+/// Reads the inlined functions of a unit, from its `.dwo` file with split DWARF.
+#[cfg(feature = "dwarf")]
+fn unit_inline_ranges(
+    dwarf: &gimli::Dwarf<DwarfReader<'_>>,
+    unit: &gimli::Unit<DwarfReader<'_>>,
+    endian: gimli::RunTimeEndian,
+    obj_path: &Path,
+    search_paths: &[PathBuf],
+) -> Result<Vec<ObjInlineRange>> {
+    if unit.dwo_id.is_some() {
+        let Some((path, data)) = dwo_file(dwarf, unit, obj_path, search_paths)? else {
+            return Ok(vec![]);
+        };
+        return Ok(dwo_inline_ranges(dwarf, unit, &data, endian).unwrap_or_else(|e| {
+            log::warn!("Skipping malformed split DWARF file {}: {e:#}", path.display());
+            vec![]
+        }));
+    }
+    Ok(inline_ranges(dwarf, unit).unwrap_or_else(|e| {
+        log::warn!("Failed to read inlined functions: {e}");
+        vec![]
+    }))
+}
+
+#[cfg(feature = "dwarf")]
+fn dwo_inline_ranges(
+    dwarf: &gimli::Dwarf<DwarfReader<'_>>,
+    unit: &gimli::Unit<DwarfReader<'_>>,
+    data: &[u8],
+    endian: gimli::RunTimeEndian,
+) -> Result<Vec<ObjInlineRange>> {
+    let file = File::parse(data)?;
+    let sections = gimli::DwarfSections::load(|id| {
+        Ok::<_, gimli::Error>(
+            id.dwo_name()
+                .and_then(|name| file.section_by_name(name))
+                .and_then(|section| section.uncompressed_data().ok())
+                .unwrap_or(std::borrow::Cow::Borrowed(&[][..])),
+        )
+    })?;
+    // The .dwo file has no relocations of its own, addresses come from the skeleton unit
+    let relocations = DwarfRelocations::default();
+    let mut dwo = sections.borrow(|section| {
+        gimli::RelocateReader::new(gimli::EndianSlice::new(section, endian), &relocations)
+    });
+    dwo.make_dwo(dwarf);
+    let header = dwo.units().next()?.ok_or_else(|| anyhow!("No units found"))?;
+    let mut dwo_unit = dwo.unit(header)?;
+    dwo_unit.copy_relocated_attributes(unit);
+    inline_ranges(&dwo, &dwo_unit)
+}
+
+/// Maps the `.dwo` file named by a skeleton unit, if it can be found.
+#[cfg(feature = "dwarf")]
+fn dwo_file<R: gimli::Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+    obj_path: &Path,
+    search_paths: &[PathBuf],
+) -> Result<Option<(PathBuf, memmap2::Mmap)>> {
+    let Some(name) = unit.dwo_name()? else { return Ok(None) };
+    let name = PathBuf::from(dwarf.attr_string(unit, name)?.to_string_lossy()?.as_ref());
+    let comp_dir_path = unit
+        .comp_dir
+        .as_ref()
+        .and_then(|dir| dir.to_string_lossy().ok())
+        .map(|dir| Path::new(dir.as_ref()).join(&name))
+        .filter(|path| path.is_file());
+    let path = comp_dir_path.or_else(|| {
+        let file_name = Path::new(name.file_name()?);
+        find_companion(obj_path, file_name, search_paths)
+    });
+    let Some(path) = path else {
+        log::warn!("Split DWARF file {} not found for {}", name.display(), obj_path.display());
+        return Ok(None);
+    };
+    log::debug!("Loading split DWARF from {}", path.display());
+    Ok(map_companion(&path).map(|data| (path, data)))
+}
+
+#[cfg(feature = "dwarf")]
+fn inline_ranges<R: gimli::Reader>(
+    dwarf: &gimli::Dwarf<R>,
+    unit: &gimli::Unit<R>,
+) -> Result<Vec<ObjInlineRange>> {
+    // Finds the name of an inlined function through its abstract origin
+    let origin_name = |entry: &gimli::DebuggingInformationEntry<R>| -> Result<Option<String>> {
+        let mut offset = match entry.attr_value(gimli::DW_AT_abstract_origin)? {
+            Some(gimli::AttributeValue::UnitRef(offset)) => offset,
+            _ => return Ok(None),
+        };
+        // Follow DW_AT_specification for out-of-line member function definitions
+        for _ in 0..2 {
+            let origin = unit.entry(offset)?;
+            if let Some(name) = origin.attr_value(gimli::DW_AT_name)? {
+                let name = dwarf.attr_string(unit, name)?;
+                return Ok(Some(name.to_string_lossy()?.into_owned()));
+            }
+            match origin.attr_value(gimli::DW_AT_specification)? {
+                Some(gimli::AttributeValue::UnitRef(next)) => offset = next,
+                _ => break,
+            }
+        }
+        Ok(None)
+    };
+
+    let mut result = vec![];
+    // Depths of the enclosing inlined subroutine entries
+    let mut scopes: Vec<isize> = vec![];
+    let mut depth = 0;
+    let mut entries = unit.entries();
+    while let Some((delta, entry)) = entries.next_dfs()? {
+        depth += delta;
+        while scopes.last().is_some_and(|&d| d >= depth) {
+            scopes.pop();
+        }
+        if entry.tag() != gimli::DW_TAG_inlined_subroutine {
+            continue;
+        }
+        let name = origin_name(entry)?.unwrap_or_else(|| "[unknown]".to_string());
+        let mut ranges = dwarf.die_ranges(unit, entry)?;
+        while let Some(range) = ranges.next()? {
+            result.push(ObjInlineRange {
+                name: name.clone(),
+                start: range.begin,
+                end: range.end,
+                depth: scopes.len() as u32,
+            });
+        }
+        scopes.push(depth);
+    }
+    Ok(result)
 }
 
 fn update_combined_symbol(symbol: ObjSymbol, address_change: i64) -> Result<ObjSymbol> {
@@ -511,6 +990,7 @@ fn update_combined_symbol(symbol: ObjSymbol, address_change: i64) -> Result<ObjS
         } else {
             None
         },
+        normalized_name: symbol.normalized_name,
     })
 }
 
@@ -540,6 +1020,15 @@ fn combine_sections(section: ObjSection, combine: ObjSection) -> Result<ObjSecti
         line_info.insert(key, line);
     }
 
+    let mut inline_ranges = section.inline_ranges;
+    for range in combine.inline_ranges {
+        inline_ranges.push(ObjInlineRange {
+            start: (range.start as i64 + address_change).try_into()?,
+            end: (range.end as i64 + address_change).try_into()?,
+            ..range
+        });
+    }
+
     Ok(ObjSection {
         name: section.name,
         kind: section.kind,
@@ -551,6 +1040,7 @@ fn combine_sections(section: ObjSection, combine: ObjSection) -> Result<ObjSecti
         relocations,
         virtual_address: section.virtual_address,
         line_info,
+        inline_ranges,
     })
 }
 
@@ -595,31 +1085,65 @@ fn combine_data_sections(sections: &mut Vec<ObjSection>) -> Result<()> {
 }
 
 pub fn read(obj_path: &Path, config: &DiffObjConfig) -> Result<ObjInfo> {
+    #[cfg(feature = "ppc")]
+    if let Some(kind) = image::ImageKind::from_path(obj_path) {
+        return image::read(obj_path, kind, config);
+    }
     let (data, timestamp) = {
         let file = fs::File::open(obj_path)?;
         let timestamp = FileTime::from_last_modification_time(&file.metadata()?);
         (unsafe { memmap2::Mmap::map(&file) }?, timestamp)
     };
     let obj_file = File::parse(&*data)?;
-    let arch = new_arch(&obj_file)?;
+    let arch = new_arch(&obj_file, config)?;
+    let ins_normalizer = InsNormalizer::new(obj_file.architecture(), arch.as_ref(), config)?;
     let split_meta = split_meta(&obj_file)?;
     let mut sections = filter_sections(&obj_file, split_meta.as_ref())?;
     for section in &mut sections {
-        section.symbols =
-            symbols_by_section(arch.as_ref(), &obj_file, section, split_meta.as_ref())?;
-        section.relocations =
-            relocations_by_section(arch.as_ref(), &obj_file, section, split_meta.as_ref())?;
+        section.symbols = symbols_by_section(
+            arch.as_ref(),
+            config.demangler,
+            &obj_file,
+            section,
+            split_meta.as_ref(),
+        )?;
+        section.relocations = relocations_by_section(
+            arch.as_ref(),
+            config.demangler,
+            &obj_file,
+            section,
+            split_meta.as_ref(),
+        )?;
     }
     if config.combine_data_sections {
         combine_data_sections(&mut sections)?;
     }
-    line_info(&obj_file, &mut sections)?;
-    let common = common_symbols(arch.as_ref(), &obj_file, split_meta.as_ref())?;
+    let source_file = line_info(&obj_file, obj_path, &mut sections, &config.debug_search_paths)?;
+    let mut common =
+        common_symbols(arch.as_ref(), config.demangler, &obj_file, split_meta.as_ref())?;
     let extab = exception_tables(&mut sections, &obj_file)?;
-    Ok(ObjInfo { arch, path: obj_path.to_owned(), timestamp, sections, common, extab, split_meta })
+    apply_symbol_name_rules(&mut sections, &mut common, &config.symbol_name_rules)?;
+    Ok(ObjInfo {
+        arch,
+        ins_normalizer,
+        path: obj_path.to_owned(),
+        timestamp,
+        sections,
+        common,
+        extab,
+        split_meta,
+        source_file,
+    })
 }
 
 pub fn has_function(obj_path: &Path, symbol_name: &str) -> Result<bool> {
+    #[cfg(feature = "ppc")]
+    if let Some(kind) = image::ImageKind::from_path(obj_path) {
+        let obj = image::read(obj_path, kind, &DiffObjConfig::default())?;
+        return Ok(obj.sections.iter().any(|s| {
+            s.kind == ObjSectionKind::Code && s.symbols.iter().any(|sym| sym.name == symbol_name)
+        }));
+    }
     let data = {
         let file = fs::File::open(obj_path)?;
         unsafe { memmap2::Mmap::map(&file) }?
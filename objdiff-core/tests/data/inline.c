@@ -0,0 +1,7 @@
+static inline __attribute__((always_inline)) void helper(void) { __asm__ volatile("ud2"); }
+
+void _start(void) {
+    __asm__ volatile("int3");
+    helper();
+    __asm__ volatile("hlt");
+}
@@ -0,0 +1,29 @@
+#![cfg(all(feature = "dwarf", feature = "x86"))]
+
+use std::path::Path;
+
+use objdiff_core::{diff::DiffObjConfig, obj::read};
+
+// inline.elf is data/inline.c built with:
+// gcc -O1 -g -nostdlib -static -fno-asynchronous-unwind-tables -fno-pie -no-pie
+//     -Wl,--build-id=none -Wl,-z,max-page-size=0x10 -Wl,-z,noseparate-code
+// and the linker's zero-size symbols removed with objcopy -N __bss_start -N _edata -N _end
+#[test]
+fn inline_range_covers_inlined_instructions() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/data/inline.elf");
+    let obj = read::read(&path, &DiffObjConfig::default()).unwrap();
+    let section = obj.sections.iter().find(|s| s.name == ".text").unwrap();
+    // Linked, so addresses in the section don't start at 0
+    assert_ne!(section.address, 0);
+
+    let [range] = section.inline_ranges.as_slice() else {
+        panic!("expected one inline range, got {:?}", section.inline_ranges);
+    };
+    assert_eq!(range.name, "helper");
+    assert_eq!(range.depth, 0);
+    // Only the ud2 from helper, between the int3 and hlt of _start
+    assert_eq!(range.start, section.address + 1);
+    assert_eq!(range.end, section.address + 3);
+    let offset = (range.start - section.address) as usize;
+    assert_eq!(&section.data[offset..offset + 2], &[0x0f, 0x0b]);
+}
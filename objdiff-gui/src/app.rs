@@ -1,37 +1,52 @@
 use std::{
+    collections::{BTreeMap, HashMap},
     default::Default,
     fs,
+    mem::take,
     path::{Path, PathBuf},
     rc::Rc,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex, RwLock,
     },
+    time::{Duration, Instant},
 };
 
 use filetime::FileTime;
-use globset::{Glob, GlobSet};
+use globset::Glob;
 use notify::{RecursiveMode, Watcher};
 use objdiff_core::{
     config::{
-        build_globset, ProjectConfigInfo, ProjectObject, ScratchConfig, DEFAULT_WATCH_PATTERNS,
+        build_watch_globset, BuildStatusConfig, ProjectConfigInfo, ProjectObject, ScratchConfig,
+        WatchGlobSet, DEFAULT_WATCH_PATTERNS,
     },
-    diff::DiffObjConfig,
+    diff::{
+        display::AddressMode, CodeDiffGranularity, Demangler, DiffAlignment, DiffObjConfig,
+        RelocDiffScore, SymbolVisibilityFilter,
+    },
+    obj::SymbolIdentityPolicy,
 };
-use time::UtcOffset;
+use strum::{EnumMessage, VariantArray};
+use time::{OffsetDateTime, UtcOffset};
 
 use crate::{
     app_config::{deserialize_config, AppConfigVersion},
-    config::{load_project_config, ProjectObjectNode},
+    config::{load_project_config, load_remote_project_config, ProjectObjectNode},
+    deeplink::DeepLink,
     jobs::{
+        fetch_config::start_fetch_config,
         objdiff::{start_build, ObjDiffConfig},
         Job, JobQueue, JobResult, JobStatus,
     },
+    remote::remote_cache_dir,
+    sound::CompletionSoundConfig,
+    storage::{storage_override, DirStorage},
     views::{
         appearance::{appearance_window, Appearance},
         config::{
             arch_config_window, config_ui, project_window, ConfigViewState, CONFIG_DISABLED_TEXT,
         },
+        dashboard::{dashboard_window, DashboardViewState},
         data_diff::data_diff_ui,
         debug::debug_window,
         demangle::{demangle_window, DemangleViewState},
@@ -40,8 +55,12 @@ use crate::{
         function_diff::function_diff_ui,
         graphics::{graphics_window, GraphicsConfig, GraphicsViewState},
         jobs::jobs_ui,
+        keybindings::{keybindings_window, KeyAction, Keybindings},
         rlwinm::{rlwinm_decode_window, RlwinmDecodeViewState},
-        symbol_diff::{symbol_diff_ui, DiffViewState, View},
+        symbol_diff::{
+            symbol_diff_ui, DiffSession, DiffViewState, SymbolAlignment, SymbolClickAction,
+            SymbolClickActions, SymbolColumns, SymbolRefByName, View,
+        },
     },
 };
 
@@ -49,18 +68,21 @@ use crate::{
 pub struct ViewState {
     pub jobs: JobQueue,
     pub config_state: ConfigViewState,
+    pub dashboard_state: DashboardViewState,
     pub demangle_state: DemangleViewState,
     pub rlwinm_decode_state: RlwinmDecodeViewState,
     pub diff_state: DiffViewState,
     pub graphics_state: GraphicsViewState,
     pub frame_history: FrameHistory,
     pub show_appearance_config: bool,
+    pub show_dashboard: bool,
     pub show_demangle: bool,
     pub show_rlwinm_decode: bool,
     pub show_project_config: bool,
     pub show_arch_config: bool,
     pub show_debug: bool,
     pub show_graphics: bool,
+    pub show_keybindings: bool,
 }
 
 /// The configuration for a single object file.
@@ -72,6 +94,9 @@ pub struct ObjectConfig {
     pub reverse_fn_order: Option<bool>,
     pub complete: Option<bool>,
     pub scratch: Option<ScratchConfig>,
+    pub build_status: Option<BuildStatusConfig>,
+    pub make_args: Option<Vec<String>>,
+    pub symbol_mappings: Option<Vec<(String, String)>>,
 }
 
 #[inline]
@@ -94,8 +119,14 @@ pub struct AppConfig {
     pub custom_args: Option<Vec<String>>,
     #[serde(default)]
     pub selected_wsl_distro: Option<String>,
+    /// Where Windows drives are mounted in WSL (`automount.root` in `/etc/wsl.conf`)
+    #[serde(default)]
+    pub wsl_mount_prefix: Option<String>,
     #[serde(default)]
     pub project_dir: Option<PathBuf>,
+    /// URL of a remote project config, mirrored locally in `project_dir`
+    #[serde(default)]
+    pub project_url: Option<String>,
     #[serde(default)]
     pub target_obj_dir: Option<PathBuf>,
     #[serde(default)]
@@ -107,7 +138,12 @@ pub struct AppConfig {
     #[serde(default)]
     pub build_target: bool,
     #[serde(default = "bool_true")]
+    pub build_parallel: bool,
+    #[serde(default = "bool_true")]
     pub rebuild_on_changes: bool,
+    /// Seconds between periodic refreshes, in case the file watcher misses changes
+    #[serde(default)]
+    pub auto_refresh_interval: Option<u64>,
     #[serde(default)]
     pub auto_update_check: bool,
     #[serde(default = "default_watch_patterns")]
@@ -116,6 +152,50 @@ pub struct AppConfig {
     pub recent_projects: Vec<PathBuf>,
     #[serde(default)]
     pub diff_obj_config: DiffObjConfig,
+    #[serde(default)]
+    pub symbol_identity: SymbolIdentityPolicy,
+    #[serde(default)]
+    pub collapse_matched_sections: bool,
+    /// Scroll both symbol lists together vertically
+    #[serde(default = "bool_true")]
+    pub link_symbol_scroll: bool,
+    /// Scroll both sides of the function diff together vertically
+    #[serde(default = "bool_true")]
+    pub link_function_scroll: bool,
+    #[serde(default)]
+    pub symbol_alignment: SymbolAlignment,
+    #[serde(default)]
+    pub symbol_click_actions: SymbolClickActions,
+    #[serde(default)]
+    pub symbol_columns: SymbolColumns,
+    /// Section name patterns, in the order sections are listed
+    #[serde(default)]
+    pub section_order: Vec<Glob>,
+    /// Regex for build log lines to highlight as errors, in addition to lines containing the
+    /// word "error"
+    #[serde(default)]
+    pub build_log_error_pattern: String,
+    /// JSON Lines file that a record is appended to after each successful build
+    #[serde(default)]
+    pub build_history_path: Option<PathBuf>,
+    /// Sound played when the selected symbol reaches 100%
+    #[serde(default)]
+    pub completion_sound: CompletionSoundConfig,
+    /// Maximum number of build processes running at once, or one per CPU if unset
+    #[serde(default)]
+    pub max_parallel_builds: Option<usize>,
+    /// Seconds after which a build is killed, or no limit if unset
+    #[serde(default)]
+    pub build_timeout: Option<u64>,
+    /// Keyboard shortcuts for each action
+    #[serde(default)]
+    pub keybindings: Keybindings,
+    /// decomp.me scratches created from this project, keyed by object name, then function name
+    #[serde(default)]
+    pub scratch_slugs: BTreeMap<String, BTreeMap<String, String>>,
+    /// Demangler last taken from the project config, so that reloading it keeps one chosen in the UI
+    #[serde(default)]
+    pub project_demangler: Option<Demangler>,
 
     #[serde(skip)]
     pub objects: Vec<ProjectObject>,
@@ -133,6 +213,22 @@ pub struct AppConfig {
     pub queue_reload: bool,
     #[serde(skip)]
     pub project_config_info: Option<ProjectConfigInfo>,
+    /// Objects whose files are missing, as of loading the project config
+    #[serde(skip)]
+    pub object_problems: Vec<String>,
+    /// Snapshot of the selected object to diff against instead of the base object
+    #[serde(skip)]
+    pub base_snapshot: Option<String>,
+    /// Another object whose base object the target is diffed against
+    #[serde(skip)]
+    pub base_object: Option<String>,
+    /// Target and base symbols paired in the symbol list, on top of the selected object's
+    /// `symbol_mappings`
+    #[serde(skip)]
+    pub symbol_pairs: Vec<(String, String)>,
+    /// Time of the last successful build, keyed by object name
+    #[serde(skip)]
+    pub build_times: HashMap<String, OffsetDateTime>,
 }
 
 impl Default for AppConfig {
@@ -142,17 +238,37 @@ impl Default for AppConfig {
             custom_make: None,
             custom_args: None,
             selected_wsl_distro: None,
+            wsl_mount_prefix: None,
             project_dir: None,
+            project_url: None,
             target_obj_dir: None,
             base_obj_dir: None,
             selected_obj: None,
             build_base: true,
             build_target: false,
+            build_parallel: true,
             rebuild_on_changes: true,
+            auto_refresh_interval: None,
             auto_update_check: true,
             watch_patterns: DEFAULT_WATCH_PATTERNS.iter().map(|s| Glob::new(s).unwrap()).collect(),
             recent_projects: vec![],
             diff_obj_config: Default::default(),
+            symbol_identity: Default::default(),
+            collapse_matched_sections: false,
+            link_symbol_scroll: true,
+            link_function_scroll: true,
+            symbol_alignment: Default::default(),
+            symbol_click_actions: Default::default(),
+            symbol_columns: Default::default(),
+            section_order: vec![],
+            build_log_error_pattern: String::new(),
+            build_history_path: None,
+            completion_sound: Default::default(),
+            max_parallel_builds: None,
+            build_timeout: None,
+            keybindings: Default::default(),
+            scratch_slugs: BTreeMap::new(),
+            project_demangler: None,
             objects: vec![],
             object_nodes: vec![],
             watcher_change: false,
@@ -161,6 +277,11 @@ impl Default for AppConfig {
             queue_build: false,
             queue_reload: false,
             project_config_info: None,
+            object_problems: vec![],
+            base_snapshot: None,
+            base_object: None,
+            symbol_pairs: vec![],
+            build_times: HashMap::new(),
         }
     }
 }
@@ -172,7 +293,17 @@ impl AppConfig {
             self.recent_projects.truncate(9);
         }
         self.recent_projects.insert(0, path.clone());
+        self.open_project(path);
+    }
+
+    pub fn set_project_url(&mut self, url: String) {
+        self.open_project(remote_cache_dir(&url));
+        self.project_url = Some(url);
+    }
+
+    fn open_project(&mut self, path: PathBuf) {
         self.project_dir = Some(path);
+        self.project_url = None;
         self.target_obj_dir = None;
         self.base_obj_dir = None;
         self.selected_obj = None;
@@ -184,6 +315,7 @@ impl AppConfig {
         self.obj_change = true;
         self.queue_build = false;
         self.project_config_info = None;
+        self.object_problems.clear();
     }
 
     pub fn set_target_obj_dir(&mut self, path: PathBuf) {
@@ -215,14 +347,30 @@ pub struct App {
     view_state: ViewState,
     config: AppConfigRef,
     modified: Arc<AtomicBool>,
+    /// When the last watched file change was seen, pending the debounce delay
+    modified_at: Option<Instant>,
     watcher: Option<notify::RecommendedWatcher>,
+    watch_error: Arc<Mutex<Option<String>>>,
     app_path: Option<PathBuf>,
     relaunch_path: Rc<Mutex<Option<PathBuf>>>,
     should_relaunch: bool,
+    pending_link: Option<DeepLink>,
+    /// Diff view state from the previous session, restored once its object is selected
+    pending_session: Option<DiffSession>,
+    last_auto_refresh: Option<Instant>,
+    /// Storage in the overridden config directory, used instead of eframe's
+    storage: Option<DirStorage>,
 }
 
 pub const APPEARANCE_KEY: &str = "appearance";
 pub const CONFIG_KEY: &str = "app_config";
+pub const SESSION_KEY: &str = "diff_session";
+pub const SECTIONS_KEY: &str = "section_open";
+/// How long toast messages stay on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(2);
+/// Quiet period after a watched file changes before rebuilding, as editors often write
+/// several times per save.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
 
 impl App {
     /// Called once before the first frame.
@@ -233,11 +381,17 @@ impl App {
         app_path: Option<PathBuf>,
         graphics_config: GraphicsConfig,
         graphics_config_path: Option<PathBuf>,
+        deep_link: Option<DeepLink>,
     ) -> Self {
         // Load previous app state (if any).
         // Note that you must enable the `persistence` feature for this to work.
-        let mut app = Self::default();
-        if let Some(storage) = cc.storage {
+        let mut app =
+            Self { storage: storage_override().map(DirStorage::new), ..Default::default() };
+        let storage = match &app.storage {
+            Some(storage) => Some(storage as &dyn eframe::Storage),
+            None => cc.storage,
+        };
+        if let Some(storage) = storage {
             if let Some(appearance) = eframe::get_value::<Appearance>(storage, APPEARANCE_KEY) {
                 app.appearance = appearance;
             }
@@ -252,11 +406,22 @@ impl App {
                 app.view_state.config_state.queue_check_update = config.auto_update_check;
                 app.config = Arc::new(RwLock::new(config));
             }
+            app.pending_session = eframe::get_value::<DiffSession>(storage, SESSION_KEY);
+            if let Some(section_open) = eframe::get_value(storage, SECTIONS_KEY) {
+                app.view_state.diff_state.symbol_state.section_open = section_open;
+            }
         }
         app.appearance.init_fonts(&cc.egui_ctx);
         app.appearance.utc_offset = utc_offset;
         app.app_path = app_path;
         app.relaunch_path = relaunch_path;
+        if let Some(link) = deep_link {
+            if let Ok(mut config) = app.config.write() {
+                config.set_project_dir(link.project_dir.clone());
+            }
+            app.pending_link = Some(link);
+            app.pending_session = None;
+        }
         #[cfg(feature = "wgpu")]
         if let Some(wgpu_render_state) = &cc.wgpu_render_state {
             use eframe::egui_wgpu::wgpu::Backend;
@@ -287,7 +452,8 @@ impl App {
     fn pre_update(&mut self, ctx: &egui::Context) {
         self.appearance.pre_update(ctx);
 
-        let ViewState { jobs, diff_state, config_state, .. } = &mut self.view_state;
+        let ViewState { jobs, diff_state, config_state, dashboard_state, show_dashboard, .. } =
+            &mut self.view_state;
 
         let mut results = vec![];
         for (job, result) in jobs.iter_finished() {
@@ -306,6 +472,27 @@ impl App {
                                 self.should_relaunch = true;
                             }
                         }
+                        JobResult::FetchConfig(Some(result)) => {
+                            let Ok(mut config) = self.config.write() else {
+                                continue;
+                            };
+                            match load_remote_project_config(
+                                &mut config,
+                                &result.url,
+                                result.config,
+                            ) {
+                                Ok(true) => {
+                                    config_state.load_error = None;
+                                    diff_state.project_progress = Default::default();
+                                    dashboard_state.invalidate(*show_dashboard);
+                                }
+                                Ok(false) => {}
+                                Err(e) => {
+                                    log::error!("Failed to load project config: {e}");
+                                    config_state.load_error = Some(format!("{e}"));
+                                }
+                            }
+                        }
                         _ => results.push(result),
                     }
                 }
@@ -328,6 +515,7 @@ impl App {
                             progress_items: None,
                             status: String::new(),
                             error: Some(err),
+                            log: vec![],
                         }));
                     }
                 }
@@ -338,15 +526,28 @@ impl App {
 
         diff_state.pre_update(jobs, &self.config);
         config_state.pre_update(jobs, &self.config);
+        dashboard_state.pre_update(jobs);
         debug_assert!(jobs.results.is_empty());
+        if let Some(progress) = dashboard_state.progress.take() {
+            diff_state.project_progress = progress;
+        }
     }
 
     fn post_update(&mut self, ctx: &egui::Context) {
         self.appearance.post_update(ctx);
 
-        let ViewState { jobs, diff_state, config_state, graphics_state, .. } = &mut self.view_state;
+        let ViewState {
+            jobs,
+            diff_state,
+            config_state,
+            dashboard_state,
+            graphics_state,
+            show_dashboard,
+            ..
+        } = &mut self.view_state;
         config_state.post_update(ctx, jobs, &self.config);
         diff_state.post_update(ctx, jobs, &self.config);
+        dashboard_state.post_update(ctx, jobs, &self.config);
 
         let Ok(mut config) = self.config.write() else {
             return;
@@ -354,18 +555,30 @@ impl App {
         let config = &mut *config;
 
         if let Some(info) = &config.project_config_info {
-            if file_modified(&info.path, info.timestamp) {
+            if file_modified(&info.path, info.timestamp)
+                || info.includes.iter().any(|(path, ts)| file_modified(path, *ts))
+            {
                 config.config_change = true;
             }
         }
 
         if config.config_change {
             config.config_change = false;
-            match load_project_config(config) {
-                Ok(()) => config_state.load_error = None,
-                Err(e) => {
-                    log::error!("Failed to load project config: {e}");
-                    config_state.load_error = Some(format!("{e}"));
+            if let Some(url) = &config.project_url {
+                // Fetched in the background, then applied in pre_update
+                let url = url.clone();
+                jobs.push_once(Job::FetchConfig, || start_fetch_config(ctx, url));
+            } else {
+                match load_project_config(config) {
+                    Ok(()) => {
+                        config_state.load_error = None;
+                        diff_state.project_progress = Default::default();
+                        dashboard_state.invalidate(*show_dashboard);
+                    }
+                    Err(e) => {
+                        log::error!("Failed to load project config: {e}");
+                        config_state.load_error = Some(format!("{e}"));
+                    }
                 }
             }
         }
@@ -374,29 +587,149 @@ impl App {
             drop(self.watcher.take());
 
             if let Some(project_dir) = &config.project_dir {
-                match build_globset(&config.watch_patterns).map_err(anyhow::Error::new).and_then(
-                    |globset| {
-                        create_watcher(ctx.clone(), self.modified.clone(), project_dir, globset)
-                            .map_err(anyhow::Error::new)
-                    },
-                ) {
-                    Ok(watcher) => self.watcher = Some(watcher),
-                    Err(e) => log::error!("Failed to create watcher: {e}"),
+                match build_watch_globset(&config.watch_patterns)
+                    .map_err(anyhow::Error::new)
+                    .and_then(|globset| {
+                        create_watcher(
+                            ctx.clone(),
+                            self.modified.clone(),
+                            self.watch_error.clone(),
+                            project_dir,
+                            globset,
+                        )
+                        .map_err(anyhow::Error::new)
+                    }) {
+                    Ok(watcher) => {
+                        self.watcher = Some(watcher);
+                        config_state.watch_error = None;
+                    }
+                    Err(e) => {
+                        log::error!("Failed to create watcher: {e}");
+                        config_state.watch_error = Some(format!("{e}"));
+                    }
                 }
                 config.watcher_change = false;
             }
         }
 
+        if let Some((object, symbol)) = dashboard_state.open_symbol.take() {
+            if let Some(project_dir) = &config.project_dir {
+                self.pending_link = Some(DeepLink {
+                    project_dir: project_dir.clone(),
+                    object: Some(object),
+                    symbol: Some(symbol),
+                });
+            }
+        }
+
+        let mut link_symbol = None;
+        if let Some(link) = self.pending_link.take() {
+            if let Some(object_name) = &link.object {
+                if let Some(project_dir) = &config.project_dir {
+                    let object = config.objects.iter().find(|o| o.name() == object_name).cloned();
+                    if let Some(mut object) = object {
+                        object.resolve_paths(
+                            project_dir,
+                            config.target_obj_dir.as_deref(),
+                            config.base_obj_dir.as_deref(),
+                        );
+                        config.set_selected_obj(ObjectConfig {
+                            name: object_name.clone(),
+                            target_path: object.target_path,
+                            base_path: object.base_path,
+                            reverse_fn_order: object.reverse_fn_order,
+                            complete: object.complete,
+                            scratch: object.scratch,
+                            build_status: object.build_status,
+                            make_args: object.make_args,
+                            symbol_mappings: object.symbol_mappings,
+                        });
+                        link_symbol = link.symbol;
+                    } else {
+                        log::error!("Linked object '{object_name}' not found in project");
+                    }
+                }
+            }
+        }
+
         if config.obj_change {
+            let project_progress = take(&mut diff_state.project_progress);
+            let section_open = take(&mut diff_state.symbol_state.section_open);
+            let reverse_fn_order = diff_state.symbol_state.reverse_fn_order;
+            let side_panel_width = diff_state.side_panel_width;
             *diff_state = Default::default();
+            diff_state.project_progress = project_progress;
+            diff_state.symbol_state.section_open = section_open;
+            diff_state.symbol_state.reverse_fn_order = reverse_fn_order;
+            diff_state.side_panel_width = side_panel_width;
+            config.base_snapshot = None;
+            config.base_object = None;
+            config.symbol_pairs.clear();
             if config.selected_obj.is_some() {
                 config.queue_build = true;
             }
             config.obj_change = false;
         }
 
+        if let Some(symbol_name) = link_symbol {
+            diff_state.symbol_state.selected_symbol = Some(SymbolRefByName {
+                symbol_name,
+                demangled_symbol_name: None,
+                section_name: String::new(),
+                section_address: None,
+            });
+            diff_state.current_view = View::FunctionDiff;
+        }
+
+        if let Some(session) = self.pending_session.take() {
+            let selected = config.selected_obj.as_ref().map(|obj| obj.name.as_str());
+            let same_object = session.object.is_some() && session.object.as_deref() == selected;
+            diff_state.restore_session(session, same_object);
+        }
+
+        if let Some(error) = self.watch_error.lock().unwrap().take() {
+            config_state.watch_error = Some(error);
+        }
+
         if self.modified.swap(false, Ordering::Relaxed) && config.rebuild_on_changes {
-            config.queue_build = true;
+            self.modified_at = Some(Instant::now());
+        }
+        if let Some(modified_at) = self.modified_at {
+            let elapsed = modified_at.elapsed();
+            if elapsed >= WATCH_DEBOUNCE {
+                self.modified_at = None;
+                // Without anything to build, only the objects need reloading
+                if config.build_target || config.build_base {
+                    config.queue_build = true;
+                } else {
+                    config.queue_reload = true;
+                }
+            } else {
+                ctx.request_repaint_after(WATCH_DEBOUNCE - elapsed);
+            }
+        }
+
+        if let Some(interval) =
+            config.auto_refresh_interval.filter(|&s| s > 0).map(Duration::from_secs)
+        {
+            let now = Instant::now();
+            let elapsed = now.duration_since(*self.last_auto_refresh.get_or_insert(now));
+            if elapsed >= interval {
+                self.last_auto_refresh = Some(now);
+                // Skip rather than queue behind a running build, so refreshes never stack
+                if config.selected_obj.is_some() && !jobs.is_running(Job::ObjDiff) {
+                    if config.rebuild_on_changes {
+                        config.queue_build = true;
+                    } else {
+                        config.queue_reload = true;
+                    }
+                }
+                ctx.request_repaint_after(interval);
+            } else {
+                ctx.request_repaint_after(interval - elapsed);
+            }
+        } else {
+            self.last_auto_refresh = None;
         }
 
         if let Some(result) = &diff_state.build {
@@ -453,22 +786,47 @@ impl eframe::App for App {
         let ViewState {
             jobs,
             config_state,
+            dashboard_state,
             demangle_state,
             rlwinm_decode_state,
             diff_state,
             graphics_state,
             frame_history,
             show_appearance_config,
+            show_dashboard,
             show_demangle,
             show_rlwinm_decode,
             show_project_config,
             show_arch_config,
             show_debug,
             show_graphics,
+            show_keybindings,
         } = view_state;
 
         frame_history.on_new_frame(ctx.input(|i| i.time), frame.info().cpu_usage);
 
+        let keybindings = config.read().unwrap().keybindings.clone();
+        let enabled = |action| match action {
+            KeyAction::Back => diff_state.current_view != View::SymbolDiff,
+            KeyAction::NextUnmatched | KeyAction::PrevUnmatched => {
+                matches!(diff_state.current_view, View::SymbolDiff | View::FunctionDiff)
+            }
+            _ => true,
+        };
+        for action in keybindings.pressed(ctx, enabled) {
+            match action {
+                KeyAction::ToggleHelp => *show_keybindings = !*show_keybindings,
+                KeyAction::Build => diff_state.queue_build = true,
+                KeyAction::Back => diff_state.current_view = View::SymbolDiff,
+                KeyAction::ToggleDashboard => *show_dashboard = !*show_dashboard,
+                KeyAction::IncreaseFontSize => appearance.adjust_code_font_size(1.0),
+                KeyAction::DecreaseFontSize => appearance.adjust_code_font_size(-1.0),
+                KeyAction::NextUnmatched | KeyAction::PrevUnmatched => {
+                    diff_state.select_unmatched(action == KeyAction::NextUnmatched)
+                }
+            }
+        }
+
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::menu::bar(ui, |ui| {
                 ui.menu_button("File", |ui| {
@@ -510,11 +868,19 @@ impl eframe::App for App {
                         *show_graphics = !*show_graphics;
                         ui.close_menu();
                     }
+                    if ui.button("Keyboard shortcuts…").clicked() {
+                        *show_keybindings = !*show_keybindings;
+                        ui.close_menu();
+                    }
                     if ui.button("Quit").clicked() {
                         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                     }
                 });
                 ui.menu_button("Tools", |ui| {
+                    if ui.button("Unmatched functions…").clicked() {
+                        *show_dashboard = !*show_dashboard;
+                        ui.close_menu();
+                    }
                     if ui.button("Demangle…").clicked() {
                         *show_demangle = !*show_demangle;
                         ui.close_menu();
@@ -536,10 +902,16 @@ impl eframe::App for App {
                     if response.changed() {
                         config.watcher_change = true;
                     };
+                    let symbol_state = &mut diff_state.symbol_state;
+                    // Show the object's setting when it overrides, leaving the global one as is
+                    let mut obj_reverse_fn_order = symbol_state.obj_reverse_fn_order;
                     ui.add_enabled(
-                        !diff_state.symbol_state.disable_reverse_fn_order,
+                        obj_reverse_fn_order.is_none(),
                         egui::Checkbox::new(
-                            &mut diff_state.symbol_state.reverse_fn_order,
+                            match &mut obj_reverse_fn_order {
+                                Some(value) => value,
+                                None => &mut symbol_state.reverse_fn_order,
+                            },
                             "Reverse function order (-inline deferred)",
                         ),
                     )
@@ -548,6 +920,39 @@ impl eframe::App for App {
                         &mut diff_state.symbol_state.show_hidden_symbols,
                         "Show hidden symbols",
                     );
+                    ui.checkbox(&mut config.collapse_matched_sections, "Collapse matched sections")
+                        .on_hover_text(
+                            "Collapses fully matched sections, unless opened or collapsed manually.",
+                        );
+                    ui.checkbox(&mut config.link_symbol_scroll, "Link symbol list scrolling")
+                        .on_hover_text(
+                            "Scrolls both symbol lists together vertically. Unlink them when the \
+                            objects' symbol lists differ a lot in length.",
+                        );
+                    ui.checkbox(&mut config.link_function_scroll, "Link function diff scrolling")
+                        .on_hover_text(
+                            "Scrolls both sides of the function diff together vertically. \
+                            Horizontal scrolling stays independent.",
+                        );
+                    ui.checkbox(&mut diff_state.function_state.show_cycle_costs, "Show cycle costs")
+                        .on_hover_text("Annotates instructions with their approximate cycle cost.");
+                    ui.checkbox(
+                        &mut diff_state.function_state.show_inline_scopes,
+                        "Show inlined functions",
+                    )
+                    .on_hover_text("Marks where inlined functions begin, using DWARF info.");
+                    ui.checkbox(
+                        &mut diff_state.function_state.show_branch_labels,
+                        "Show branch labels",
+                    )
+                    .on_hover_text(
+                        "Labels branch targets within the function (e.g. .L0) in place of arrows.",
+                    );
+                    ui.checkbox(&mut diff_state.function_state.show_raw_bytes, "Show raw bytes")
+                        .on_hover_text(
+                            "Shows each instruction's encoding, highlighting bytes that differ \
+                            from the other side.",
+                        );
                     if ui
                         .checkbox(
                             &mut config.diff_obj_config.relax_reloc_diffs,
@@ -560,6 +965,18 @@ impl eframe::App for App {
                     {
                         config.queue_reload = true;
                     }
+                    if ui
+                        .checkbox(
+                            &mut config.diff_obj_config.raw_reloc_diffs,
+                            "Compare relocation addresses",
+                        )
+                        .on_hover_text(
+                            "Compares relocation targets by address and addend instead of by symbol name. Useful for debugging mismatches.",
+                        )
+                        .changed()
+                    {
+                        config.queue_reload = true;
+                    }
                     if ui
                         .checkbox(
                             &mut config.diff_obj_config.space_between_args,
@@ -579,6 +996,199 @@ impl eframe::App for App {
                     {
                         config.queue_reload = true;
                     }
+                    if ui
+                        .checkbox(
+                            &mut config.diff_obj_config.ignore_padding_nops,
+                            "Ignore padding NOPs",
+                        )
+                        .on_hover_text(
+                            "Excludes trailing padding NOPs from the match percentage when the rest of the function matches.",
+                        )
+                        .changed()
+                    {
+                        config.queue_reload = true;
+                    }
+                    if ui
+                        .checkbox(
+                            &mut config.diff_obj_config.ignore_address_args,
+                            "Ignore address operands",
+                        )
+                        .on_hover_text(
+                            "Treats unrelocated absolute addresses as equal: lis (PowerPC), lui (MIPS) and movw/movt (ARM) immediates, and x86 memory operands that are only a displacement.",
+                        )
+                        .changed()
+                    {
+                        config.queue_reload = true;
+                    }
+                    if ui
+                        .checkbox(
+                            &mut config.diff_obj_config.detect_moved_blocks,
+                            "Detect moved blocks",
+                        )
+                        .on_hover_text(
+                            "Colors blocks of instructions that were reordered without changes, instead of showing them as deleted and inserted.",
+                        )
+                        .changed()
+                    {
+                        config.queue_reload = true;
+                    }
+                    if ui
+                        .checkbox(&mut config.diff_obj_config.lazy_code_diff, "Lazy function diffs")
+                        .on_hover_text(
+                            "Diffs functions when their section is expanded, instead of up front.\nSection match percentages and build history are unavailable.",
+                        )
+                        .changed()
+                    {
+                        config.queue_reload = true;
+                    }
+                    ui.menu_button("Symbol order", |ui| {
+                        for &alignment in SymbolAlignment::VARIANTS {
+                            if ui
+                                .selectable_label(
+                                    config.symbol_alignment == alignment,
+                                    alignment.get_message().unwrap(),
+                                )
+                                .on_hover_text(alignment.get_detailed_message().unwrap())
+                                .clicked()
+                            {
+                                config.symbol_alignment = alignment;
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button("Symbol clicks", |ui| {
+                        let actions = &mut config.symbol_click_actions;
+                        for (label, action) in [
+                            ("Click", &mut actions.click),
+                            ("Double click", &mut actions.double_click),
+                            ("Ctrl click", &mut actions.modifier_click),
+                        ] {
+                            ui.menu_button(label, |ui| {
+                                for &variant in SymbolClickAction::VARIANTS {
+                                    if ui
+                                        .selectable_label(
+                                            *action == variant,
+                                            variant.get_message().unwrap(),
+                                        )
+                                        .clicked()
+                                    {
+                                        *action = variant;
+                                        ui.close_menu();
+                                    }
+                                }
+                            });
+                        }
+                    });
+                    ui.menu_button("Symbol columns", |ui| {
+                        let columns = &mut config.symbol_columns;
+                        ui.checkbox(&mut columns.flags, "Flags");
+                        ui.checkbox(&mut columns.match_percent, "Match percentage");
+                        ui.checkbox(&mut columns.regalloc_match_percent, "Match ignoring registers")
+                            .on_hover_text(
+                                "Also show the match percentage with instructions that differ \
+                                 only in register operands counted as matching.",
+                            );
+                        ui.checkbox(&mut columns.pair_confidence, "Pairing confidence");
+                        ui.checkbox(&mut columns.address, "Address");
+                        ui.checkbox(&mut columns.size, "Size");
+                        ui.checkbox(&mut columns.size_mismatch, "Size mismatch")
+                            .on_hover_text("Marks paired symbols whose sizes differ with Δ.");
+                    });
+                    ui.menu_button("Symbol identity", |ui| {
+                        for &policy in SymbolIdentityPolicy::VARIANTS {
+                            if ui
+                                .selectable_label(
+                                    config.symbol_identity == policy,
+                                    policy.get_message().unwrap(),
+                                )
+                                .on_hover_text(policy.get_detailed_message().unwrap())
+                                .clicked()
+                            {
+                                config.symbol_identity = policy;
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button("Match percentage", |ui| {
+                        for &visibility in SymbolVisibilityFilter::VARIANTS {
+                            if ui
+                                .selectable_label(
+                                    config.diff_obj_config.match_visibility == visibility,
+                                    visibility.get_message().unwrap(),
+                                )
+                                .on_hover_text(visibility.get_detailed_message().unwrap())
+                                .clicked()
+                            {
+                                config.diff_obj_config.match_visibility = visibility;
+                                config.queue_reload = true;
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button("Addresses", |ui| {
+                        for &mode in AddressMode::VARIANTS {
+                            let state = &mut diff_state.function_state;
+                            if ui
+                                .selectable_label(
+                                    state.address_mode == mode,
+                                    mode.get_message().unwrap(),
+                                )
+                                .on_hover_text(mode.get_detailed_message().unwrap())
+                                .clicked()
+                            {
+                                state.address_mode = mode;
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button("Alignment", |ui| {
+                        for &alignment in DiffAlignment::VARIANTS {
+                            if ui
+                                .selectable_label(
+                                    config.diff_obj_config.diff_alignment == alignment,
+                                    alignment.get_message().unwrap(),
+                                )
+                                .on_hover_text(alignment.get_detailed_message().unwrap())
+                                .clicked()
+                            {
+                                config.diff_obj_config.diff_alignment = alignment;
+                                config.queue_reload = true;
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button("Relocation differences", |ui| {
+                        for &score in RelocDiffScore::VARIANTS {
+                            if ui
+                                .selectable_label(
+                                    config.diff_obj_config.reloc_diff_score == score,
+                                    score.get_message().unwrap(),
+                                )
+                                .on_hover_text(score.get_detailed_message().unwrap())
+                                .clicked()
+                            {
+                                config.diff_obj_config.reloc_diff_score = score;
+                                config.queue_reload = true;
+                                ui.close_menu();
+                            }
+                        }
+                    });
+                    ui.menu_button("Code granularity", |ui| {
+                        for &granularity in CodeDiffGranularity::VARIANTS {
+                            if ui
+                                .selectable_label(
+                                    config.diff_obj_config.code_diff_granularity == granularity,
+                                    granularity.get_message().unwrap(),
+                                )
+                                .on_hover_text(granularity.get_detailed_message().unwrap())
+                                .clicked()
+                            {
+                                config.diff_obj_config.code_diff_granularity = granularity;
+                                config.queue_reload = true;
+                                ui.close_menu();
+                            }
+                        }
+                    });
                 });
             });
         });
@@ -597,43 +1207,80 @@ impl eframe::App for App {
                 extab_diff_ui(ui, diff_state, appearance);
             });
         } else {
-            egui::SidePanel::left("side_panel").show(ctx, |ui| {
+            let mut side_panel = egui::SidePanel::left("side_panel");
+            if let Some(width) = diff_state.side_panel_width {
+                side_panel = side_panel.default_width(width);
+            }
+            let response = side_panel.show(ctx, |ui| {
                 egui::ScrollArea::both().show(ui, |ui| {
                     config_ui(ui, config, show_project_config, config_state, appearance);
                     jobs_ui(ui, jobs, appearance);
                 });
             });
+            diff_state.side_panel_width = Some(response.response.rect.width());
 
             egui::CentralPanel::default().show(ctx, |ui| {
                 symbol_diff_ui(ui, diff_state, appearance);
             });
         }
 
+        if let Some((text, shown)) = &diff_state.toast {
+            let elapsed = shown.elapsed();
+            if elapsed < TOAST_DURATION {
+                egui::Area::new(egui::Id::new("toast"))
+                    .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -40.0])
+                    .interactable(false)
+                    .show(ctx, |ui| {
+                        egui::Frame::popup(ui.style()).show(ui, |ui| ui.label(text));
+                    });
+                ctx.request_repaint_after(TOAST_DURATION - elapsed);
+            } else {
+                diff_state.toast = None;
+            }
+        }
+
         project_window(ctx, config, show_project_config, config_state, appearance);
         appearance_window(ctx, show_appearance_config, appearance);
+        dashboard_window(ctx, show_dashboard, dashboard_state, appearance);
         demangle_window(ctx, show_demangle, demangle_state, appearance);
         rlwinm_decode_window(ctx, show_rlwinm_decode, rlwinm_decode_state, appearance);
         arch_config_window(ctx, config, show_arch_config, appearance);
         debug_window(ctx, show_debug, frame_history, appearance);
         graphics_window(ctx, show_graphics, frame_history, graphics_state, appearance);
+        keybindings_window(ctx, show_keybindings, &keybindings, appearance);
 
         self.post_update(ctx);
     }
 
     /// Called by the frame work to save state before shutdown.
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        let storage = match &mut self.storage {
+            Some(storage) => storage as &mut dyn eframe::Storage,
+            None => storage,
+        };
         if let Ok(config) = self.config.read() {
             eframe::set_value(storage, CONFIG_KEY, &*config);
+            let object = config.selected_obj.as_ref().map(|obj| obj.name.clone());
+            eframe::set_value(storage, SESSION_KEY, &self.view_state.diff_state.session(object));
         }
+        eframe::set_value(
+            storage,
+            SECTIONS_KEY,
+            &self.view_state.diff_state.symbol_state.section_open,
+        );
         eframe::set_value(storage, APPEARANCE_KEY, &self.appearance);
+        if let Some(storage) = &mut self.storage {
+            eframe::Storage::flush(storage);
+        }
     }
 }
 
 fn create_watcher(
     ctx: egui::Context,
     modified: Arc<AtomicBool>,
+    error: Arc<Mutex<Option<String>>>,
     project_dir: &Path,
-    patterns: GlobSet,
+    patterns: WatchGlobSet,
 ) -> notify::Result<notify::RecommendedWatcher> {
     let base_dir = project_dir.to_owned();
     let mut watcher =
@@ -649,7 +1296,7 @@ fn create_watcher(
                         let Ok(path) = path.strip_prefix(&base_dir) else {
                             continue;
                         };
-                        if patterns.is_match(path) {
+                        if patterns.is_watched(path) {
                             log::info!("File modified: {}", path.display());
                             modified.store(true, Ordering::Relaxed);
                             ctx.request_repaint();
@@ -657,7 +1304,11 @@ fn create_watcher(
                     }
                 }
             }
-            Err(e) => log::error!("watch error: {e:?}"),
+            Err(e) => {
+                log::error!("watch error: {e:?}");
+                *error.lock().unwrap() = Some(format!("{e}"));
+                ctx.request_repaint();
+            }
         })?;
     watcher.watch(project_dir, RecursiveMode::Recursive)?;
     Ok(watcher)
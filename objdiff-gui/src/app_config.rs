@@ -61,6 +61,9 @@ impl ObjectConfigV0 {
             reverse_fn_order: self.reverse_fn_order,
             complete: None,
             scratch: None,
+            build_status: None,
+            make_args: None,
+            symbol_mappings: None,
         }
     }
 }
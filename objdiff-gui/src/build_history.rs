@@ -0,0 +1,79 @@
+use std::{fs::OpenOptions, io::Write, path::Path};
+
+use anyhow::{Context, Result};
+use objdiff_core::{
+    diff::{ObjDiff, SymbolVisibilityFilter},
+    obj::{ObjInfo, ObjSectionKind},
+};
+use time::{format_description::well_known::Rfc3339, OffsetDateTime};
+
+/// A single line in the build history file.
+#[derive(serde::Serialize)]
+struct BuildRecord<'a> {
+    timestamp: String,
+    object: &'a str,
+    fuzzy_match_percent: f32,
+    sections: Vec<SectionRecord<'a>>,
+}
+
+#[derive(serde::Serialize)]
+struct SectionRecord<'a> {
+    name: &'a str,
+    fuzzy_match_percent: f32,
+}
+
+/// Overall match percent of the object's code, weighted by function size.
+fn fuzzy_match_percent(obj: &ObjInfo, diff: &ObjDiff, visibility: SymbolVisibilityFilter) -> f32 {
+    let mut total_code = 0u64;
+    let mut match_percent = 0.0;
+    for (section, section_diff) in obj.sections.iter().zip(&diff.sections) {
+        if section.kind != ObjSectionKind::Code {
+            continue;
+        }
+        for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
+            if symbol.size == 0 || !visibility.includes(symbol) {
+                continue;
+            }
+            match_percent += symbol_diff.match_percent.unwrap_or(0.0) * symbol.size as f32;
+            total_code += symbol.size;
+        }
+    }
+    if total_code == 0 {
+        100.0
+    } else {
+        match_percent / total_code as f32
+    }
+}
+
+/// Appends a JSON line describing a completed build to `path`.
+pub fn append_build_record(
+    path: &Path,
+    object: &str,
+    obj: &ObjInfo,
+    diff: &ObjDiff,
+    visibility: SymbolVisibilityFilter,
+    time: OffsetDateTime,
+) -> Result<()> {
+    let record = BuildRecord {
+        timestamp: time.format(&Rfc3339)?,
+        object,
+        fuzzy_match_percent: fuzzy_match_percent(obj, diff, visibility),
+        sections: obj
+            .sections
+            .iter()
+            .zip(&diff.sections)
+            .map(|(section, section_diff)| SectionRecord {
+                name: &section.name,
+                fuzzy_match_percent: section_diff.match_percent.unwrap_or(0.0),
+            })
+            .collect(),
+    };
+    let mut line = serde_json::to_string(&record)?;
+    line.push('\n');
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    file.write_all(line.as_bytes()).with_context(|| format!("Failed to write {}", path.display()))
+}
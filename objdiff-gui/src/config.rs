@@ -1,8 +1,13 @@
-use std::path::{Component, Path};
+use std::{
+    fs,
+    path::{Component, Path},
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use globset::Glob;
-use objdiff_core::config::{try_project_config, ProjectObject, DEFAULT_WATCH_PATTERNS};
+use objdiff_core::config::{
+    try_project_config, ProjectConfig, ProjectObject, DEFAULT_WATCH_PATTERNS,
+};
 
 use crate::app::AppConfig;
 
@@ -65,29 +70,79 @@ fn build_nodes(
 }
 
 pub fn load_project_config(config: &mut AppConfig) -> Result<()> {
-    let Some(project_dir) = &config.project_dir else {
+    let Some(project_dir) = config.project_dir.clone() else {
         return Ok(());
     };
-    if let Some((result, info)) = try_project_config(project_dir) {
-        let project_config = result?;
-        config.custom_make = project_config.custom_make;
-        config.custom_args = project_config.custom_args;
-        config.target_obj_dir = project_config.target_dir.map(|p| project_dir.join(p));
-        config.base_obj_dir = project_config.base_dir.map(|p| project_dir.join(p));
-        config.build_base = project_config.build_base;
-        config.build_target = project_config.build_target;
-        config.watch_patterns = project_config.watch_patterns.unwrap_or_else(|| {
-            DEFAULT_WATCH_PATTERNS.iter().map(|s| Glob::new(s).unwrap()).collect()
-        });
-        config.watcher_change = true;
-        config.objects = project_config.objects;
-        config.object_nodes = build_nodes(
-            &config.objects,
-            project_dir,
-            config.target_obj_dir.as_deref(),
-            config.base_obj_dir.as_deref(),
-        );
+    if let Some((result, info)) = try_project_config(&project_dir) {
+        // Record the new timestamp even if the config fails to parse, keeping the previous
+        // config loaded until the file changes again
         config.project_config_info = Some(info);
+        let project_config = result?;
+        config.object_problems = project_config.validate(&project_dir);
+        apply_project_config(config, &project_dir, project_config);
     }
     Ok(())
 }
+
+/// Applies a project config fetched by [`start_fetch_config`], unless the user has since
+/// opened a different project.
+///
+/// [`start_fetch_config`]: crate::jobs::fetch_config::start_fetch_config
+pub fn load_remote_project_config(
+    config: &mut AppConfig,
+    url: &str,
+    project_config: ProjectConfig,
+) -> Result<bool> {
+    let Some(project_dir) = config.project_dir.clone() else {
+        return Ok(false);
+    };
+    if config.project_url.as_deref() != Some(url) {
+        return Ok(false);
+    }
+    fs::create_dir_all(&project_dir)
+        .with_context(|| format!("Failed to create directory {}", project_dir.display()))?;
+    apply_project_config(config, &project_dir, project_config);
+    // Remote objects are downloaded rather than built
+    config.build_base = false;
+    config.build_target = false;
+    Ok(true)
+}
+
+fn apply_project_config(config: &mut AppConfig, project_dir: &Path, project_config: ProjectConfig) {
+    // Only apply the project's demangler when it changes, so a reload keeps the one chosen in the UI
+    let demangler = project_config.demangler.is_some().then(|| project_config.demangler());
+    if demangler != config.project_demangler {
+        if let Some(demangler) = demangler {
+            config.diff_obj_config.demangler = demangler;
+        }
+        config.project_demangler = demangler;
+    }
+    config.custom_make = project_config.custom_make;
+    config.custom_args = project_config.custom_args;
+    config.target_obj_dir = project_config.target_dir.map(|p| project_dir.join(p));
+    config.base_obj_dir = project_config.base_dir.map(|p| project_dir.join(p));
+    config.build_base = project_config.build_base;
+    config.build_target = project_config.build_target;
+    config.build_parallel = project_config.build_parallel;
+    config.watch_patterns = project_config
+        .watch_patterns
+        .unwrap_or_else(|| DEFAULT_WATCH_PATTERNS.iter().map(|s| Glob::new(s).unwrap()).collect());
+    config.diff_obj_config.custom_relocations =
+        project_config.custom_relocations.unwrap_or_default();
+    config.diff_obj_config.symbol_name_rules = project_config.symbol_name_rules.unwrap_or_default();
+    config.diff_obj_config.instruction_rules = project_config.instruction_rules.unwrap_or_default();
+    config.diff_obj_config.debug_search_paths = project_config
+        .debug_search_paths
+        .unwrap_or_default()
+        .iter()
+        .map(|p| project_dir.join(p))
+        .collect();
+    config.watcher_change = true;
+    config.objects = project_config.objects;
+    config.object_nodes = build_nodes(
+        &config.objects,
+        project_dir,
+        config.target_obj_dir.as_deref(),
+        config.base_obj_dir.as_deref(),
+    );
+}
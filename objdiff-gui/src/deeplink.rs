@@ -0,0 +1,128 @@
+use std::{fmt::Write, path::PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+
+pub const DEEP_LINK_SCHEME: &str = "objdiff://";
+
+/// A shareable link to a project, optionally selecting an object and symbol.
+///
+/// Format: `objdiff://open?project=<path>&object=<name>&symbol=<name>`
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct DeepLink {
+    pub project_dir: PathBuf,
+    pub object: Option<String>,
+    pub symbol: Option<String>,
+}
+
+impl DeepLink {
+    pub fn to_url(&self) -> String {
+        let mut url = format!(
+            "{DEEP_LINK_SCHEME}open?project={}",
+            percent_encode(&self.project_dir.to_string_lossy())
+        );
+        if let Some(object) = &self.object {
+            write!(url, "&object={}", percent_encode(object)).unwrap();
+        }
+        if let Some(symbol) = &self.symbol {
+            write!(url, "&symbol={}", percent_encode(symbol)).unwrap();
+        }
+        url
+    }
+
+    pub fn parse(url: &str) -> Result<Self> {
+        let Some(rest) = url.strip_prefix(DEEP_LINK_SCHEME) else {
+            bail!("Not an objdiff link: {url}");
+        };
+        // Some platforms add a slash after the host, as in `objdiff://open/?project=...`
+        let query = match rest.split_once('?') {
+            Some((path, query)) if path.trim_end_matches('/') == "open" => query,
+            _ => bail!("Unsupported objdiff link: {url}"),
+        };
+        let mut project_dir = None;
+        let mut object = None;
+        let mut symbol = None;
+        for pair in query.split('&').filter(|s| !s.is_empty()) {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            let value = percent_decode(value)?;
+            match key {
+                "project" => project_dir = Some(PathBuf::from(value)),
+                "object" => object = Some(value),
+                "symbol" => symbol = Some(value),
+                _ => log::warn!("Ignoring unknown link parameter '{key}'"),
+            }
+        }
+        let project_dir = project_dir.ok_or_else(|| anyhow!("Link is missing a project"))?;
+        Ok(Self { project_dir, object, symbol })
+    }
+}
+
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b'~' | b'/') {
+            out.push(b as char);
+        } else {
+            write!(out, "%{b:02X}").unwrap();
+        }
+    }
+    out
+}
+
+fn percent_decode(s: &str) -> Result<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s.get(i + 1..i + 3).ok_or_else(|| anyhow!("Invalid escape in '{s}'"))?;
+            out.push(u8::from_str_radix(hex, 16).map_err(|_| anyhow!("Invalid escape in '{s}'"))?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(String::from_utf8(out)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let links = [
+            DeepLink { project_dir: PathBuf::from("/home/user/game"), object: None, symbol: None },
+            DeepLink {
+                project_dir: PathBuf::from("/home/user/my game"),
+                object: Some("main/100%/a&b=c".to_string()),
+                symbol: Some("operator+=(Vec&, const Vec&)".to_string()),
+            },
+            DeepLink {
+                project_dir: PathBuf::from("C:\\Users\\ゲーム"),
+                object: Some("src/".to_string()),
+                symbol: Some("Café::ñame?".to_string()),
+            },
+        ];
+        for link in links {
+            let url = link.to_url();
+            assert!(url.is_ascii(), "{url}");
+            assert_eq!(DeepLink::parse(&url).unwrap(), link, "{url}");
+        }
+    }
+
+    #[test]
+    fn parse_links() {
+        let link = DeepLink::parse("objdiff://open/?symbol=foo%20bar&project=%2Fa%20b").unwrap();
+        assert_eq!(link, DeepLink {
+            project_dir: PathBuf::from("/a b"),
+            object: None,
+            symbol: Some("foo bar".to_string()),
+        });
+        assert!(DeepLink::parse("https://open?project=a").is_err());
+        assert!(DeepLink::parse("objdiff://close?project=a").is_err());
+        assert!(DeepLink::parse("objdiff://open?object=a").is_err());
+        assert!(DeepLink::parse("objdiff://open?project=a%2").is_err());
+        assert!(DeepLink::parse("objdiff://open?project=%FF").is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt::Write;
+
+use egui::Color32;
+use objdiff_core::{
+    arch::ObjArch,
+    diff::{
+        display::{display_diff, AddressMode, DiffText},
+        ObjInsDiff, ObjInsDiffKind, ObjSymbolDiff,
+    },
+    obj::{ObjSection, ObjSymbol},
+};
+
+use crate::views::{
+    appearance::Appearance,
+    function_diff::{inline_scope_marker, FunctionViewState},
+    symbol_diff::match_color_for_symbol,
+};
+
+/// One side of an exported function diff.
+pub struct HtmlDiffSide<'a> {
+    pub arch: &'a dyn ObjArch,
+    pub section: Option<&'a ObjSection>,
+    pub symbol: &'a ObjSymbol,
+    pub diff: &'a ObjSymbolDiff,
+    /// Address that instruction addresses are displayed relative to
+    pub base_addr: u64,
+    /// How branch targets are formatted
+    pub address_mode: AddressMode,
+}
+
+fn css_color(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// Resolves egui's additive colors, which are blended onto what's below them.
+fn over_background(color: Color32, background: Color32) -> Color32 {
+    if color.a() != 0 {
+        return color;
+    }
+    Color32::from_rgb(
+        background.r().saturating_add(color.r()),
+        background.g().saturating_add(color.g()),
+        background.b().saturating_add(color.b()),
+    )
+}
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Renders a function diff as a standalone side-by-side HTML page, colored like the
+/// function diff view and with the annotations it shows.
+pub fn function_diff_html(
+    left: Option<HtmlDiffSide<'_>>,
+    right: Option<HtmlDiffSide<'_>>,
+    view_state: &FunctionViewState,
+    appearance: &Appearance,
+) -> String {
+    let name = |side: &Option<HtmlDiffSide<'_>>| {
+        side.as_ref()
+            .map(|s| escape(s.symbol.demangled_name.as_ref().unwrap_or(&s.symbol.name)))
+            .unwrap_or_default()
+    };
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+    writeln!(out, "<title>{}</title>", name(&left)).unwrap();
+    out.push_str("<style>\n");
+    writeln!(
+        out,
+        "body {{ background: {}; color: {}; font-family: monospace; }}",
+        css_color(appearance.background_color),
+        css_color(appearance.text_color)
+    )
+    .unwrap();
+    // Rows that differ, like the function diff view's row fill
+    let changed = over_background(appearance.faint_background_color, appearance.background_color);
+    writeln!(out, "td.changed {{ background: {}; }}", css_color(changed)).unwrap();
+    out.push_str("table { border-collapse: collapse; }\n");
+    out.push_str("td { white-space: pre; padding: 0 2em 0 0; vertical-align: top; }\n");
+    out.push_str("th { text-align: left; }\n");
+    for (class, color) in [
+        ("line", appearance.deemphasized_text_color),
+        ("annotation", appearance.deemphasized_text_color),
+        ("symbol", appearance.emphasized_text_color),
+        ("replace", appearance.replace_color),
+        ("delete", appearance.delete_color),
+        ("insert", appearance.insert_color),
+        ("moved", appearance.moved_color),
+    ] {
+        writeln!(out, ".{class} {{ color: {}; }}", css_color(color)).unwrap();
+    }
+    for (i, &color) in appearance.diff_colors.iter().enumerate() {
+        writeln!(out, ".d{i} {{ color: {}; }}", css_color(color)).unwrap();
+    }
+    out.push_str("</style>\n</head>\n<body>\n<table>\n");
+    out.push_str("<tr>");
+    for side in [&left, &right] {
+        write!(out, "<th>{}", name(side)).unwrap();
+        if let Some(match_percent) = side.as_ref().and_then(|s| s.diff.match_percent) {
+            write!(
+                out,
+                " <span style=\"color: {}\">({match_percent:.0}%)</span>",
+                css_color(match_color_for_symbol(match_percent, appearance))
+            )
+            .unwrap();
+        }
+        out.push_str("</th>");
+    }
+    out.push_str("</tr>\n");
+
+    let rows = |side: &Option<HtmlDiffSide<'_>>| {
+        side.as_ref().map(|s| s.diff.instructions.len()).unwrap_or_default()
+    };
+    for i in 0..rows(&left).max(rows(&right)) {
+        out.push_str("<tr>");
+        for side in [&left, &right] {
+            let ins_diff =
+                side.as_ref().and_then(|side| Some((side, side.diff.instructions.get(i)?)));
+            match ins_diff {
+                Some((_, ins_diff)) if ins_diff.kind != ObjInsDiffKind::None => {
+                    out.push_str("<td class=\"changed\">")
+                }
+                _ => out.push_str("<td>"),
+            }
+            if let Some((side, ins_diff)) = ins_diff {
+                write_instruction(&mut out, side, ins_diff, appearance);
+                write_annotations(&mut out, side, ins_diff, view_state);
+            }
+            out.push_str("</td>");
+        }
+        out.push_str("</tr>\n");
+    }
+    out.push_str("</table>\n</body>\n</html>\n");
+    out
+}
+
+/// Writes the cycle cost and inlined function scopes, when the view shows them.
+fn write_annotations(
+    out: &mut String,
+    side: &HtmlDiffSide<'_>,
+    ins_diff: &ObjInsDiff,
+    view_state: &FunctionViewState,
+) {
+    let Some(ins) = &ins_diff.ins else {
+        return;
+    };
+    let mut annotations = vec![];
+    if view_state.show_cycle_costs {
+        if let Some(cost) = side.arch.instruction_cost(ins) {
+            annotations.push(format!("; {cost}c"));
+        }
+    }
+    if view_state.show_inline_scopes {
+        annotations
+            .extend(side.section.and_then(|section| inline_scope_marker(section, ins.address)));
+    }
+    for annotation in annotations {
+        write!(out, "  <span class=\"annotation\">{}</span>", escape(&annotation)).unwrap();
+    }
+}
+
+fn write_instruction(
+    out: &mut String,
+    side: &HtmlDiffSide<'_>,
+    ins_diff: &ObjInsDiff,
+    appearance: &Appearance,
+) {
+    let row_class = match ins_diff.kind {
+        ObjInsDiffKind::None | ObjInsDiffKind::OpMismatch | ObjInsDiffKind::ArgMismatch => None,
+        ObjInsDiffKind::Replace => Some("replace".to_string()),
+        ObjInsDiffKind::Delete => Some("delete".to_string()),
+        ObjInsDiffKind::Insert => Some("insert".to_string()),
+        ObjInsDiffKind::Moved => Some("moved".to_string()),
+    };
+    let diff_class = |idx: usize| Some(format!("d{}", idx % appearance.diff_colors.len().max(1)));
+    display_diff(ins_diff, side.base_addr, |text| {
+        let mut class = row_class.clone();
+        let mut pad_to: usize = 0;
+        let text = match text {
+            DiffText::Basic(s) => s.to_string(),
+            DiffText::BasicColor(s, idx) => {
+                class = diff_class(idx);
+                s.to_string()
+            }
+            DiffText::Line(num) => {
+                class = Some("line".to_string());
+                pad_to = 5;
+                num.to_string()
+            }
+            DiffText::Address(addr) => {
+                pad_to = 5;
+                format!("{:x}:", addr)
+            }
+            DiffText::Opcode(mnemonic, _op) => {
+                if ins_diff.kind == ObjInsDiffKind::OpMismatch {
+                    class = Some("replace".to_string());
+                }
+                pad_to = 8;
+                mnemonic.to_string()
+            }
+            DiffText::Argument(arg, diff) => {
+                if let Some(diff) = diff {
+                    class = diff_class(diff.idx);
+                }
+                arg.to_string()
+            }
+            DiffText::BranchDest(addr, diff) => {
+                if let Some(diff) = diff {
+                    class = diff_class(diff.idx);
+                }
+                side.address_mode.branch_dest_text(side.symbol, addr)
+            }
+            DiffText::Symbol(sym, diff) => {
+                class = match diff {
+                    Some(diff) => diff_class(diff.idx),
+                    None => Some("symbol".to_string()),
+                };
+                sym.demangled_name.as_ref().unwrap_or(&sym.name).clone()
+            }
+            DiffText::Spacing(n) => " ".repeat(n),
+            DiffText::Eol => return Ok(()),
+        };
+        let padding = " ".repeat(pad_to.saturating_sub(text.len()));
+        match class {
+            Some(class) => write!(out, "<span class=\"{class}\">{}</span>", escape(&text))?,
+            None => out.push_str(&escape(&text)),
+        }
+        out.push_str(&padding);
+        Ok::<_, std::fmt::Error>(())
+    })
+    .unwrap();
+}
@@ -1,14 +1,18 @@
-use std::{fs, path::PathBuf, sync::mpsc::Receiver};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc::Receiver,
+};
 
 use anyhow::{anyhow, bail, Context, Result};
 use const_format::formatcp;
+use filetime::FileTime;
+use objdiff_core::build::{run_make, BuildConfig, BuildStatus};
 
 use crate::{
     app::AppConfig,
-    jobs::{
-        objdiff::{run_make, BuildConfig, BuildStatus},
-        start_job, update_status, Job, JobContext, JobResult, JobState,
-    },
+    jobs::{objdiff::build_config, start_job, update_status, Job, JobContext, JobResult, JobState},
 };
 
 #[derive(Debug, Clone)]
@@ -16,6 +20,10 @@ pub struct CreateScratchConfig {
     pub build_config: BuildConfig,
     pub context_path: Option<PathBuf>,
     pub build_context: bool,
+    /// Command printing the context, used instead of `make <context_path>`
+    pub context_command: Option<Vec<String>>,
+    /// The object's source file, which `context_path` is relative to
+    pub source_path: Option<PathBuf>,
 
     // Scratch fields
     pub compiler: String,
@@ -23,6 +31,8 @@ pub struct CreateScratchConfig {
     pub compiler_flags: String,
     pub function_name: String,
     pub target_obj: PathBuf,
+    /// Name of the object the function belongs to
+    pub unit_name: String,
 }
 
 impl CreateScratchConfig {
@@ -36,29 +46,54 @@ impl CreateScratchConfig {
         let Some(scratch_config) = &selected_obj.scratch else {
             bail!("No scratch configuration for {}", selected_obj.name);
         };
+        let source_path = config
+            .objects
+            .iter()
+            .find(|o| o.name() == selected_obj.name)
+            .and_then(|o| Some(config.project_dir.as_ref()?.join(o.source_path.as_ref()?)));
         Ok(Self {
-            build_config: BuildConfig::from_config(config),
+            build_config: build_config(config),
             context_path: scratch_config.ctx_path.clone(),
             build_context: scratch_config.build_ctx,
+            context_command: scratch_config.ctx_command.clone(),
+            source_path,
             compiler: scratch_config.compiler.clone().unwrap_or_default(),
             platform: scratch_config.platform.clone().unwrap_or_default(),
             compiler_flags: scratch_config.c_flags.clone().unwrap_or_default(),
             function_name,
             target_obj: target_path.to_path_buf(),
+            unit_name: selected_obj.name.clone(),
         })
     }
 
-    pub fn is_available(config: &AppConfig) -> bool {
+    /// Why a scratch can't be created for the selected object, if it can't.
+    pub fn missing_requirement(config: &AppConfig) -> Option<&'static str> {
         let Some(selected_obj) = &config.selected_obj else {
-            return false;
+            return Some("No object selected");
+        };
+        if selected_obj.target_path.is_none() {
+            return Some("The object has no target path");
+        }
+        let Some(scratch) = &selected_obj.scratch else {
+            return Some("The object has no scratch configuration");
         };
-        selected_obj.target_path.is_some() && selected_obj.scratch.is_some()
+        let is_set = |value: &Option<String>| value.as_ref().is_some_and(|v| !v.is_empty());
+        if !is_set(&scratch.platform) {
+            return Some("The scratch configuration needs a platform, e.g. \"gc_wii\"");
+        }
+        if !is_set(&scratch.compiler) {
+            return Some("The scratch configuration needs a compiler, e.g. \"mwcc_247_92\"");
+        }
+        None
     }
 }
 
 #[derive(Default, Debug, Clone)]
 pub struct CreateScratchResult {
     pub scratch_url: String,
+    pub scratch_slug: String,
+    pub function_name: String,
+    pub unit_name: String,
 }
 
 #[derive(Debug, Default, Clone, serde::Deserialize)]
@@ -67,33 +102,149 @@ struct CreateScratchResponse {
     pub claim_token: String,
 }
 
-const API_HOST: &str = "https://decomp.me";
+/// Resolves `ctx_path` relative to the object's source directory, falling back to the
+/// project directory.
+fn resolve_context_path(
+    ctx_path: &Path,
+    source_dir: Option<&Path>,
+    project_dir: &Path,
+) -> Result<PathBuf> {
+    let candidates: Vec<PathBuf> = source_dir
+        .map(|dir| dir.join(ctx_path))
+        .into_iter()
+        .chain(std::iter::once(project_dir.join(ctx_path)))
+        .collect();
+    if let Some(path) = candidates.iter().find(|p| p.is_file()) {
+        return Ok(path.clone());
+    }
+    let tried = candidates.iter().map(|p| format!("\n  {}", p.display())).collect::<String>();
+    bail!("Context file {} not found, tried:{tried}", ctx_path.display())
+}
 
-fn run_create_scratch(
-    status: &JobContext,
-    cancel: Receiver<()>,
-    config: CreateScratchConfig,
-) -> Result<Box<CreateScratchResult>> {
-    let project_dir =
-        config.build_config.project_dir.as_ref().ok_or_else(|| anyhow!("Missing project dir"))?;
+/// Files the context command reads: the source file, the headers it includes with quotes
+/// (searched next to the including file and in the command's `-I` directories), and any
+/// existing files among the command's arguments.
+fn context_inputs(
+    command: &[String],
+    source_path: Option<&Path>,
+    project_dir: &Path,
+) -> Vec<PathBuf> {
+    let include_dirs: Vec<PathBuf> = command
+        .iter()
+        .filter_map(|arg| arg.strip_prefix("-I"))
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| project_dir.join(dir))
+        .collect();
+    let mut inputs: Vec<PathBuf> = command
+        .iter()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .map(|arg| project_dir.join(arg))
+        .filter(|path| path.is_file())
+        .collect();
+    let mut pending: Vec<PathBuf> = source_path.map(Path::to_path_buf).into_iter().collect();
+    while let Some(path) = pending.pop() {
+        if inputs.contains(&path) {
+            continue;
+        }
+        let Ok(text) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for line in text.lines() {
+            let Some(rest) = line.trim_start().strip_prefix('#') else {
+                continue;
+            };
+            let Some(rest) = rest.trim_start().strip_prefix("include") else {
+                continue;
+            };
+            let Some(name) = rest.trim().strip_prefix('"').and_then(|s| s.split('"').next()) else {
+                continue;
+            };
+            let header = std::iter::once(dir)
+                .chain(include_dirs.iter().map(PathBuf::as_path))
+                .map(|dir| dir.join(name))
+                .find(|p| p.is_file());
+            pending.extend(header);
+        }
+        inputs.push(path);
+    }
+    inputs
+}
+
+/// Whether the context is missing or older than any of its inputs.
+fn context_stale(context_path: Option<&Path>, inputs: &[PathBuf]) -> bool {
+    let modified =
+        |path: &Path| fs::metadata(path).ok().map(|m| FileTime::from_last_modification_time(&m));
+    let Some(context_time) = context_path.and_then(modified) else {
+        return true;
+    };
+    inputs.iter().any(|input| modified(input).is_some_and(|time| time > context_time))
+}
+
+fn run_context_command(command: &[String], project_dir: &Path, output_path: &Path) -> Result<()> {
+    let Some((program, args)) = command.split_first() else {
+        bail!("Empty context command");
+    };
+    let mut command = Command::new(program);
+    command.current_dir(project_dir).args(args);
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(winapi::um::winbase::CREATE_NO_WINDOW);
+    }
+    let output =
+        command.output().with_context(|| format!("Failed to run context command {program}"))?;
+    if !output.status.success() {
+        bail!("Failed to build context:\n{}", String::from_utf8_lossy(&output.stderr));
+    }
+    fs::write(output_path, &output.stdout)
+        .with_context(|| format!("Failed to write {}", output_path.display()))
+}
 
-    let mut context = None;
-    if let Some(context_path) = &config.context_path {
-        if config.build_context {
-            update_status(status, "Building context".to_string(), 0, 2, &cancel)?;
-            match run_make(&config.build_config, context_path) {
+/// Reads the scratch context, first generating it if `build_context` is set. make tracks the
+/// context's dependencies itself, while `context_command` only runs if the context is missing
+/// or older than one of its inputs, see [`context_inputs`].
+pub(crate) fn load_context(config: &CreateScratchConfig, project_dir: &Path) -> Result<String> {
+    let Some(context_path) = &config.context_path else {
+        return Ok(String::new());
+    };
+    let source_dir = config.source_path.as_deref().and_then(Path::parent);
+    let existing = resolve_context_path(context_path, source_dir, project_dir).ok();
+    if config.build_context {
+        if let Some(command) = &config.context_command {
+            let inputs = context_inputs(command, config.source_path.as_deref(), project_dir);
+            if context_stale(existing.as_deref(), &inputs) {
+                let output_path =
+                    existing.clone().unwrap_or_else(|| project_dir.join(context_path));
+                run_context_command(command, project_dir, &output_path)?;
+            }
+        } else {
+            match run_make(&config.build_config, context_path, &[], None) {
                 BuildStatus { success: true, .. } => {}
                 BuildStatus { success: false, stdout, stderr, .. } => {
                     bail!("Failed to build context:\n{stdout}\n{stderr}")
                 }
             }
         }
-        let context_path = project_dir.join(context_path);
-        context = Some(
-            fs::read_to_string(&context_path)
-                .map_err(|e| anyhow!("Failed to read {}: {}", context_path.display(), e))?,
-        );
     }
+    let context_path = resolve_context_path(context_path, source_dir, project_dir)?;
+    fs::read_to_string(&context_path)
+        .map_err(|e| anyhow!("Failed to read {}: {}", context_path.display(), e))
+}
+
+pub(crate) const API_HOST: &str = "https://decomp.me";
+
+fn run_create_scratch(
+    status: &JobContext,
+    cancel: Receiver<()>,
+    config: CreateScratchConfig,
+) -> Result<Box<CreateScratchResult>> {
+    let project_dir =
+        config.build_config.project_dir.as_ref().ok_or_else(|| anyhow!("Missing project dir"))?;
+
+    update_status(status, "Loading context".to_string(), 0, 2, &cancel)?;
+    let context = load_context(&config, project_dir)?;
 
     update_status(status, "Creating scratch".to_string(), 1, 2, &cancel)?;
     let diff_flags = [format!("--disassemble={}", config.function_name)];
@@ -107,7 +258,7 @@ fn run_create_scratch(
         .text("compiler_flags", config.compiler_flags.clone())
         .text("diff_label", config.function_name.clone())
         .text("diff_flags", diff_flags)
-        .text("context", context.unwrap_or_default())
+        .text("context", context)
         .text("source_code", "// Move related code from Context tab to here")
         .part("target_obj", file);
     let client = reqwest::blocking::Client::new();
@@ -123,7 +274,12 @@ fn run_create_scratch(
     let scratch_url = format!("{API_HOST}/scratch/{}/claim?token={}", body.slug, body.claim_token);
 
     update_status(status, "Complete".to_string(), 2, 2, &cancel)?;
-    Ok(Box::from(CreateScratchResult { scratch_url }))
+    Ok(Box::from(CreateScratchResult {
+        scratch_url,
+        scratch_slug: body.slug,
+        function_name: config.function_name,
+        unit_name: config.unit_name,
+    }))
 }
 
 pub fn start_create_scratch(ctx: &egui::Context, config: CreateScratchConfig) -> JobState {
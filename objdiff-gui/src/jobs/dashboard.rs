@@ -0,0 +1,200 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    sync::mpsc::Receiver,
+};
+
+use anyhow::{Context, Result};
+use objdiff_core::{
+    config::ProjectObject,
+    diff::{diff_objs, DiffObjConfig, ObjDiff},
+    obj::{read, ObjInfo, ObjSectionKind},
+};
+
+use crate::{
+    app::AppConfig,
+    jobs::{start_job, update_status, Job, JobContext, JobResult, JobState},
+};
+
+#[derive(Clone)]
+pub struct DashboardConfig {
+    pub project_dir: PathBuf,
+    pub target_obj_dir: Option<PathBuf>,
+    pub base_obj_dir: Option<PathBuf>,
+    pub objects: Vec<ProjectObject>,
+    pub diff_obj_config: DiffObjConfig,
+}
+
+impl DashboardConfig {
+    pub(crate) fn from_config(config: &AppConfig) -> Option<Self> {
+        Some(Self {
+            project_dir: config.project_dir.clone()?,
+            target_obj_dir: config.target_obj_dir.clone(),
+            base_obj_dir: config.base_obj_dir.clone(),
+            objects: config.objects.clone(),
+            // Every function's match percentage is needed up front
+            diff_obj_config: DiffObjConfig {
+                lazy_code_diff: false,
+                ..config.diff_obj_config.clone()
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DashboardEntry {
+    pub object: String,
+    pub symbol_name: String,
+    pub demangled_name: Option<String>,
+    pub size: u64,
+    pub match_percent: f32,
+}
+
+impl DashboardEntry {
+    /// Bytes of the function left to match, used to rank entries.
+    pub fn remaining_bytes(&self) -> f32 { self.size as f32 * (1.0 - self.match_percent / 100.0) }
+}
+
+/// Matched and total code bytes of one unit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CodeProgress {
+    pub matched: f64,
+    pub total: u64,
+}
+
+impl CodeProgress {
+    pub fn new(obj: &ObjInfo, diff: &ObjDiff) -> Self {
+        let mut progress = Self::default();
+        for (section, section_diff) in obj.sections.iter().zip(&diff.sections) {
+            if section.kind != ObjSectionKind::Code {
+                continue;
+            }
+            for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
+                let match_percent = symbol_diff.match_percent.unwrap_or(0.0);
+                progress.total += symbol.size;
+                progress.matched += symbol.size as f64 * match_percent as f64 / 100.0;
+            }
+        }
+        progress
+    }
+}
+
+/// Code matching progress across the project's units, weighted by function size.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectProgress {
+    pub units: BTreeMap<String, CodeProgress>,
+    /// Units that failed to build or load, left out of the totals
+    pub failed: BTreeSet<String>,
+}
+
+impl ProjectProgress {
+    /// Records a unit's progress, or `None` if it failed.
+    pub fn update(&mut self, unit: &str, progress: Option<CodeProgress>) {
+        match progress {
+            Some(progress) => {
+                self.failed.remove(unit);
+                self.units.insert(unit.to_string(), progress);
+            }
+            None => {
+                self.units.remove(unit);
+                self.failed.insert(unit.to_string());
+            }
+        }
+    }
+
+    pub fn match_percent(&self) -> Option<f32> {
+        let total: u64 = self.units.values().map(|p| p.total).sum();
+        let matched: f64 = self.units.values().map(|p| p.matched).sum();
+        (total > 0).then(|| (matched / total as f64 * 100.0) as f32)
+    }
+}
+
+#[derive(Default)]
+pub struct DashboardResult {
+    pub entries: Vec<DashboardEntry>,
+    pub progress: ProjectProgress,
+}
+
+/// Sorts entries by remaining bytes, largest first.
+pub fn rank_entries(entries: &mut [DashboardEntry]) {
+    entries.sort_by(|a, b| b.remaining_bytes().total_cmp(&a.remaining_bytes()));
+}
+
+fn object_entries(
+    object: &mut ProjectObject,
+    config: &DashboardConfig,
+    entries: &mut Vec<DashboardEntry>,
+    progress: &mut ProjectProgress,
+) -> Result<()> {
+    object.resolve_paths(
+        &config.project_dir,
+        config.target_obj_dir.as_deref(),
+        config.base_obj_dir.as_deref(),
+    );
+    let (Some(target_path), Some(base_path)) = (&object.target_path, &object.base_path) else {
+        return Ok(());
+    };
+    let target = read::read(target_path, &config.diff_obj_config)
+        .with_context(|| format!("Failed to open {}", target_path.display()))?;
+    let base = read::read(base_path, &config.diff_obj_config)
+        .with_context(|| format!("Failed to open {}", base_path.display()))?;
+    let diff_config = match &object.symbol_mappings {
+        Some(mappings) => Cow::Owned(DiffObjConfig {
+            symbol_mappings: mappings.clone(),
+            ..config.diff_obj_config.clone()
+        }),
+        None => Cow::Borrowed(&config.diff_obj_config),
+    };
+    let result = diff_objs(&diff_config, Some(&target), Some(&base), None)?;
+    let Some(obj_diff) = &result.left else {
+        return Ok(());
+    };
+    progress.update(object.name(), Some(CodeProgress::new(&target, obj_diff)));
+    for (section, section_diff) in target.sections.iter().zip(&obj_diff.sections) {
+        if section.kind != ObjSectionKind::Code {
+            continue;
+        }
+        for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
+            let match_percent = symbol_diff.match_percent.unwrap_or(0.0);
+            if symbol.size == 0 || match_percent >= 100.0 {
+                continue;
+            }
+            entries.push(DashboardEntry {
+                object: object.name().to_string(),
+                symbol_name: symbol.name.clone(),
+                demangled_name: symbol.demangled_name.clone(),
+                size: symbol.size,
+                match_percent,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn run_dashboard(
+    status: &JobContext,
+    cancel: Receiver<()>,
+    mut config: DashboardConfig,
+) -> Result<Box<DashboardResult>> {
+    let mut objects = std::mem::take(&mut config.objects);
+    let total = objects.len() as u32;
+    let mut entries = vec![];
+    let mut progress = ProjectProgress::default();
+    for (i, object) in objects.iter_mut().enumerate() {
+        update_status(status, format!("Processing {}", object.name()), i as u32, total, &cancel)?;
+        if let Err(e) = object_entries(object, &config, &mut entries, &mut progress) {
+            log::warn!("Skipping {}: {e:?}", object.name());
+            progress.update(object.name(), None);
+        }
+    }
+    rank_entries(&mut entries);
+    update_status(status, "Complete".to_string(), total, total, &cancel)?;
+    Ok(Box::new(DashboardResult { entries, progress }))
+}
+
+pub fn start_dashboard(ctx: &egui::Context, config: DashboardConfig) -> JobState {
+    start_job(ctx, "Unmatched functions", Job::Dashboard, move |context, cancel| {
+        run_dashboard(&context, cancel, config).map(|result| JobResult::Dashboard(Some(result)))
+    })
+}
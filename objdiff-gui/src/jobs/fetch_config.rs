@@ -0,0 +1,31 @@
+use std::sync::mpsc::Receiver;
+
+use anyhow::Result;
+use objdiff_core::config::ProjectConfig;
+
+use crate::{
+    jobs::{start_job, update_status, Job, JobContext, JobResult, JobState},
+    remote::fetch_project_config,
+};
+
+pub struct FetchConfigResult {
+    pub url: String,
+    pub config: ProjectConfig,
+}
+
+fn run_fetch_config(
+    context: &JobContext,
+    cancel: Receiver<()>,
+    url: String,
+) -> Result<Box<FetchConfigResult>> {
+    update_status(context, format!("Fetching {url}"), 0, 1, &cancel)?;
+    let config = fetch_project_config(&url)?;
+    update_status(context, "Complete".to_string(), 1, 1, &cancel)?;
+    Ok(Box::new(FetchConfigResult { url, config }))
+}
+
+pub fn start_fetch_config(ctx: &egui::Context, url: String) -> JobState {
+    start_job(ctx, "Fetch project config", Job::FetchConfig, move |context, cancel| {
+        run_fetch_config(&context, cancel, url).map(|result| JobResult::FetchConfig(Some(result)))
+    })
+}
@@ -0,0 +1,113 @@
+use std::{fs, path::PathBuf, sync::mpsc::Receiver};
+
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use objdiff_core::{
+    build::BuildStatus,
+    diff::{diff_objs, DiffObjConfig},
+    obj::read,
+};
+use reqwest::StatusCode;
+use time::OffsetDateTime;
+
+use crate::{
+    app::AppConfig,
+    jobs::{
+        create_scratch::API_HOST, objdiff::ObjDiffResult, start_job, update_status, Job,
+        JobContext, JobResult, JobState,
+    },
+};
+
+#[derive(Debug, Clone)]
+pub struct FetchScratchConfig {
+    pub slug: String,
+    pub target_path: PathBuf,
+    pub diff_obj_config: DiffObjConfig,
+}
+
+impl FetchScratchConfig {
+    pub(crate) fn from_config(config: &AppConfig, slug: String) -> Result<Self> {
+        let Some(selected_obj) = &config.selected_obj else {
+            bail!("No object selected");
+        };
+        let Some(target_path) = &selected_obj.target_path else {
+            bail!("No target path for {}", selected_obj.name);
+        };
+        Ok(Self {
+            slug,
+            target_path: target_path.clone(),
+            diff_obj_config: config.diff_obj_config.clone(),
+        })
+    }
+}
+
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+struct CompileResponse {
+    #[serde(default)]
+    pub success: bool,
+    #[serde(default)]
+    pub compiler_output: String,
+    /// Base64-encoded object compiled from the scratch's current source
+    #[serde(default)]
+    pub right_object: Option<String>,
+}
+
+fn run_fetch_scratch(
+    status: &JobContext,
+    cancel: Receiver<()>,
+    config: FetchScratchConfig,
+) -> Result<Box<ObjDiffResult>> {
+    update_status(status, format!("Compiling scratch {}", config.slug), 0, 3, &cancel)?;
+    let client = reqwest::blocking::Client::new();
+    let response = client
+        .post(format!("{API_HOST}/api/scratch/{}/compile", config.slug))
+        .json(&serde_json::json!({}))
+        .send()
+        .map_err(|e| anyhow!("Failed to send request: {}", e))?;
+    match response.status() {
+        s if s.is_success() => {}
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            bail!("decomp.me denied access to scratch {}", config.slug)
+        }
+        StatusCode::NOT_FOUND => bail!("Scratch {} not found on decomp.me", config.slug),
+        StatusCode::TOO_MANY_REQUESTS => {
+            bail!("decomp.me rate limit reached, please try again later")
+        }
+        s => bail!("Failed to compile scratch ({s}): {}", response.text()?),
+    }
+    let body: CompileResponse = response.json().context("Failed to parse response")?;
+    if !body.success {
+        bail!("Scratch failed to compile:\n{}", body.compiler_output);
+    }
+    let object = body.right_object.ok_or_else(|| anyhow!("Response is missing the object"))?;
+    let data = STANDARD.decode(object).context("Failed to decode object")?;
+
+    // Keep the object on disk so it can be reloaded like a local build
+    let scratch_path = std::env::temp_dir().join(format!("objdiff-scratch-{}.o", config.slug));
+    fs::write(&scratch_path, data)
+        .with_context(|| format!("Failed to write {}", scratch_path.display()))?;
+
+    update_status(status, "Loading objects".to_string(), 1, 3, &cancel)?;
+    let target = read::read(&config.target_path, &config.diff_obj_config)
+        .with_context(|| format!("Failed to read object '{}'", config.target_path.display()))?;
+    let scratch = read::read(&scratch_path, &config.diff_obj_config)
+        .with_context(|| format!("Failed to read scratch object '{}'", scratch_path.display()))?;
+
+    update_status(status, "Performing diff".to_string(), 2, 3, &cancel)?;
+    let result = diff_objs(&config.diff_obj_config, Some(&target), Some(&scratch), None)?;
+
+    update_status(status, "Complete".to_string(), 3, 3, &cancel)?;
+    Ok(Box::new(ObjDiffResult {
+        first_status: BuildStatus::default(),
+        second_status: BuildStatus::default(),
+        first_obj: result.left.map(|d| (target, d)),
+        second_obj: result.right.map(|d| (scratch, d)),
+        time: OffsetDateTime::now_utc(),
+    }))
+}
+
+pub fn start_fetch_scratch(ctx: &egui::Context, config: FetchScratchConfig) -> JobState {
+    start_job(ctx, "Fetch scratch", Job::FetchScratch, move |context, cancel| {
+        run_fetch_scratch(&context, cancel, config).map(|result| JobResult::ObjDiff(Some(result)))
+    })
+}
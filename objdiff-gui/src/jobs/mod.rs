@@ -8,14 +8,19 @@ use std::{
 };
 
 use anyhow::Result;
+use objdiff_core::build::BuildOutputLine;
 
 use crate::jobs::{
-    check_update::CheckUpdateResult, create_scratch::CreateScratchResult, objdiff::ObjDiffResult,
+    check_update::CheckUpdateResult, create_scratch::CreateScratchResult,
+    dashboard::DashboardResult, fetch_config::FetchConfigResult, objdiff::ObjDiffResult,
     update::UpdateResult,
 };
 
 pub mod check_update;
 pub mod create_scratch;
+pub mod dashboard;
+pub mod fetch_config;
+pub mod fetch_scratch;
 pub mod objdiff;
 pub mod update;
 
@@ -25,6 +30,9 @@ pub enum Job {
     CheckUpdate,
     Update,
     CreateScratch,
+    Dashboard,
+    FetchScratch,
+    FetchConfig,
 }
 pub static JOB_ID: AtomicUsize = AtomicUsize::new(0);
 
@@ -110,6 +118,9 @@ pub struct JobState {
     pub should_remove: bool,
 }
 
+/// Lines of process output kept for display while a job runs
+pub const MAX_LOG_LINES: usize = 200;
+
 #[derive(Default)]
 pub struct JobStatus {
     pub title: String,
@@ -117,6 +128,8 @@ pub struct JobStatus {
     pub progress_items: Option<[u32; 2]>,
     pub status: String,
     pub error: Option<anyhow::Error>,
+    /// Latest output of the job's running processes, up to [`MAX_LOG_LINES`]
+    pub log: Vec<BuildOutputLine>,
 }
 
 pub enum JobResult {
@@ -125,9 +138,11 @@ pub enum JobResult {
     CheckUpdate(Option<Box<CheckUpdateResult>>),
     Update(Box<UpdateResult>),
     CreateScratch(Option<Box<CreateScratchResult>>),
+    Dashboard(Option<Box<DashboardResult>>),
+    FetchConfig(Option<Box<FetchConfigResult>>),
 }
 
-fn should_cancel(rx: &Receiver<()>) -> bool {
+pub(crate) fn should_cancel(rx: &Receiver<()>) -> bool {
     match rx.try_recv() {
         Ok(_) | Err(TryRecvError::Disconnected) => true,
         Err(_) => false,
@@ -146,6 +161,7 @@ fn start_job(
         progress_items: None,
         status: String::new(),
         error: None,
+        log: vec![],
     }));
     let context = JobContext { status: status.clone(), egui: ctx.clone() };
     let context_inner = JobContext { status: status.clone(), egui: ctx.clone() };
@@ -1,12 +1,19 @@
 use std::{
     path::{Path, PathBuf},
-    process::Command,
-    str::from_utf8,
-    sync::mpsc::Receiver,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{Receiver, Sender},
+        Arc,
+    },
+    time::Duration,
 };
 
 use anyhow::{anyhow, Context, Error, Result};
 use objdiff_core::{
+    build::{
+        apply_build_status, default_parallel_builds, run_make, BuildConfig, BuildOutputLine,
+        BuildStatus, POLL_INTERVAL,
+    },
     diff::{diff_objs, DiffObjConfig, ObjDiff},
     obj::{read, ObjInfo},
 };
@@ -14,44 +21,28 @@ use time::OffsetDateTime;
 
 use crate::{
     app::{AppConfig, ObjectConfig},
-    jobs::{start_job, update_status, Job, JobContext, JobResult, JobState},
+    jobs::{
+        should_cancel, start_job, update_status, Job, JobContext, JobResult, JobState,
+        MAX_LOG_LINES,
+    },
+    remote::fetch_remote_file,
+    snapshot::snapshot_path,
 };
 
-pub struct BuildStatus {
-    pub success: bool,
-    pub cmdline: String,
-    pub stdout: String,
-    pub stderr: String,
-}
-
-impl Default for BuildStatus {
-    fn default() -> Self {
-        BuildStatus {
-            success: true,
-            cmdline: String::new(),
-            stdout: String::new(),
-            stderr: String::new(),
-        }
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct BuildConfig {
-    pub project_dir: Option<PathBuf>,
-    pub custom_make: Option<String>,
-    pub custom_args: Option<Vec<String>>,
-    #[allow(unused)]
-    pub selected_wsl_distro: Option<String>,
-}
-
-impl BuildConfig {
-    pub(crate) fn from_config(config: &AppConfig) -> Self {
-        Self {
-            project_dir: config.project_dir.clone(),
-            custom_make: config.custom_make.clone(),
-            custom_args: config.custom_args.clone(),
-            selected_wsl_distro: config.selected_wsl_distro.clone(),
-        }
+/// Build settings from the app config.
+pub(crate) fn build_config(config: &AppConfig) -> BuildConfig {
+    BuildConfig {
+        project_dir: config.project_dir.clone(),
+        custom_make: config.custom_make.clone(),
+        custom_args: config.custom_args.clone(),
+        selected_wsl_distro: config.selected_wsl_distro.clone(),
+        wsl_mount_prefix: config.wsl_mount_prefix.clone(),
+        max_parallel_builds: config
+            .max_parallel_builds
+            .filter(|&n| n > 0)
+            .unwrap_or_else(default_parallel_builds),
+        timeout: config.build_timeout.map(Duration::from_secs),
+        cancel: None,
     }
 }
 
@@ -59,18 +50,57 @@ pub struct ObjDiffConfig {
     pub build_config: BuildConfig,
     pub build_base: bool,
     pub build_target: bool,
+    pub build_parallel: bool,
     pub selected_obj: Option<ObjectConfig>,
     pub diff_obj_config: DiffObjConfig,
+    pub project_url: Option<String>,
+    pub base_snapshot: Option<PathBuf>,
+    /// Base path of another object, replacing the selected object's base path
+    pub base_object_path: Option<PathBuf>,
 }
 
 impl ObjDiffConfig {
     pub(crate) fn from_config(config: &AppConfig) -> Self {
         Self {
-            build_config: BuildConfig::from_config(config),
+            build_config: build_config(config),
             build_base: config.build_base,
             build_target: config.build_target,
+            build_parallel: config.build_parallel,
             selected_obj: config.selected_obj.clone(),
-            diff_obj_config: config.diff_obj_config.clone(),
+            diff_obj_config: DiffObjConfig {
+                symbol_mappings: config
+                    .selected_obj
+                    .as_ref()
+                    .and_then(|obj| obj.symbol_mappings.as_ref())
+                    .into_iter()
+                    .flatten()
+                    .filter(|(l, r)| !config.symbol_pairs.iter().any(|(pl, pr)| pl == l || pr == r))
+                    .chain(&config.symbol_pairs)
+                    .cloned()
+                    .collect(),
+                ..config.diff_obj_config.clone()
+            },
+            project_url: config.project_url.clone(),
+            base_snapshot: config
+                .base_snapshot
+                .as_ref()
+                .zip(config.project_dir.as_ref().zip(config.selected_obj.as_ref()))
+                .and_then(|(label, (project_dir, obj))| {
+                    snapshot_path(project_dir, &obj.name, label)
+                }),
+            base_object_path: config
+                .base_object
+                .as_ref()
+                .zip(config.project_dir.as_ref())
+                .and_then(|(name, project_dir)| {
+                    let mut object = config.objects.iter().find(|o| o.name() == name)?.clone();
+                    object.resolve_paths(
+                        project_dir,
+                        config.target_obj_dir.as_deref(),
+                        config.base_obj_dir.as_deref(),
+                    );
+                    object.base_path
+                }),
         }
     }
 }
@@ -83,83 +113,13 @@ pub struct ObjDiffResult {
     pub time: OffsetDateTime,
 }
 
-pub(crate) fn run_make(config: &BuildConfig, arg: &Path) -> BuildStatus {
-    let Some(cwd) = &config.project_dir else {
-        return BuildStatus {
-            success: false,
-            stderr: "Missing project dir".to_string(),
-            ..Default::default()
-        };
-    };
-    match run_make_cmd(config, cwd, arg) {
-        Ok(status) => status,
-        Err(e) => BuildStatus { success: false, stderr: e.to_string(), ..Default::default() },
-    }
-}
-
-fn run_make_cmd(config: &BuildConfig, cwd: &Path, arg: &Path) -> Result<BuildStatus> {
-    let make = config.custom_make.as_deref().unwrap_or("make");
-    let make_args = config.custom_args.as_deref().unwrap_or(&[]);
-    #[cfg(not(windows))]
-    let mut command = {
-        let mut command = Command::new(make);
-        command.current_dir(cwd).args(make_args).arg(arg);
-        command
-    };
-    #[cfg(windows)]
-    let mut command = {
-        use std::os::windows::process::CommandExt;
-
-        use path_slash::PathExt;
-        let mut command = if config.selected_wsl_distro.is_some() {
-            Command::new("wsl")
-        } else {
-            Command::new(make)
-        };
-        if let Some(distro) = &config.selected_wsl_distro {
-            // Strip distro root prefix \\wsl.localhost\{distro}
-            let wsl_path_prefix = format!("\\\\wsl.localhost\\{}", distro);
-            let cwd = match cwd.strip_prefix(wsl_path_prefix) {
-                Ok(new_cwd) => format!("/{}", new_cwd.to_slash_lossy().as_ref()),
-                Err(_) => cwd.to_string_lossy().to_string(),
-            };
-
-            command
-                .arg("--cd")
-                .arg(cwd)
-                .arg("-d")
-                .arg(distro)
-                .arg("--")
-                .arg(make)
-                .args(make_args)
-                .arg(arg.to_slash_lossy().as_ref());
-        } else {
-            command.current_dir(cwd).args(make_args).arg(arg.to_slash_lossy().as_ref());
-        }
-        command.creation_flags(winapi::um::winbase::CREATE_NO_WINDOW);
-        command
-    };
-    let mut cmdline = shell_escape::escape(command.get_program().to_string_lossy()).into_owned();
-    for arg in command.get_args() {
-        cmdline.push(' ');
-        cmdline.push_str(shell_escape::escape(arg.to_string_lossy()).as_ref());
-    }
-    let output = command.output().map_err(|e| anyhow!("Failed to execute build: {e}"))?;
-    let stdout = from_utf8(&output.stdout).context("Failed to process stdout")?;
-    let stderr = from_utf8(&output.stderr).context("Failed to process stderr")?;
-    Ok(BuildStatus {
-        success: output.status.code().unwrap_or(-1) == 0,
-        cmdline,
-        stdout: stdout.to_string(),
-        stderr: stderr.to_string(),
-    })
-}
-
 fn run_build(
     context: &JobContext,
     cancel: Receiver<()>,
-    config: ObjDiffConfig,
+    mut config: ObjDiffConfig,
 ) -> Result<Box<ObjDiffResult>> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    config.build_config.cancel = Some(cancelled.clone());
     let obj_config = config.selected_obj.as_ref().ok_or_else(|| Error::msg("Missing obj path"))?;
     let project_dir = config
         .build_config
@@ -177,7 +137,13 @@ fn run_build(
     } else {
         None
     };
-    let base_path_rel = if let Some(base_path) = &obj_config.base_path {
+    // A selected snapshot replaces the base object, so there's nothing to build
+    let base_path = if config.base_snapshot.is_some() {
+        None
+    } else {
+        config.base_object_path.as_ref().or(obj_config.base_path.as_ref())
+    };
+    let base_path_rel = if let Some(base_path) = base_path {
         Some(base_path.strip_prefix(project_dir).map_err(|_| {
             anyhow!(
                 "Base path '{}' doesn't begin with '{}'",
@@ -196,33 +162,89 @@ fn run_build(
     if config.build_base && base_path_rel.is_some() {
         total += 1;
     }
-    let first_status = match target_path_rel {
-        Some(target_path_rel) if config.build_target => {
-            update_status(
-                context,
-                format!("Building target {}", target_path_rel.display()),
-                0,
-                total,
-                &cancel,
-            )?;
-            run_make(&config.build_config, target_path_rel)
+    let target_build = target_path_rel.filter(|_| config.build_target);
+    let base_build = base_path_rel.filter(|_| config.build_base);
+    let status = match (target_build, base_build) {
+        (Some(target), Some(base)) => {
+            format!("Building target {} and base {}", target.display(), base.display())
         }
-        _ => BuildStatus::default(),
+        (Some(target), None) => format!("Building target {}", target.display()),
+        (None, Some(base)) => format!("Building base {}", base.display()),
+        (None, None) => String::new(),
     };
-
-    let second_status = match base_path_rel {
-        Some(base_path_rel) if config.build_base => {
-            update_status(
-                context,
-                format!("Building base {}", base_path_rel.display()),
-                0,
-                total,
-                &cancel,
-            )?;
-            run_make(&config.build_config, base_path_rel)
+    if target_build.is_some() || base_build.is_some() {
+        update_status(context, status, 0, total, &cancel)?;
+    }
+    // Target then base, or both at once if the project allows it, limited by the parallel
+    // build setting
+    let build_config = &config.build_config;
+    let build_status = obj_config.build_status.as_ref();
+    let extra_args = obj_config.make_args.as_deref().unwrap_or_default();
+    let parallel = config.build_parallel;
+    let build = move |path: Option<&Path>, output: Sender<BuildOutputLine>| {
+        path.map(|path| {
+            let mut status = run_make(build_config, path, extra_args, Some(output));
+            if let Some(build_status) = build_status {
+                apply_build_status(&mut status, build_status);
+            }
+            status
+        })
+        .unwrap_or_default()
+    };
+    let (output_tx, output_rx) = std::sync::mpsc::channel::<BuildOutputLine>();
+    let append_output = || {
+        let mut lines = output_rx.try_iter().peekable();
+        if lines.peek().is_some() {
+            if let Ok(mut status) = context.status.write() {
+                status.log.extend(lines);
+                let excess = status.log.len().saturating_sub(MAX_LOG_LINES);
+                status.log.drain(..excess);
+            }
+            context.egui.request_repaint();
         }
-        _ => BuildStatus::default(),
     };
+    let joined = |result: std::thread::Result<BuildStatus>| {
+        result.unwrap_or_else(|_| BuildStatus {
+            success: false,
+            stderr: "Build thread panicked".to_string(),
+            ..Default::default()
+        })
+    };
+    let (first_status, second_status) = std::thread::scope(|s| {
+        let builds = s.spawn(move || {
+            if parallel {
+                let first_tx = output_tx.clone();
+                let first = s.spawn(move || build(target_build, first_tx));
+                let second = build(base_build, output_tx);
+                (joined(first.join()), second)
+            } else {
+                (build(target_build, output_tx.clone()), build(base_build, output_tx))
+            }
+        });
+        // Relay the job's cancellation to the running builds, and their output to the UI
+        while !builds.is_finished() {
+            if should_cancel(&cancel) {
+                cancelled.store(true, Ordering::Relaxed);
+            }
+            append_output();
+            std::thread::sleep(POLL_INTERVAL);
+        }
+        append_output();
+        builds.join().unwrap_or_else(|_| {
+            let panicked = || joined(Err(Box::new("Build thread panicked")));
+            (panicked(), panicked())
+        })
+    });
+    if cancelled.load(Ordering::Relaxed) {
+        return Err(Error::msg("Cancelled"));
+    }
+
+    if let Some(project_url) = &config.project_url {
+        for path in [obj_config.target_path.as_ref(), base_path].into_iter().flatten() {
+            update_status(context, format!("Downloading {}", path.display()), 1, total, &cancel)?;
+            fetch_remote_file(project_url, project_dir, path)?;
+        }
+    }
 
     let time = OffsetDateTime::now_utc();
 
@@ -243,15 +265,10 @@ fn run_build(
             _ => None,
         };
 
-    let second_obj = match &obj_config.base_path {
+    let second_obj = match config.base_snapshot.as_ref().or(base_path) {
         Some(base_path) if second_status.success => {
-            update_status(
-                context,
-                format!("Loading base {}", base_path_rel.unwrap().display()),
-                3,
-                total,
-                &cancel,
-            )?;
+            let name = base_path_rel.unwrap_or(base_path);
+            update_status(context, format!("Loading base {}", name.display()), 3, total, &cancel)?;
             Some(
                 read::read(base_path, &config.diff_obj_config)
                     .with_context(|| format!("Failed to read object '{}'", base_path.display()))?,
@@ -3,9 +3,16 @@
 
 mod app;
 mod app_config;
+mod build_history;
 mod config;
+mod deeplink;
 mod fonts;
+mod html_export;
 mod jobs;
+mod remote;
+mod snapshot;
+mod sound;
+mod storage;
 mod update;
 mod views;
 
@@ -20,7 +27,10 @@ use anyhow::{ensure, Result};
 use cfg_if::cfg_if;
 use time::UtcOffset;
 
-use crate::views::graphics::{load_graphics_config, GraphicsBackend, GraphicsConfig};
+use crate::{
+    deeplink::{DeepLink, DEEP_LINK_SCHEME},
+    views::graphics::{load_graphics_config, GraphicsBackend, GraphicsConfig},
+};
 
 fn load_icon() -> Result<egui::IconData> {
     use bytes::Buf;
@@ -47,6 +57,19 @@ fn main() -> ExitCode {
     // https://github.com/time-rs/time/issues/293
     let utc_offset = UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC);
 
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    storage::init_storage_dir(&args);
+
+    let deep_link = args.iter().find(|arg| arg.starts_with(DEEP_LINK_SCHEME)).and_then(|arg| {
+        match DeepLink::parse(arg) {
+            Ok(link) => Some(link),
+            Err(e) => {
+                log::error!("Failed to parse link: {e:?}");
+                None
+            }
+        }
+    });
+
     let app_path = std::env::current_exe().ok();
     let exec_path: Rc<Mutex<Option<PathBuf>>> = Rc::new(Mutex::new(None));
     let mut native_options =
@@ -61,7 +84,7 @@ fn main() -> ExitCode {
     }
     let mut graphics_config = GraphicsConfig::default();
     let mut graphics_config_path = None;
-    if let Some(storage_dir) = eframe::storage_dir(APP_NAME) {
+    if let Some(storage_dir) = storage::storage_dir() {
         let config_path = storage_dir.join("graphics.ron");
         match load_graphics_config(&config_path) {
             Ok(Some(config)) => {
@@ -95,6 +118,7 @@ fn main() -> ExitCode {
         app_path.clone(),
         graphics_config.clone(),
         graphics_config_path.clone(),
+        deep_link.clone(),
     ) {
         eframe_error = Some(e);
     }
@@ -119,6 +143,7 @@ fn main() -> ExitCode {
                 app_path.clone(),
                 graphics_config.clone(),
                 graphics_config_path.clone(),
+                deep_link.clone(),
             ) {
                 eframe_error = Some(e);
             } else {
@@ -141,6 +166,7 @@ fn main() -> ExitCode {
             app_path,
             graphics_config,
             graphics_config_path,
+            deep_link,
         ) {
             eframe_error = Some(e);
         } else {
@@ -184,6 +210,7 @@ fn run_eframe(
     app_path: Option<PathBuf>,
     graphics_config: GraphicsConfig,
     graphics_config_path: Option<PathBuf>,
+    deep_link: Option<DeepLink>,
 ) -> Result<(), eframe::Error> {
     eframe::run_native(
         APP_NAME,
@@ -196,6 +223,7 @@ fn run_eframe(
                 app_path,
                 graphics_config,
                 graphics_config_path,
+                deep_link,
             ))
         }),
     )
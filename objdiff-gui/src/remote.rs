@@ -0,0 +1,112 @@
+use std::{
+    fs,
+    hash::Hasher,
+    path::{Component, Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use fnv::FnvHasher;
+use objdiff_core::config::{read_project_config, ProjectConfig};
+use reqwest::{header, StatusCode};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+pub fn is_remote_url(s: &str) -> bool { s.starts_with("http://") || s.starts_with("https://") }
+
+/// Local directory that mirrors files fetched for a remote project.
+pub fn remote_cache_dir(url: &str) -> PathBuf {
+    // A stable hash, as the directory is reused across runs and objdiff versions
+    let mut hasher = FnvHasher::default();
+    hasher.write(url.as_bytes());
+    std::env::temp_dir().join("objdiff-remote").join(format!("{:016x}", hasher.finish()))
+}
+
+/// The URL that project-relative paths are resolved against.
+pub fn base_url(url: &str) -> &str {
+    let path_end = url.find(['?', '#']).unwrap_or(url.len());
+    match url[..path_end].rfind('/') {
+        Some(idx) => &url[..=idx],
+        None => url,
+    }
+}
+
+struct Fetched {
+    data: Vec<u8>,
+    etag: Option<String>,
+}
+
+/// Fetches `url`, or returns `None` if it still matches `etag`.
+fn get(url: &str, etag: Option<&str>) -> Result<Option<Fetched>> {
+    let client = reqwest::blocking::Client::builder().timeout(TIMEOUT).build()?;
+    let mut request = client.get(url);
+    if let Some(etag) = etag {
+        request = request.header(header::IF_NONE_MATCH, etag);
+    }
+    let response = request.send().map_err(|e| anyhow!("Failed to fetch {url}: {e}"))?;
+    let status = response.status();
+    if status == StatusCode::NOT_MODIFIED && etag.is_some() {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        bail!("Failed to fetch {url}: HTTP {status}");
+    }
+    let etag =
+        response.headers().get(header::ETAG).and_then(|v| v.to_str().ok()).map(str::to_string);
+    let data = response.bytes().map_err(|e| anyhow!("Failed to fetch {url}: {e}"))?.to_vec();
+    Ok(Some(Fetched { data, etag }))
+}
+
+pub fn fetch_project_config(url: &str) -> Result<ProjectConfig> {
+    let Fetched { data, .. } = get(url, None)?.context("Unexpected response")?;
+    let path_end = url.find(['?', '#']).unwrap_or(url.len());
+    // Includes are fetched relative to the config's URL
+    read_project_config(&mut data.as_slice(), Path::new(&url[..path_end]), &mut |path| {
+        let url = path.to_string_lossy().replace('\\', "/");
+        Ok(get(&url, None)?.context("Unexpected response")?.data)
+    })
+    .with_context(|| format!("Failed to parse project config {url}"))
+}
+
+/// Sidecar file holding the ETag of a cached download.
+fn etag_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".etag");
+    path.with_file_name(name)
+}
+
+/// Downloads `path` (relative to `cache_dir`) from the remote project. A cached copy is
+/// revalidated with its ETag, and downloaded again if it changed or the server sent no ETag.
+pub fn fetch_remote_file(project_url: &str, cache_dir: &Path, path: &Path) -> Result<()> {
+    let etag_path = etag_path(path);
+    let etag = path.is_file().then(|| fs::read_to_string(&etag_path).ok()).flatten();
+    let rel_path = path.strip_prefix(cache_dir).map_err(|_| {
+        anyhow!("Path '{}' doesn't begin with '{}'", path.display(), cache_dir.display())
+    })?;
+    let mut url = base_url(project_url).to_string();
+    for (i, component) in rel_path.components().enumerate() {
+        let Component::Normal(name) = component else {
+            bail!("Unsupported remote path '{}'", rel_path.display());
+        };
+        if i > 0 {
+            url.push('/');
+        }
+        url.push_str(&name.to_string_lossy());
+    }
+    let Some(Fetched { data, etag }) = get(&url, etag.as_deref())? else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    fs::write(path, data).with_context(|| format!("Failed to write {}", path.display()))?;
+    match etag {
+        Some(etag) => fs::write(&etag_path, etag)
+            .with_context(|| format!("Failed to write {}", etag_path.display())),
+        None => {
+            fs::remove_file(&etag_path).ok();
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::{
+    fs,
+    hash::Hasher,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{anyhow, bail, Context, Result};
+use fnv::FnvHasher;
+
+use crate::storage::storage_dir;
+
+const SNAPSHOT_EXT: &str = "o";
+
+/// Directory holding the saved snapshots for an object.
+fn snapshot_dir(project_dir: &Path, object_name: &str) -> Option<PathBuf> {
+    // A stable hash, as snapshots are kept across runs and objdiff versions
+    let mut hasher = FnvHasher::default();
+    hasher.write(project_dir.to_string_lossy().as_bytes());
+    hasher.write_u8(0);
+    hasher.write(object_name.as_bytes());
+    Some(storage_dir()?.join("snapshots").join(format!("{:016x}", hasher.finish())))
+}
+
+pub fn snapshot_path(project_dir: &Path, object_name: &str, label: &str) -> Option<PathBuf> {
+    Some(snapshot_dir(project_dir, object_name)?.join(format!("{label}.{SNAPSHOT_EXT}")))
+}
+
+/// Saves a copy of the object at `source` under `label`, replacing any existing snapshot.
+pub fn save_snapshot(
+    project_dir: &Path,
+    object_name: &str,
+    label: &str,
+    source: &Path,
+) -> Result<()> {
+    if label.is_empty() || label.contains(['/', '\\', '.']) {
+        bail!("Invalid snapshot label '{label}'");
+    }
+    let path = snapshot_path(project_dir, object_name, label)
+        .ok_or_else(|| anyhow!("Failed to determine snapshot directory"))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {}", parent.display()))?;
+    }
+    fs::copy(source, &path)
+        .with_context(|| format!("Failed to copy {} to {}", source.display(), path.display()))?;
+    Ok(())
+}
+
+/// Labels of the saved snapshots for an object, sorted by name.
+pub fn list_snapshots(project_dir: &Path, object_name: &str) -> Vec<String> {
+    let Some(dir) = snapshot_dir(project_dir, object_name) else {
+        return vec![];
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut labels: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == SNAPSHOT_EXT))
+        .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().into_owned()))
+        .collect();
+    labels.sort();
+    labels
+}
@@ -0,0 +1,81 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{Context, Result};
+use rodio::{Decoder, OutputStream, Sink};
+
+/// Minimum time between two completion sounds.
+const MIN_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct CompletionSoundConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Sound file (WAV, FLAC or Ogg Vorbis)
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// Volume from 0.0 to 1.0
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+}
+
+fn default_volume() -> f32 { 0.5 }
+
+impl Default for CompletionSoundConfig {
+    fn default() -> Self { Self { enabled: false, path: None, volume: default_volume() } }
+}
+
+/// Decides when the selected symbol has just been matched.
+#[derive(Default)]
+pub struct CompletionSoundTrigger {
+    /// Symbol name and match percentage from the previous build
+    last: Option<(String, f32)>,
+    last_played: Option<Instant>,
+}
+
+impl CompletionSoundTrigger {
+    /// Records the selected symbol's match percentage after a build, returning whether it went
+    /// from unmatched to 100%. Selecting an already matched symbol doesn't count.
+    pub fn update(&mut self, symbol_name: &str, match_percent: f32, now: Instant) -> bool {
+        let completed = match &self.last {
+            Some((name, percent)) => name == symbol_name && *percent < 100.0,
+            None => false,
+        } && match_percent >= 100.0;
+        self.last = Some((symbol_name.to_string(), match_percent));
+        if !completed || self.last_played.is_some_and(|t| now.duration_since(t) < MIN_INTERVAL) {
+            return false;
+        }
+        self.last_played = Some(now);
+        true
+    }
+
+    pub fn reset(&mut self) { self.last = None; }
+}
+
+/// Plays a sound file in the background on the default audio output.
+pub fn play_sound(path: &Path, volume: f32) -> Result<()> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let source = Decoder::new(BufReader::new(file))
+        .with_context(|| format!("Failed to decode {}", path.display()))?;
+    let volume = volume.clamp(0.0, 1.0);
+    // The output stream can't be moved across threads, so it's opened by the playing thread
+    std::thread::spawn(move || {
+        let result = (|| -> Result<()> {
+            let (_stream, handle) =
+                OutputStream::try_default().context("Failed to open audio output")?;
+            let sink = Sink::try_new(&handle).context("Failed to create audio sink")?;
+            sink.set_volume(volume);
+            sink.append(source);
+            sink.sleep_until_end();
+            Ok(())
+        })();
+        if let Err(e) = result {
+            log::error!("Failed to play sound: {e:#}");
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use anyhow::{Context, Result};
+
+use crate::APP_NAME;
+
+/// Environment variable overriding the directory that all saved state goes to.
+pub const CONFIG_DIR_ENV: &str = "OBJDIFF_CONFIG_DIR";
+/// Command-line flag overriding the directory that all saved state goes to.
+pub const CONFIG_DIR_ARG: &str = "--config-dir";
+
+static STORAGE_OVERRIDE: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+/// Reads the storage directory override from the command line or environment. An unusable
+/// directory is ignored with a warning, falling back to the default.
+pub fn init_storage_dir(args: &[String]) {
+    let arg = args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == CONFIG_DIR_ARG {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix(CONFIG_DIR_ARG)?.strip_prefix('=').map(str::to_string)
+        }
+    });
+    let dir = arg.or_else(|| std::env::var(CONFIG_DIR_ENV).ok()).filter(|s| !s.is_empty());
+    let dir = dir.map(PathBuf::from).and_then(|dir| match check_writable(&dir) {
+        Ok(()) => Some(dir),
+        Err(e) => {
+            log::warn!("Using the default config directory: {e:?}");
+            None
+        }
+    });
+    STORAGE_OVERRIDE.set(dir).ok();
+}
+
+fn check_writable(dir: &Path) -> Result<()> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    let test_path = dir.join(".objdiff-write-test");
+    fs::write(&test_path, []).with_context(|| format!("{} is not writable", dir.display()))?;
+    fs::remove_file(&test_path).ok();
+    Ok(())
+}
+
+/// The overridden storage directory, if any.
+pub fn storage_override() -> Option<&'static Path> {
+    STORAGE_OVERRIDE.get().and_then(|dir| dir.as_deref())
+}
+
+/// Directory for saved state: the override if set, otherwise the platform default.
+pub fn storage_dir() -> Option<PathBuf> {
+    storage_override().map(Path::to_path_buf).or_else(|| eframe::storage_dir(APP_NAME))
+}
+
+/// Key-value storage in `app.ron` within the overridden storage directory, used in place of
+/// eframe's storage, which is always in the platform default directory.
+pub struct DirStorage {
+    path: PathBuf,
+    kv: HashMap<String, String>,
+    dirty: bool,
+}
+
+impl DirStorage {
+    pub fn new(dir: &Path) -> Self {
+        let path = dir.join("app.ron");
+        let kv = match fs::read_to_string(&path) {
+            Ok(data) => ron::from_str(&data).unwrap_or_else(|e| {
+                log::warn!("Failed to parse {}: {e}", path.display());
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        Self { path, kv, dirty: false }
+    }
+}
+
+impl eframe::Storage for DirStorage {
+    fn get_string(&self, key: &str) -> Option<String> { self.kv.get(key).cloned() }
+
+    fn set_string(&mut self, key: &str, value: String) {
+        if self.kv.get(key) != Some(&value) {
+            self.kv.insert(key.to_string(), value);
+            self.dirty = true;
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        self.dirty = false;
+        let result = ron::ser::to_string_pretty(&self.kv, Default::default())
+            .map_err(anyhow::Error::from)
+            .and_then(|data| fs::write(&self.path, data).map_err(anyhow::Error::from));
+        if let Err(e) = result {
+            log::warn!("Failed to save {}: {e:?}", self.path.display());
+        }
+    }
+}
@@ -11,7 +11,11 @@ pub struct Appearance {
     pub ui_font: FontId,
     pub code_font: FontId,
     pub diff_colors: Vec<Color32>,
+    /// Instructions in blocks that moved within the function
+    pub moved_color: Color32,
     pub theme: eframe::Theme,
+    /// Use red, yellow and blue instead of red and green for match colors
+    pub colorblind: bool,
 
     // Applied by theme
     #[serde(skip)]
@@ -28,6 +32,10 @@ pub struct Appearance {
     pub insert_color: Color32, // GREEN
     #[serde(skip)]
     pub delete_color: Color32, // RED
+    #[serde(skip)]
+    pub background_color: Color32,
+    #[serde(skip)]
+    pub faint_background_color: Color32,
 
     // Global
     #[serde(skip)]
@@ -47,8 +55,11 @@ pub struct FontState {
     // loaded_families: HashMap<String, LoadedFontFamily>,
 }
 
+const DEFAULT_MOVED_COLOR: Color32 = Color32::from_rgb(230, 190, 40);
 const DEFAULT_UI_FONT: FontId = FontId { size: 12.0, family: FontFamily::Proportional };
 const DEFAULT_CODE_FONT: FontId = FontId { size: 14.0, family: FontFamily::Monospace };
+/// Code font sizes reachable with the font size shortcuts.
+const CODE_FONT_SIZE_RANGE: std::ops::RangeInclusive<f32> = 8.0..=32.0;
 
 impl Default for Appearance {
     fn default() -> Self {
@@ -56,7 +67,9 @@ impl Default for Appearance {
             ui_font: DEFAULT_UI_FONT,
             code_font: DEFAULT_CODE_FONT,
             diff_colors: DEFAULT_COLOR_ROTATION.to_vec(),
+            moved_color: DEFAULT_MOVED_COLOR,
             theme: eframe::Theme::Dark,
+            colorblind: false,
             text_color: Color32::GRAY,
             emphasized_text_color: Color32::LIGHT_GRAY,
             deemphasized_text_color: Color32::DARK_GRAY,
@@ -64,6 +77,8 @@ impl Default for Appearance {
             replace_color: Color32::LIGHT_BLUE,
             insert_color: Color32::GREEN,
             delete_color: Color32::from_rgb(200, 40, 41),
+            background_color: Color32::from_gray(27),
+            faint_background_color: Color32::from_additive_luminance(5),
             utc_offset: UtcOffset::UTC,
             fonts: FontState::default(),
             next_ui_font: None,
@@ -119,6 +134,22 @@ impl Appearance {
                 self.delete_color = Color32::from_rgb(200, 40, 41);
             }
         }
+        self.background_color = style.visuals.panel_fill;
+        self.faint_background_color = style.visuals.faint_bg_color;
+        if self.colorblind {
+            (self.replace_color, self.insert_color, self.delete_color) = match self.theme {
+                eframe::Theme::Dark => (
+                    Color32::from_rgb(240, 228, 66),
+                    Color32::from_rgb(86, 180, 233),
+                    Color32::from_rgb(213, 94, 0),
+                ),
+                eframe::Theme::Light => (
+                    Color32::from_rgb(170, 130, 0),
+                    Color32::from_rgb(0, 114, 178),
+                    Color32::from_rgb(213, 94, 0),
+                ),
+            };
+        }
         style.spacing.scroll = egui::style::ScrollStyle::solid();
         style.spacing.scroll.bar_width = 10.0;
         ctx.set_style(style);
@@ -156,6 +187,13 @@ impl Appearance {
         }
     }
 
+    /// Queues a code font size change, clamped to [`CODE_FONT_SIZE_RANGE`].
+    pub fn adjust_code_font_size(&mut self, delta: f32) {
+        let font = self.next_code_font.get_or_insert_with(|| self.code_font.clone());
+        font.size =
+            (font.size + delta).clamp(*CODE_FONT_SIZE_RANGE.start(), *CODE_FONT_SIZE_RANGE.end());
+    }
+
     pub fn init_fonts(&mut self, ctx: &egui::Context) {
         self.fonts.family_names = self.fonts.source.all_families().unwrap_or_default();
         match load_font_if_needed(
@@ -277,6 +315,18 @@ pub fn appearance_window(ctx: &egui::Context, show: &mut bool, appearance: &mut
                 ui.selectable_value(&mut appearance.theme, eframe::Theme::Dark, "Dark");
                 ui.selectable_value(&mut appearance.theme, eframe::Theme::Light, "Light");
             });
+        ui.checkbox(&mut appearance.colorblind, "Colorblind-friendly palette").on_hover_text(
+            "Use red, yellow and blue for match and diff colors instead of red and green.",
+        );
+        ui.horizontal(|ui| {
+            for (color, text) in [
+                (appearance.insert_color, "100% / inserted"),
+                (appearance.replace_color, "≥50% / replaced"),
+                (appearance.delete_color, "<50% / deleted"),
+            ] {
+                ui.colored_label(color, format!("■ {text}"));
+            }
+        });
         ui.separator();
         appearance.next_ui_font =
             font_id_ui(ui, "UI font:", appearance.ui_font.clone(), DEFAULT_UI_FONT, appearance);
@@ -309,5 +359,13 @@ pub fn appearance_window(ctx: &egui::Context, show: &mut bool, appearance: &mut
         if ui.small_button("+").clicked() {
             appearance.diff_colors.push(Color32::BLACK);
         }
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Moved blocks:");
+            ui.color_edit_button_srgba(&mut appearance.moved_color);
+            if ui.small_button("Reset").clicked() {
+                appearance.moved_color = DEFAULT_MOVED_COLOR;
+            }
+        });
     });
 }
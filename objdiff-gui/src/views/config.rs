@@ -2,8 +2,11 @@
 use std::string::FromUtf16Error;
 use std::{
     borrow::Cow,
+    collections::{HashMap, HashSet},
     mem::take,
     path::{PathBuf, MAIN_SEPARATOR},
+    sync::OnceLock,
+    time::{Duration, Instant},
 };
 
 #[cfg(all(windows, feature = "wsl"))]
@@ -13,13 +16,18 @@ use egui::{
     output::OpenUrl, text::LayoutJob, CollapsingHeader, FontFamily, FontId, RichText,
     SelectableLabel, TextFormat, Widget,
 };
+use filetime::FileTime;
 use globset::Glob;
 use objdiff_core::{
     config::{ProjectObject, DEFAULT_WATCH_PATTERNS},
-    diff::{ArmArchVersion, ArmR9Usage, MipsAbi, MipsInstrCategory, X86Formatter},
+    diff::{ArmArchVersion, ArmR9Usage, Demangler, MipsAbi, MipsInstrCategory, X86Formatter},
 };
 use self_update::cargo_crate_version;
 use strum::{EnumMessage, VariantArray};
+use time::{
+    format_description::{self, FormatItem},
+    OffsetDateTime,
+};
 
 use crate::{
     app::{AppConfig, AppConfigRef, ObjectConfig},
@@ -29,6 +37,8 @@ use crate::{
         update::start_update,
         Job, JobQueue, JobResult,
     },
+    remote::is_remote_url,
+    sound::play_sound,
     update::RELEASE_URL,
     views::{
         appearance::Appearance,
@@ -46,10 +56,17 @@ pub struct ConfigViewState {
     pub build_running: bool,
     pub queue_build: bool,
     pub watch_pattern_text: String,
+    pub section_order_text: Option<String>,
+    pub project_url_text: String,
     pub load_error: Option<String>,
+    /// Error from the file watcher, shown until dismissed
+    pub watch_error: Option<String>,
     pub object_search: String,
     pub filter_diffable: bool,
     pub filter_incomplete: bool,
+    /// Objects whose source file was modified since their last build, as of `stale_checked`
+    pub stale_objects: HashSet<String>,
+    pub stale_checked: Option<Instant>,
     #[cfg(all(windows, feature = "wsl"))]
     pub available_wsl_distros: Option<Vec<String>>,
     pub file_dialog_state: FileDialogState,
@@ -84,6 +101,14 @@ impl ConfigViewState {
                 let mut guard = config.write().unwrap();
                 guard.set_base_obj_dir(path.to_path_buf());
             }
+            FileDialogResult::BuildHistory(path) => {
+                let mut guard = config.write().unwrap();
+                guard.build_history_path = Some(path);
+            }
+            FileDialogResult::CompletionSound(path) => {
+                let mut guard = config.write().unwrap();
+                guard.completion_sound.path = Some(path);
+            }
             FileDialogResult::Object(path) => {
                 let mut guard = config.write().unwrap();
                 if let (Some(base_dir), Some(target_dir)) =
@@ -98,6 +123,9 @@ impl ConfigViewState {
                             reverse_fn_order: None,
                             complete: None,
                             scratch: None,
+                            build_status: None,
+                            make_args: None,
+                            symbol_mappings: None,
                         });
                     } else if let Ok(obj_path) = path.strip_prefix(target_dir) {
                         let base_path = base_dir.join(obj_path);
@@ -108,6 +136,9 @@ impl ConfigViewState {
                             reverse_fn_order: None,
                             complete: None,
                             scratch: None,
+                            build_status: None,
+                            make_args: None,
+                            symbol_mappings: None,
                         });
                     }
                 }
@@ -182,6 +213,9 @@ pub fn config_ui(
         auto_update_check,
         objects,
         object_nodes,
+        build_times,
+        object_problems,
+        project_dir,
         ..
     } = &mut *config_guard;
 
@@ -234,6 +268,38 @@ pub fn config_ui(
         }
     });
 
+    if !object_problems.is_empty() {
+        egui::CollapsingHeader::new(
+            RichText::new(format!("⚠ {} objects with missing files", object_problems.len()))
+                .color(appearance.replace_color),
+        )
+        .id_source("object_problems")
+        .show(ui, |ui| {
+            for problem in object_problems.iter() {
+                ui.label(RichText::new(problem).color(appearance.text_color));
+            }
+        });
+    }
+
+    if state.stale_checked.map_or(true, |time| time.elapsed() >= STALE_CHECK_INTERVAL) {
+        state.stale_objects = objects
+            .iter()
+            .filter(|object| {
+                let Some(project_dir) = project_dir.as_deref() else {
+                    return false;
+                };
+                build_times.get(object.name()).is_some_and(|time| {
+                    object.is_stale(
+                        project_dir,
+                        FileTime::from_unix_time(time.unix_timestamp(), time.nanosecond()),
+                    )
+                })
+            })
+            .map(|object| object.name().to_string())
+            .collect();
+        state.stale_checked = Some(Instant::now());
+    }
+
     let mut new_selected_obj = selected_obj.clone();
     if objects.is_empty() {
         if let (Some(_base_dir), Some(target_dir)) = (base_obj_dir, target_obj_dir) {
@@ -243,7 +309,7 @@ pub fn config_ui(
                         Box::pin(
                             rfd::AsyncFileDialog::new()
                                 .set_directory(target_dir)
-                                .add_filter("Object file", &["o", "elf", "obj"])
+                                .add_filter("Object file", &["o", "elf", "obj", "dol", "rel"])
                                 .pick_file(),
                         )
                     },
@@ -335,7 +401,15 @@ pub fn config_ui(
 
             ui.style_mut().wrap = Some(false);
             for node in nodes.iter() {
-                display_node(ui, &mut new_selected_obj, node, appearance, node_open);
+                display_node(
+                    ui,
+                    &mut new_selected_obj,
+                    node,
+                    appearance,
+                    node_open,
+                    build_times,
+                    &state.stale_objects,
+                );
             }
         });
     }
@@ -354,12 +428,17 @@ pub fn config_ui(
     ui.separator();
 }
 
+/// Format of the build times shown next to objects, parsed once
+static BUILD_TIME_FORMAT: OnceLock<Vec<FormatItem<'static>>> = OnceLock::new();
+
 fn display_object(
     ui: &mut egui::Ui,
     selected_obj: &mut Option<ObjectConfig>,
     name: &str,
     object: &ProjectObject,
     appearance: &Appearance,
+    build_times: &HashMap<String, OffsetDateTime>,
+    stale_objects: &HashSet<String>,
 ) {
     let object_name = object.name();
     let selected = matches!(selected_obj, Some(obj) if obj.name == object_name);
@@ -374,17 +453,37 @@ fn display_object(
     } else {
         appearance.text_color
     };
-    let clicked = SelectableLabel::new(
-        selected,
-        RichText::new(name)
-            .font(FontId {
-                size: appearance.ui_font.size,
-                family: appearance.code_font.family.clone(),
-            })
-            .color(color),
-    )
-    .ui(ui)
-    .clicked();
+    let last_build = build_times.get(object_name);
+    let clicked = ui
+        .horizontal(|ui| {
+            let clicked = SelectableLabel::new(
+                selected,
+                RichText::new(name)
+                    .font(FontId {
+                        size: appearance.ui_font.size,
+                        family: appearance.code_font.family.clone(),
+                    })
+                    .color(color),
+            )
+            .ui(ui)
+            .clicked();
+            if object.is_complete() {
+                ui.colored_label(appearance.insert_color, "✔").on_hover_text("Marked complete");
+            }
+            if let Some(time) = last_build {
+                let format = BUILD_TIME_FORMAT
+                    .get_or_init(|| format_description::parse("[hour]:[minute]:[second]").unwrap());
+                let time_str = time.to_offset(appearance.utc_offset).format(format).unwrap();
+                if stale_objects.contains(object_name) {
+                    ui.colored_label(appearance.replace_color, "●")
+                        .on_hover_text(format!("Source modified since last build ({time_str})"));
+                } else {
+                    ui.weak(time_str).on_hover_text("Last built");
+                }
+            }
+            clicked
+        })
+        .inner;
     // Always recreate ObjectConfig if selected, in case the project config changed.
     // ObjectConfig is compared using equality, so this won't unnecessarily trigger a rebuild.
     if selected || clicked {
@@ -395,6 +494,9 @@ fn display_object(
             reverse_fn_order: object.reverse_fn_order,
             complete: object.complete,
             scratch: object.scratch.clone(),
+            build_status: object.build_status.clone(),
+            make_args: object.make_args.clone(),
+            symbol_mappings: object.symbol_mappings.clone(),
         });
     }
 }
@@ -414,10 +516,12 @@ fn display_node(
     node: &ProjectObjectNode,
     appearance: &Appearance,
     node_open: NodeOpen,
+    build_times: &HashMap<String, OffsetDateTime>,
+    stale_objects: &HashSet<String>,
 ) {
     match node {
         ProjectObjectNode::File(name, object) => {
-            display_object(ui, selected_obj, name, object, appearance);
+            display_object(ui, selected_obj, name, object, appearance, build_times, stale_objects);
         }
         ProjectObjectNode::Dir(name, children) => {
             let contains_obj = selected_obj.as_ref().map(|path| contains_node(node, path));
@@ -443,7 +547,15 @@ fn display_node(
             .open(open)
             .show(ui, |ui| {
                 for node in children {
-                    display_node(ui, selected_obj, node, appearance, node_open);
+                    display_node(
+                        ui,
+                        selected_obj,
+                        node,
+                        appearance,
+                        node_open,
+                        build_times,
+                        stale_objects,
+                    );
                 }
             });
         }
@@ -470,7 +582,7 @@ fn filter_node(
             if (search.is_empty() || name.to_ascii_lowercase().contains(search))
                 && (!filter_diffable
                     || (object.base_path.is_some() && object.target_path.is_some()))
-                && (!filter_incomplete || matches!(object.complete, None | Some(false)))
+                && (!filter_incomplete || !object.is_complete())
             {
                 Some(node.clone())
             } else {
@@ -499,6 +611,9 @@ fn filter_node(
 
 const HELP_ICON: &str = "ℹ";
 
+/// How often source files are checked for changes since their objects were last built
+const STALE_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
 fn subheading(ui: &mut egui::Ui, text: &str, appearance: &Appearance) {
     ui.label(
         RichText::new(text).size(appearance.ui_font.size).color(appearance.emphasized_text_color),
@@ -564,6 +679,17 @@ pub fn project_window(
             state.load_error = None;
         }
     }
+
+    if let Some(error) = &state.watch_error {
+        let mut open = true;
+        egui::Window::new("File watcher error").open(&mut open).show(ctx, |ui| {
+            ui.label("Watching for changes failed, so rebuilds may be missed:");
+            ui.colored_label(appearance.delete_color, error);
+        });
+        if !open {
+            state.watch_error = None;
+        }
+    }
 }
 
 fn split_obj_config_ui(
@@ -601,6 +727,19 @@ fn split_obj_config_ui(
             FileDialogResult::ProjectDir,
         );
     }
+    ui.horizontal(|ui| {
+        egui::TextEdit::singleline(&mut state.project_url_text)
+            .hint_text("https://…/objdiff.json")
+            .ui(ui)
+            .on_hover_text("Load a project config from a URL. Objects are fetched relative to it.");
+        let url = state.project_url_text.trim();
+        if ui.add_enabled(is_remote_url(url), egui::Button::new("Open URL")).clicked() {
+            config.set_project_url(url.to_string());
+        }
+    });
+    if let Some(url) = &config.project_url {
+        ui.label(RichText::new(url).color(appearance.replace_color).family(FontFamily::Monospace));
+    }
     ui.separator();
 
     ui.horizontal(|ui| {
@@ -657,7 +796,47 @@ fn split_obj_config_ui(
                     );
                 }
             });
+        if config.selected_wsl_distro.is_some() {
+            ui.horizontal(|ui| {
+                ui.label("Drive mount prefix").on_hover_text(
+                    "Where Windows drives are mounted in WSL, as set by automount.root in \
+                     /etc/wsl.conf",
+                );
+                let mut prefix = config.wsl_mount_prefix.clone().unwrap_or_default();
+                if egui::TextEdit::singleline(&mut prefix)
+                    .hint_text(objdiff_core::build::DEFAULT_WSL_MOUNT_PREFIX)
+                    .ui(ui)
+                    .changed()
+                {
+                    config.wsl_mount_prefix = (!prefix.is_empty()).then_some(prefix);
+                }
+            });
+        }
     }
+    ui.horizontal(|ui| {
+        let mut enabled = config.max_parallel_builds.is_some();
+        let response = ui
+            .checkbox(&mut enabled, "Limit parallel builds to")
+            .on_hover_text("Further builds wait until one finishes. Defaults to one per CPU.");
+        if response.changed() {
+            config.max_parallel_builds = enabled.then(objdiff_core::build::default_parallel_builds);
+        }
+        if let Some(limit) = &mut config.max_parallel_builds {
+            ui.add(egui::DragValue::new(limit).clamp_range(1..=256));
+        }
+    });
+    ui.horizontal(|ui| {
+        let mut enabled = config.build_timeout.is_some();
+        let response = ui
+            .checkbox(&mut enabled, "Kill builds after")
+            .on_hover_text("Builds still running after this many seconds are stopped and fail.");
+        if response.changed() {
+            config.build_timeout = enabled.then_some(300);
+        }
+        if let Some(timeout) = &mut config.build_timeout {
+            ui.add(egui::DragValue::new(timeout).clamp_range(1..=86400).suffix(" s"));
+        }
+    });
     ui.separator();
 
     if let Some(project_dir) = config.project_dir.clone() {
@@ -775,6 +954,138 @@ fn split_obj_config_ui(
         ui.separator();
     }
 
+    ui.horizontal(|ui| {
+        subheading(ui, "Section order", appearance);
+        ui.link(HELP_ICON).on_hover_ui(|ui| {
+            let mut job = LayoutJob::default();
+            job.append(
+                "Comma-separated section name patterns, listed in this order.\n",
+                0.0,
+                text_format.clone(),
+            );
+            job.append("For example, ", 0.0, text_format.clone());
+            job.append(".text*, .rodata*, .data*, .bss*", 0.0, code_format.clone());
+            job.append(".\nOther sections follow in file order.", 0.0, text_format.clone());
+            ui.label(job);
+        });
+    });
+    let section_order_text = state.section_order_text.get_or_insert_with(|| {
+        config.section_order.iter().map(|g| g.glob()).collect::<Vec<_>>().join(", ")
+    });
+    if egui::TextEdit::singleline(section_order_text).hint_text("File order").ui(ui).lost_focus() {
+        let patterns = section_order_text
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(Glob::new)
+            .collect::<Result<Vec<_>, _>>();
+        match patterns {
+            Ok(patterns) => config.section_order = patterns,
+            Err(e) => log::error!("Invalid section pattern: {e}"),
+        }
+        state.section_order_text = None;
+    }
+    ui.separator();
+
+    ui.horizontal(|ui| {
+        subheading(ui, "Build log errors", appearance);
+        ui.link(HELP_ICON).on_hover_ui(|ui| {
+            let mut job = LayoutJob::default();
+            job.append(
+                "Build log lines containing the word \"error\" or \"warning\" are highlighted.\n",
+                0.0,
+                text_format.clone(),
+            );
+            job.append(
+                "Lines matching this regex are highlighted as errors too.",
+                0.0,
+                text_format.clone(),
+            );
+            ui.label(job);
+        });
+    });
+    egui::TextEdit::singleline(&mut config.build_log_error_pattern)
+        .hint_text("Regex, e.g. ^.*: E\\d+")
+        .ui(ui);
+    ui.separator();
+
+    let response = ui.horizontal(|ui| {
+        subheading(ui, "Build history", appearance);
+        ui.link(HELP_ICON).on_hover_ui(|ui| {
+            let mut job = LayoutJob::default();
+            job.append(
+                "Appends a JSON line with the match percentages to this file\n",
+                0.0,
+                text_format.clone(),
+            );
+            job.append("after each successful build.", 0.0, text_format.clone());
+            ui.label(job);
+        });
+        let response = ui.button("Select");
+        if config.build_history_path.is_some() && ui.button("Clear").clicked() {
+            config.build_history_path = None;
+        }
+        response
+    });
+    ui.label(format_path(&config.build_history_path, appearance));
+    if response.inner.clicked() {
+        state.file_dialog_state.queue(
+            || {
+                Box::pin(
+                    rfd::AsyncFileDialog::new()
+                        .add_filter("JSON Lines", &["jsonl"])
+                        .set_file_name("build_history.jsonl")
+                        .save_file(),
+                )
+            },
+            FileDialogResult::BuildHistory,
+        );
+    }
+    ui.separator();
+
+    let response = ui.horizontal(|ui| {
+        subheading(ui, "Completion sound", appearance);
+        ui.link(HELP_ICON).on_hover_ui(|ui| {
+            let mut job = LayoutJob::default();
+            job.append(
+                "Plays this sound when a build brings the selected symbol to 100%.\n",
+                0.0,
+                text_format.clone(),
+            );
+            job.append("Played at most once every 10 seconds.", 0.0, text_format.clone());
+            ui.label(job);
+        });
+        ui.button("Select")
+    });
+    ui.label(format_path(&config.completion_sound.path, appearance));
+    if response.inner.clicked() {
+        state.file_dialog_state.queue(
+            || {
+                Box::pin(
+                    rfd::AsyncFileDialog::new()
+                        .add_filter("Audio", &["wav", "ogg", "flac"])
+                        .pick_file(),
+                )
+            },
+            FileDialogResult::CompletionSound,
+        );
+    }
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut config.completion_sound.enabled, "Enabled");
+        ui.add(egui::Slider::new(&mut config.completion_sound.volume, 0.0..=1.0).text("Volume"));
+        if ui
+            .add_enabled(config.completion_sound.path.is_some(), egui::Button::new("Test"))
+            .clicked()
+        {
+            if let Some(path) = &config.completion_sound.path {
+                if let Err(e) = play_sound(path, config.completion_sound.volume) {
+                    log::warn!("{e:?}");
+                }
+            }
+        }
+    });
+    ui.separator();
+
     subheading(ui, "Watch settings", appearance);
     let response =
         ui.checkbox(&mut config.rebuild_on_changes, "Rebuild on changes").on_hover_ui(|ui| {
@@ -791,7 +1102,28 @@ fn split_obj_config_ui(
     };
 
     ui.horizontal(|ui| {
-        ui.label(RichText::new("File patterns").color(appearance.text_color));
+        let mut enabled = config.auto_refresh_interval.is_some();
+        let response = ui.checkbox(&mut enabled, "Refresh every").on_hover_ui(|ui| {
+            let mut job = LayoutJob::default();
+            job.append(
+                "Periodically re-run the build & diff, in case file changes are missed.\n",
+                0.0,
+                text_format.clone(),
+            );
+            job.append("Skipped while a build is running.", 0.0, text_format.clone());
+            ui.label(job);
+        });
+        if response.changed() {
+            config.auto_refresh_interval = enabled.then_some(30);
+        }
+        if let Some(interval) = &mut config.auto_refresh_interval {
+            ui.add(egui::DragValue::new(interval).clamp_range(1..=3600).suffix(" s"));
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("File patterns").color(appearance.text_color))
+            .on_hover_text("Patterns starting with ! exclude files matched by the others.");
         if ui
             .add_enabled(config.project_config_info.is_none(), egui::Button::new("Reset"))
             .on_disabled_hover_text(CONFIG_DISABLED_TEXT)
@@ -856,6 +1188,55 @@ pub fn arch_config_window(
 }
 
 fn arch_config_ui(ui: &mut egui::Ui, config: &mut AppConfig, _appearance: &Appearance) {
+    ui.heading("General");
+    ui.label("Ignored instructions").on_hover_text(
+        "Instructions removed from both sides before diffing, such as injected profiling calls. \
+         One per line: a mnemonic, optionally followed by its operands.",
+    );
+    // Keep the text as typed, and only apply it once editing is done
+    let id = ui.make_persistent_id("ignored_instructions");
+    let mut text = ui
+        .data(|d| d.get_temp::<String>(id))
+        .unwrap_or_else(|| config.diff_obj_config.ignored_instructions.join("\n"));
+    let response = egui::TextEdit::multiline(&mut text)
+        .desired_rows(2)
+        .hint_text("e.g. trap\nbl mcount")
+        .ui(ui);
+    if response.lost_focus() {
+        let rules: Vec<String> =
+            text.lines().map(str::trim).filter(|s| !s.is_empty()).map(str::to_string).collect();
+        if rules != config.diff_obj_config.ignored_instructions {
+            config.diff_obj_config.ignored_instructions = rules;
+            config.queue_reload = true;
+        }
+    }
+    ui.data_mut(|d| d.insert_temp(id, text));
+    egui::ComboBox::new("demangler", "Demangler")
+        .selected_text(config.diff_obj_config.demangler.get_message().unwrap())
+        .show_ui(ui, |ui| {
+            for &demangler in Demangler::VARIANTS {
+                if ui
+                    .selectable_label(
+                        config.diff_obj_config.demangler == demangler,
+                        demangler.get_message().unwrap(),
+                    )
+                    .on_hover_text(demangler.get_detailed_message().unwrap())
+                    .clicked()
+                {
+                    config.diff_obj_config.demangler = demangler;
+                    config.queue_reload = true;
+                }
+            }
+        });
+    ui.separator();
+    ui.heading("PowerPC");
+    let response = ui
+        .checkbox(&mut config.diff_obj_config.ppc_simplified_mnemonics, "Simplified mnemonics")
+        .on_hover_text("Display simplified mnemonics such as li instead of addi.");
+    if response.changed() {
+        config.queue_reload = true;
+    }
+    ui.separator();
     ui.heading("x86");
     egui::ComboBox::new("x86_formatter", "Format")
         .selected_text(config.diff_obj_config.x86_formatter.get_message().unwrap())
@@ -907,6 +1288,12 @@ fn arch_config_ui(ui: &mut egui::Ui, config: &mut AppConfig, _appearance: &Appea
                 }
             }
         });
+    let response = ui
+        .checkbox(&mut config.diff_obj_config.mips_pseudo_instructions, "Pseudo-instructions")
+        .on_hover_text("Display pseudo-instructions such as move instead of or.");
+    if response.changed() {
+        config.queue_reload = true;
+    }
     ui.separator();
     ui.heading("ARM");
     egui::ComboBox::new("arm_arch_version", "Architecture Version")
@@ -0,0 +1,183 @@
+use std::mem::take;
+
+use egui::{Align, Layout, RichText, TextStyle};
+use egui_extras::{Column, TableBuilder};
+
+use crate::{
+    app::AppConfigRef,
+    jobs::{
+        dashboard::{
+            rank_entries, start_dashboard, DashboardConfig, DashboardEntry, ProjectProgress,
+        },
+        Job, JobQueue, JobResult,
+    },
+    views::appearance::Appearance,
+};
+
+#[derive(Default, Copy, Clone, Eq, PartialEq)]
+pub enum DashboardSort {
+    #[default]
+    Remaining,
+    Size,
+    MatchPercent,
+    Object,
+    Symbol,
+}
+
+#[derive(Default)]
+pub struct DashboardViewState {
+    pub entries: Vec<DashboardEntry>,
+    pub sort: DashboardSort,
+    pub queue_refresh: bool,
+    pub running: bool,
+    /// Whether a scan was started since the project was loaded
+    pub loaded: bool,
+    /// Object and symbol name to open in the function diff view
+    pub open_symbol: Option<(String, String)>,
+    /// Project progress from the latest scan, handed to the diff view
+    pub progress: Option<ProjectProgress>,
+}
+
+impl DashboardViewState {
+    pub fn pre_update(&mut self, jobs: &mut JobQueue) {
+        jobs.results.retain_mut(|result| {
+            if let JobResult::Dashboard(result) = result {
+                if let Some(result) = take(result) {
+                    self.entries = result.entries;
+                    self.progress = Some(result.progress);
+                    self.sort_entries();
+                }
+                false
+            } else {
+                true
+            }
+        });
+        self.running = jobs.is_running(Job::Dashboard);
+    }
+
+    /// Rescans the project if the dashboard is open, or the next time it's opened.
+    pub fn invalidate(&mut self, open: bool) {
+        if open {
+            self.queue_refresh = true;
+        } else {
+            self.loaded = false;
+        }
+    }
+
+    pub fn post_update(&mut self, ctx: &egui::Context, jobs: &mut JobQueue, config: &AppConfigRef) {
+        if self.queue_refresh {
+            self.queue_refresh = false;
+            if let Ok(config) = config.read() {
+                if let Some(config) = DashboardConfig::from_config(&config) {
+                    jobs.push_once(Job::Dashboard, || start_dashboard(ctx, config));
+                }
+            }
+        }
+    }
+
+    fn sort_entries(&mut self) {
+        match self.sort {
+            DashboardSort::Remaining => rank_entries(&mut self.entries),
+            DashboardSort::Size => self.entries.sort_by_key(|e| std::cmp::Reverse(e.size)),
+            DashboardSort::MatchPercent => {
+                self.entries.sort_by(|a, b| a.match_percent.total_cmp(&b.match_percent))
+            }
+            DashboardSort::Object => self
+                .entries
+                .sort_by(|a, b| a.object.cmp(&b.object).then(a.symbol_name.cmp(&b.symbol_name))),
+            DashboardSort::Symbol => self.entries.sort_by(|a, b| {
+                let a_name = a.demangled_name.as_ref().unwrap_or(&a.symbol_name);
+                let b_name = b.demangled_name.as_ref().unwrap_or(&b.symbol_name);
+                a_name.cmp(b_name)
+            }),
+        }
+    }
+}
+
+pub fn dashboard_window(
+    ctx: &egui::Context,
+    show: &mut bool,
+    state: &mut DashboardViewState,
+    appearance: &Appearance,
+) {
+    if *show && !state.loaded {
+        state.loaded = true;
+        state.queue_refresh = true;
+    }
+    egui::Window::new("Unmatched functions").open(show).default_width(600.0).show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!state.running, egui::Button::new("Refresh")).clicked() {
+                state.queue_refresh = true;
+            }
+            if state.running {
+                ui.spinner();
+            } else {
+                let remaining: f32 = state.entries.iter().map(|e| e.remaining_bytes()).sum();
+                ui.label(format!(
+                    "{} functions, {:.0} bytes remaining",
+                    state.entries.len(),
+                    remaining
+                ));
+            }
+        });
+        ui.separator();
+
+        let mut sort = state.sort;
+        let row_height = ui.text_style_height(&TextStyle::Monospace);
+        TableBuilder::new(ui)
+            .striped(true)
+            .cell_layout(Layout::left_to_right(Align::Center))
+            .column(Column::auto().resizable(true))
+            .column(Column::remainder().clip(true))
+            .columns(Column::auto(), 3)
+            .header(row_height + 4.0, |mut header| {
+                for (label, kind) in [
+                    ("Object", DashboardSort::Object),
+                    ("Symbol", DashboardSort::Symbol),
+                    ("Size", DashboardSort::Size),
+                    ("Match", DashboardSort::MatchPercent),
+                    ("Remaining", DashboardSort::Remaining),
+                ] {
+                    header.col(|ui| {
+                        if ui
+                            .selectable_label(sort == kind, RichText::new(label).strong())
+                            .clicked()
+                        {
+                            sort = kind;
+                        }
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, state.entries.len(), |mut row| {
+                    let entry = &state.entries[row.index()];
+                    row.col(|ui| {
+                        ui.label(&entry.object);
+                    });
+                    row.col(|ui| {
+                        let name = entry.demangled_name.as_ref().unwrap_or(&entry.symbol_name);
+                        let text = RichText::new(name)
+                            .font(appearance.code_font.clone())
+                            .color(appearance.highlight_color);
+                        if ui.link(text).clicked() {
+                            state.open_symbol =
+                                Some((entry.object.clone(), entry.symbol_name.clone()));
+                        }
+                    });
+                    row.col(|ui| {
+                        ui.label(format!("{:#x}", entry.size));
+                    });
+                    row.col(|ui| {
+                        ui.label(format!("{:.0}%", entry.match_percent.floor()));
+                    });
+                    row.col(|ui| {
+                        ui.label(format!("{:.0}", entry.remaining_bytes()));
+                    });
+                });
+            });
+        if sort != state.sort {
+            state.sort = sort;
+            state.sort_entries();
+        }
+    });
+}
@@ -3,8 +3,11 @@ use std::{cmp::min, default::Default, mem::take};
 use egui::{text::LayoutJob, Align, Label, Layout, Sense, Vec2, Widget};
 use egui_extras::{Column, TableBuilder};
 use objdiff_core::{
-    diff::{ObjDataDiff, ObjDataDiffKind, ObjDiff},
-    obj::ObjInfo,
+    diff::{
+        data::{group_data_relocs, ObjDataRelocGroup},
+        ObjDataDiff, ObjDataDiffKind, ObjDiff, ObjSectionDiff,
+    },
+    obj::{ObjInfo, ObjReloc, ObjSection},
 };
 use time::format_description;
 
@@ -16,11 +19,79 @@ use crate::views::{
 
 const BYTES_PER_ROW: usize = 16;
 
+/// One side's data diff split into rows, with the relocation labels starting in each row.
+type DataRows = (Vec<Vec<ObjDataDiff>>, Vec<Vec<String>>);
+
+/// Data diff rows, built once per diff result and section.
+#[derive(Default)]
+pub struct DataDiffCache {
+    /// Section name and whether relocations are grouped
+    key: Option<(String, bool)>,
+    left: Option<DataRows>,
+    right: Option<DataRows>,
+}
+
 fn find_section(obj: &ObjInfo, selected_symbol: &SymbolRefByName) -> Option<usize> {
     obj.sections.iter().position(|section| section.name == selected_symbol.section_name)
 }
 
-fn data_row_ui(ui: &mut egui::Ui, address: usize, diffs: &[ObjDataDiff], appearance: &Appearance) {
+fn reloc_target_name(reloc: &ObjReloc) -> String {
+    let target = &reloc.target;
+    let name = target.demangled_name.as_ref().unwrap_or(&target.name);
+    match target.addend {
+        0 => name.clone(),
+        addend if addend < 0 => format!("{name}-{:#x}", -addend),
+        addend => format!("{name}+{addend:#x}"),
+    }
+}
+
+fn reloc_group_label(group: &ObjDataRelocGroup<'_>) -> String {
+    if let [reloc] = group.relocs.as_slice() {
+        return format!("→ {}", reloc_target_name(reloc));
+    }
+    let mut names = group.relocs.iter().take(3).map(|r| reloc_target_name(r)).collect::<Vec<_>>();
+    if group.relocs.len() > names.len() {
+        names.push("…".to_string());
+    }
+    format!("→ [{} × {:#x}] {}", group.relocs.len(), group.stride, names.join(", "))
+}
+
+/// Labels for the relocations starting in each row, by the row's offset into the section.
+fn reloc_annotations(
+    section: &ObjSection,
+    rows: &[Vec<ObjDataDiff>],
+    group_relocs: bool,
+) -> Vec<Vec<String>> {
+    let groups = if group_relocs {
+        group_data_relocs(&section.relocations)
+    } else {
+        let mut relocs = section.relocations.iter().collect::<Vec<_>>();
+        relocs.sort_by_key(|r| r.address);
+        relocs
+            .into_iter()
+            .map(|r| ObjDataRelocGroup { address: r.address, stride: 0, relocs: vec![r] })
+            .collect()
+    };
+    let mut annotations = vec![Vec::new(); rows.len()];
+    let mut groups = groups.iter().peekable();
+    let mut offset = 0u64;
+    for (row, row_annotations) in rows.iter().zip(&mut annotations) {
+        // Padding for the other side's insertions has no data
+        offset += row.iter().map(|d| d.data.len() as u64).sum::<u64>();
+        while let Some(group) = groups.next_if(|g| g.address < offset) {
+            row_annotations.push(reloc_group_label(group));
+        }
+    }
+    annotations
+}
+
+fn data_row_ui(
+    ui: &mut egui::Ui,
+    address: usize,
+    diffs: &[ObjDataDiff],
+    annotations: &[String],
+    appearance: &Appearance,
+) {
     if diffs.iter().any(|d| d.kind != ObjDataDiffKind::None) {
         ui.painter().rect_filled(ui.available_rect_before_wrap(), 0.0, ui.visuals().faint_bg_color);
     }
@@ -91,6 +162,14 @@ fn data_row_ui(ui: &mut egui::Ui, address: usize, diffs: &[ObjDataDiff], appeara
             write_text(text.as_str(), base_color, &mut job, appearance.code_font.clone());
         }
     }
+    for annotation in annotations {
+        write_text(
+            format!(" {annotation}").as_str(),
+            appearance.deemphasized_text_color,
+            &mut job,
+            appearance.code_font.clone(),
+        );
+    }
     Label::new(job).sense(Sense::click()).ui(ui);
     //     .on_hover_ui_at_pointer(|ui| ins_hover_ui(ui, ins))
     //     .context_menu(|ui| ins_context_menu(ui, ins));
@@ -136,6 +215,8 @@ fn data_table_ui(
     left_obj: Option<&(ObjInfo, ObjDiff)>,
     right_obj: Option<&(ObjInfo, ObjDiff)>,
     selected_symbol: &SymbolRefByName,
+    group_relocs: bool,
+    cache: &mut DataDiffCache,
     config: &Appearance,
 ) -> Option<()> {
     let left_section = left_obj.and_then(|(obj, diff)| {
@@ -156,21 +237,33 @@ fn data_table_ui(
     }
     let total_rows = (total_bytes - 1) / BYTES_PER_ROW + 1;
 
-    let left_diffs = left_section.map(|(_, section)| split_diffs(&section.data_diff));
-    let right_diffs = right_section.map(|(_, section)| split_diffs(&section.data_diff));
+    let key = (selected_symbol.section_name.clone(), group_relocs);
+    if cache.key.as_ref() != Some(&key) {
+        let data_rows = |(section, section_diff): (&ObjSection, &ObjSectionDiff)| {
+            let rows = split_diffs(&section_diff.data_diff);
+            let annotations = reloc_annotations(section, &rows, group_relocs);
+            (rows, annotations)
+        };
+        *cache = DataDiffCache {
+            key: Some(key),
+            left: left_section.map(data_rows),
+            right: right_section.map(data_rows),
+        };
+    }
+    let (left_diffs, right_diffs) = (&cache.left, &cache.right);
 
     table.body(|body| {
         body.rows(config.code_font.size, total_rows, |mut row| {
             let row_index = row.index();
             let address = row_index * BYTES_PER_ROW;
             row.col(|ui| {
-                if let Some(left_diffs) = &left_diffs {
-                    data_row_ui(ui, address, &left_diffs[row_index], config);
+                if let Some((rows, annotations)) = left_diffs {
+                    data_row_ui(ui, address, &rows[row_index], &annotations[row_index], config);
                 }
             });
             row.col(|ui| {
-                if let Some(right_diffs) = &right_diffs {
-                    data_row_ui(ui, address, &right_diffs[row_index], config);
+                if let Some((rows, annotations)) = right_diffs {
+                    data_row_ui(ui, address, &rows[row_index], &annotations[row_index], config);
                 }
             });
         });
@@ -198,9 +291,16 @@ pub fn data_diff_ui(ui: &mut egui::Ui, state: &mut DiffViewState, appearance: &A
                 |ui| {
                     ui.set_width(column_width);
 
-                    if ui.button("⏴ Back").clicked() {
-                        state.current_view = View::SymbolDiff;
-                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("⏴ Back").clicked() {
+                            state.current_view = View::SymbolDiff;
+                        }
+                        ui.checkbox(&mut state.group_data_relocs, "Group relocations")
+                            .on_hover_text(
+                                "Show evenly spaced relocations of the same kind, such as pointer \
+                                 tables, as one entry",
+                            );
+                    });
 
                     ui.scope(|ui| {
                         ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
@@ -272,6 +372,8 @@ pub fn data_diff_ui(ui: &mut egui::Ui, state: &mut DiffViewState, appearance: &A
         result.first_obj.as_ref(),
         result.second_obj.as_ref(),
         selected_symbol,
+        state.group_data_relocs,
+        &mut state.data_diff_cache,
         appearance,
     );
 }
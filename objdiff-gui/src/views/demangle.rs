@@ -5,6 +5,8 @@ use crate::views::appearance::Appearance;
 #[derive(Default)]
 pub struct DemangleViewState {
     pub text: String,
+    /// Last demangled text and its result
+    cached: Option<(String, Option<String>)>,
 }
 
 pub fn demangle_window(
@@ -16,7 +18,11 @@ pub fn demangle_window(
     egui::Window::new("Demangle").open(show).show(ctx, |ui| {
         ui.text_edit_singleline(&mut state.text);
         ui.add_space(10.0);
-        if let Some(demangled) = cwdemangle::demangle(&state.text, &Default::default()) {
+        if state.cached.as_ref().map_or(true, |(text, _)| *text != state.text) {
+            let demangled = cwdemangle::demangle(&state.text, &Default::default());
+            state.cached = Some((state.text.clone(), demangled));
+        }
+        if let Some(demangled) = state.cached.as_ref().and_then(|(_, d)| d.clone()) {
             ui.scope(|ui| {
                 ui.style_mut().override_text_style = Some(TextStyle::Monospace);
                 ui.colored_label(appearance.replace_color, &demangled);
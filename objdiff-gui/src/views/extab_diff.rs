@@ -8,20 +8,9 @@ use time::format_description;
 
 use crate::views::{
     appearance::Appearance,
-    symbol_diff::{match_color_for_symbol, DiffViewState, SymbolRefByName, View},
+    symbol_diff::{match_color_for_symbol, DiffViewState, View},
 };
 
-fn find_symbol(obj: &ObjInfo, selected_symbol: &SymbolRefByName) -> Option<SymbolRef> {
-    for (section_idx, section) in obj.sections.iter().enumerate() {
-        for (symbol_idx, symbol) in section.symbols.iter().enumerate() {
-            if symbol.name == selected_symbol.symbol_name {
-                return Some(SymbolRef { section_idx, symbol_idx });
-            }
-        }
-    }
-    None
-}
-
 fn decode_extab(extab: &ObjExtab) -> String {
     let mut text = String::from("");
 
@@ -76,7 +65,7 @@ fn extab_text_ui(
 fn extab_ui(
     ui: &mut Ui,
     obj: Option<&(ObjInfo, ObjDiff)>,
-    selected_symbol: &SymbolRefByName,
+    symbol: Option<SymbolRef>,
     appearance: &Appearance,
     _left: bool,
 ) {
@@ -85,8 +74,6 @@ fn extab_ui(
             ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
             ui.style_mut().wrap = Some(false);
 
-            let symbol = obj.and_then(|(obj, _)| find_symbol(obj, selected_symbol));
-
             if let (Some(object), Some(symbol_ref)) = (obj, symbol) {
                 extab_text_ui(ui, object, symbol_ref, appearance);
             }
@@ -99,6 +86,8 @@ pub fn extab_diff_ui(ui: &mut egui::Ui, state: &mut DiffViewState, appearance: &
     else {
         return;
     };
+    let (left_ref, right_ref) =
+        state.symbol_state.selected_refs(result.first_obj.as_ref(), result.second_obj.as_ref());
 
     // Header
     let available_width = ui.available_width();
@@ -180,12 +169,8 @@ pub fn extab_diff_ui(ui: &mut egui::Ui, state: &mut DiffViewState, appearance: &
                         if let Some(match_percent) = result
                             .second_obj
                             .as_ref()
-                            .and_then(|(obj, diff)| {
-                                find_symbol(obj, selected_symbol).map(|sref| {
-                                    &diff.sections[sref.section_idx].symbols[sref.symbol_idx]
-                                })
-                            })
-                            .and_then(|symbol| symbol.match_percent)
+                            .zip(right_ref)
+                            .and_then(|((_, diff), sref)| diff.symbol_diff(sref).match_percent)
                         {
                             ui.colored_label(
                                 match_color_for_symbol(match_percent, appearance),
@@ -207,10 +192,10 @@ pub fn extab_diff_ui(ui: &mut egui::Ui, state: &mut DiffViewState, appearance: &
         strip.strip(|builder| {
             builder.sizes(Size::remainder(), 2).horizontal(|mut strip| {
                 strip.cell(|ui| {
-                    extab_ui(ui, result.first_obj.as_ref(), selected_symbol, appearance, true);
+                    extab_ui(ui, result.first_obj.as_ref(), left_ref, appearance, true);
                 });
                 strip.cell(|ui| {
-                    extab_ui(ui, result.second_obj.as_ref(), selected_symbol, appearance, false);
+                    extab_ui(ui, result.second_obj.as_ref(), right_ref, appearance, false);
                 });
             });
         });
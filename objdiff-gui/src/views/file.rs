@@ -11,6 +11,8 @@ pub enum FileDialogResult {
     TargetDir(PathBuf),
     BaseDir(PathBuf),
     Object(PathBuf),
+    BuildHistory(PathBuf),
+    CompletionSound(PathBuf),
 }
 
 #[derive(Default)]
@@ -1,25 +1,45 @@
 use std::default::Default;
 
-use egui::{text::LayoutJob, Align, Label, Layout, Response, Sense, Vec2, Widget};
-use egui_extras::{Column, TableBuilder, TableRow};
+use egui::{
+    text::LayoutJob, Align, Label, Layout, Response, ScrollArea, Sense, Stroke, Vec2, Widget,
+};
 use objdiff_core::{
-    arch::ObjArch,
+    arch::{total_cost, ObjArch},
     diff::{
-        display::{display_diff, DiffText, HighlightKind},
+        code::instruction_components,
+        display::{display_diff, AddressMode, DiffText, HighlightKind},
+        text::write_unified_diff,
         ObjDiff, ObjInsDiff, ObjInsDiffKind,
     },
     obj::{ObjInfo, ObjIns, ObjInsArg, ObjInsArgValue, ObjSection, ObjSymbol, SymbolRef},
 };
 use time::format_description;
 
-use crate::views::{
-    appearance::Appearance,
-    symbol_diff::{match_color_for_symbol, DiffViewState, SymbolRefByName, View},
+use crate::{
+    html_export::{function_diff_html, HtmlDiffSide},
+    views::{
+        appearance::Appearance,
+        file::{FileDialogResult, FileDialogState},
+        symbol_diff::{match_color_for_symbol, DiffViewState, View},
+    },
 };
 
 #[derive(Default)]
 pub struct FunctionViewState {
     pub highlight: HighlightKind,
+    pub show_cycle_costs: bool,
+    pub show_inline_scopes: bool,
+    /// Replace branch arrows and in-function branch destinations with local labels
+    pub show_branch_labels: bool,
+    /// Show each instruction's encoding before its mnemonic
+    pub show_raw_bytes: bool,
+    pub address_mode: AddressMode,
+    /// Scroll both sides together vertically
+    pub link_scroll: bool,
+    /// Vertical offset shared by both sides while scrolling is linked
+    scroll_offset: f32,
+    /// Dialog choosing where to save the HTML export
+    pub export_dialog: FileDialogState,
 }
 
 fn ins_hover_ui(
@@ -34,11 +54,7 @@ fn ins_hover_ui(
         ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
         ui.style_mut().wrap = Some(false);
 
-        let offset = ins.address - section.address;
-        ui.label(format!(
-            "{:02x?}",
-            &section.data[offset as usize..(offset + ins.size as u64) as usize]
-        ));
+        ui.label(format!("{:02x?}", ins_bytes(section, ins)));
 
         if let Some(virtual_address) = symbol.virtual_address {
             let offset = ins.address - symbol.address;
@@ -51,6 +67,9 @@ fn ins_hover_ui(
         if let Some(orig) = &ins.orig {
             ui.label(format!("Original: {}", orig));
         }
+        if let Some(canonical) = &ins.canonical {
+            ui.label(format!("Compared as: {}", canonical));
+        }
 
         for arg in &ins.args {
             if let ObjInsArg::Arg(arg) = arg {
@@ -86,6 +105,30 @@ fn ins_hover_ui(
     });
 }
 
+/// Shows how a mismatched instruction differs from its counterpart, component by component.
+fn ins_components_ui(
+    ui: &mut egui::Ui,
+    left: &ObjInsDiff,
+    right: &ObjInsDiff,
+    appearance: &Appearance,
+) {
+    ui.separator();
+    egui::Grid::new("ins_components").num_columns(3).spacing([12.0, 2.0]).show(ui, |ui| {
+        ui.label("");
+        ui.label("Target");
+        ui.label("Base");
+        ui.end_row();
+        for component in instruction_components(left, right) {
+            let color =
+                if component.differs { appearance.replace_color } else { appearance.text_color };
+            ui.label(&component.name);
+            ui.colored_label(color, component.left.as_deref().unwrap_or("-"));
+            ui.colored_label(color, component.right.as_deref().unwrap_or("-"));
+            ui.end_row();
+        }
+    });
+}
+
 fn ins_context_menu(ui: &mut egui::Ui, section: &ObjSection, ins: &ObjIns, symbol: &ObjSymbol) {
     ui.scope(|ui| {
         ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
@@ -156,20 +199,18 @@ fn ins_context_menu(ui: &mut egui::Ui, section: &ObjSection, ins: &ObjIns, symbo
     });
 }
 
-fn find_symbol(obj: &ObjInfo, selected_symbol: &SymbolRefByName) -> Option<SymbolRef> {
-    for (section_idx, section) in obj.sections.iter().enumerate() {
-        for (symbol_idx, symbol) in section.symbols.iter().enumerate() {
-            if symbol.name == selected_symbol.symbol_name {
-                return Some(SymbolRef { section_idx, symbol_idx });
-            }
-        }
-    }
-    None
+fn symbol_cost(obj: Option<&(ObjInfo, ObjDiff)>, symbol_ref: Option<SymbolRef>) -> Option<u32> {
+    let (obj, diff) = obj?;
+    let symbol_ref = symbol_ref?;
+    let instructions = &diff.symbol_diff(symbol_ref).instructions;
+    total_cost(obj.arch.as_ref(), instructions.iter().filter_map(|d| d.ins.as_ref()))
 }
 
+#[allow(clippy::too_many_arguments)]
 fn diff_text_ui(
     ui: &mut egui::Ui,
     text: DiffText<'_>,
+    symbol: &ObjSymbol,
     ins_diff: &ObjInsDiff,
     appearance: &Appearance,
     ins_view_state: &mut FunctionViewState,
@@ -184,6 +225,7 @@ fn diff_text_ui(
         ObjInsDiffKind::Replace => appearance.replace_color,
         ObjInsDiffKind::Delete => appearance.delete_color,
         ObjInsDiffKind::Insert => appearance.insert_color,
+        ObjInsDiffKind::Moved => appearance.moved_color,
     };
     let mut pad_to = 0;
     match text {
@@ -217,15 +259,18 @@ fn diff_text_ui(
             }
         }
         DiffText::BranchDest(addr, diff) => {
-            label_text = format!("{addr:x}");
+            label_text = ins_view_state.address_mode.branch_dest_text(symbol, addr);
             if let Some(diff) = diff {
                 base_color = appearance.diff_colors[diff.idx % appearance.diff_colors.len()]
             }
         }
-        DiffText::Symbol(sym) => {
+        DiffText::Symbol(sym, diff) => {
             let name = sym.demangled_name.as_ref().unwrap_or(&sym.name);
             label_text = name.clone();
-            base_color = appearance.emphasized_text_color;
+            base_color = match diff {
+                Some(diff) => appearance.diff_colors[diff.idx % appearance.diff_colors.len()],
+                None => appearance.emphasized_text_color,
+            };
         }
         DiffText::Spacing(n) => {
             ui.add_space(n as f32 * space_width);
@@ -257,10 +302,78 @@ fn diff_text_ui(
     }
 }
 
+fn ins_bytes<'a>(section: &'a ObjSection, ins: &ObjIns) -> &'a [u8] {
+    let offset = (ins.address - section.address) as usize;
+    section.data.get(offset..offset + ins.size as usize).unwrap_or_default()
+}
+
+/// Instruction length that raw bytes are padded to, longer encodings overflow.
+const RAW_BYTES_WIDTH: usize = 4;
+
+/// Draws the instruction's encoding, coloring bytes that differ from the counterpart's. If
+/// both sides have the same bytes but disassemble differently, all bytes are flagged.
+fn raw_bytes_ui(
+    ui: &mut egui::Ui,
+    ins: &ObjIns,
+    bytes: &[u8],
+    counterpart: Option<(&ObjIns, &[u8])>,
+    appearance: &Appearance,
+) {
+    let decoder_mismatch = counterpart
+        .is_some_and(|(other, other_bytes)| other_bytes == bytes && other.mnemonic != ins.mnemonic);
+    let mut job = LayoutJob::default();
+    for (i, byte) in bytes.iter().enumerate() {
+        let differs = counterpart.is_some_and(|(_, other_bytes)| other_bytes.get(i) != Some(byte));
+        let color = if decoder_mismatch {
+            appearance.delete_color
+        } else if differs {
+            appearance.replace_color
+        } else {
+            appearance.deemphasized_text_color
+        };
+        let mut format = appearance.code_text_format(color, false);
+        if decoder_mismatch {
+            format.underline = Stroke::new(1.0, appearance.delete_color);
+        }
+        job.append(&format!("{byte:02x} "), 0.0, format);
+    }
+    job.append(
+        &"   ".repeat(RAW_BYTES_WIDTH.saturating_sub(bytes.len()) + 1),
+        0.0,
+        appearance.code_text_format(appearance.text_color, false),
+    );
+    let response = ui.label(job);
+    if decoder_mismatch {
+        response.on_hover_text("Both sides have the same bytes but disassemble differently");
+    }
+}
+
+/// Guides for the inlined function scopes enclosing an instruction, one per scope, naming the
+/// scopes that begin at it.
+pub fn inline_scope_marker(section: &ObjSection, address: u64) -> Option<String> {
+    let mut marker = String::new();
+    for range in section.inline_ranges.iter().filter(|r| (r.start..r.end).contains(&address)) {
+        if range.start == address {
+            marker.push_str(&format!("┌ inlined {} ", range.name));
+        } else {
+            marker.push_str("│ ");
+        }
+    }
+    (!marker.is_empty()).then_some(marker)
+}
+
+/// Local label for a branch target. Targets are numbered in order of their first branch, so
+/// labels match on both sides when the control flow does.
+fn branch_label(branch_idx: usize) -> String { format!(".L{branch_idx}") }
+
+#[allow(clippy::too_many_arguments)]
 fn asm_row_ui(
     ui: &mut egui::Ui,
-    ins_diff: &ObjInsDiff,
+    arch: &dyn ObjArch,
+    section: &ObjSection,
     symbol: &ObjSymbol,
+    ins_diff: &ObjInsDiff,
+    counterpart: Option<(&ObjIns, &[u8])>,
     appearance: &Appearance,
     ins_view_state: &mut FunctionViewState,
     response_cb: impl Fn(Response) -> Response,
@@ -270,92 +383,247 @@ fn asm_row_ui(
         ui.painter().rect_filled(ui.available_rect_before_wrap(), 0.0, ui.visuals().faint_bg_color);
     }
     let space_width = ui.fonts(|f| f.glyph_width(&appearance.code_font, ' '));
-    display_diff(ins_diff, symbol.address, |text| {
-        diff_text_ui(ui, text, ins_diff, appearance, ins_view_state, space_width, &response_cb);
+    let labels = ins_view_state.show_branch_labels;
+    let raw_bytes = ins_view_state.show_raw_bytes;
+    let mut seen_opcode = false;
+    let base_addr = ins_view_state.address_mode.base_address(section, symbol);
+    display_diff(ins_diff, base_addr, |text| {
+        let label: String;
+        let text = match text {
+            // Branch target marker before the opcode
+            DiffText::BasicColor(_, idx) if labels && !seen_opcode => {
+                label = format!(" {:<5}", format!("{}:", branch_label(idx)));
+                DiffText::BasicColor(&label, idx)
+            }
+            DiffText::Spacing(n) if labels && !seen_opcode => DiffText::Spacing(n + 2),
+            // Branch source marker after the operands, redundant with the label
+            DiffText::BasicColor(..) if labels => return Ok(()),
+            DiffText::BranchDest(..) if labels => match &ins_diff.branch_to {
+                Some(branch) => {
+                    label = branch_label(branch.branch_idx);
+                    DiffText::BasicColor(&label, branch.branch_idx)
+                }
+                None => text,
+            },
+            DiffText::Opcode(..) => {
+                seen_opcode = true;
+                if let Some(ins) = ins_diff.ins.as_ref().filter(|_| raw_bytes) {
+                    raw_bytes_ui(ui, ins, ins_bytes(section, ins), counterpart, appearance);
+                }
+                text
+            }
+            _ => text,
+        };
+        if matches!(text, DiffText::Eol) && ins_view_state.show_cycle_costs {
+            if let Some(cost) = ins_diff.ins.as_ref().and_then(|ins| arch.instruction_cost(ins)) {
+                ui.add_space(2.0 * space_width);
+                ui.label(LayoutJob::single_section(
+                    format!("; {cost}c"),
+                    appearance.code_text_format(appearance.deemphasized_text_color, false),
+                ));
+            }
+        }
+        if matches!(text, DiffText::Eol) && ins_view_state.show_inline_scopes {
+            if let Some(marker) =
+                ins_diff.ins.as_ref().and_then(|ins| inline_scope_marker(section, ins.address))
+            {
+                ui.add_space(2.0 * space_width);
+                ui.label(LayoutJob::single_section(
+                    marker,
+                    appearance.code_text_format(appearance.deemphasized_text_color, false),
+                ));
+            }
+        }
+        diff_text_ui(
+            ui,
+            text,
+            symbol,
+            ins_diff,
+            appearance,
+            ins_view_state,
+            space_width,
+            &response_cb,
+        );
         Ok::<_, ()>(())
     })
     .unwrap();
 }
 
+#[allow(clippy::too_many_arguments)]
 fn asm_col_ui(
-    row: &mut TableRow<'_, '_>,
+    ui: &mut egui::Ui,
+    row_idx: usize,
     obj: &(ObjInfo, ObjDiff),
     symbol_ref: SymbolRef,
+    counterpart: Option<(&ObjInsDiff, &ObjSection)>,
+    left: bool,
     appearance: &Appearance,
     ins_view_state: &mut FunctionViewState,
 ) {
     let (section, symbol) = obj.0.section_symbol(symbol_ref);
     let section = section.unwrap();
-    let ins_diff = &obj.1.symbol_diff(symbol_ref).instructions[row.index()];
+    let ins_diff = &obj.1.symbol_diff(symbol_ref).instructions[row_idx];
     let response_cb = |response: Response| {
         if let Some(ins) = &ins_diff.ins {
             response.context_menu(|ui| ins_context_menu(ui, section, ins, symbol));
             response.on_hover_ui_at_pointer(|ui| {
-                ins_hover_ui(ui, obj.0.arch.as_ref(), section, ins, symbol, appearance)
+                ins_hover_ui(ui, obj.0.arch.as_ref(), section, ins, symbol, appearance);
+                if let Some((counterpart, _)) =
+                    counterpart.filter(|_| ins_diff.kind != ObjInsDiffKind::None)
+                {
+                    let (l, r) =
+                        if left { (ins_diff, counterpart) } else { (counterpart, ins_diff) };
+                    ins_components_ui(ui, l, r, appearance);
+                }
             })
         } else {
             response
         }
     };
-    let (_, response) = row.col(|ui| {
-        asm_row_ui(ui, ins_diff, symbol, appearance, ins_view_state, response_cb);
-    });
+    let row_size = Vec2 { x: ui.available_width(), y: appearance.code_font.size };
+    let response = ui
+        .allocate_ui_with_layout(row_size, Layout::left_to_right(Align::Min), |ui| {
+            ui.set_min_size(row_size);
+            asm_row_ui(
+                ui,
+                obj.0.arch.as_ref(),
+                section,
+                symbol,
+                ins_diff,
+                counterpart.and_then(|(diff, section)| {
+                    diff.ins.as_ref().map(|ins| (ins, ins_bytes(section, ins)))
+                }),
+                appearance,
+                ins_view_state,
+                response_cb,
+            );
+        })
+        .response;
     response_cb(response);
 }
 
-fn empty_col_ui(row: &mut TableRow<'_, '_>) {
-    row.col(|ui| {
-        ui.label("");
-    });
+type SideDiff<'a> = (Option<&'a (ObjInfo, ObjDiff)>, Option<SymbolRef>);
+
+/// One side of the function diff, scrolling horizontally on its own and vertically together
+/// with the other side while [`FunctionViewState::link_scroll`] is set.
+fn asm_side_ui<'a>(
+    ui: &mut egui::Ui,
+    (obj, symbol_ref): SideDiff<'a>,
+    (other_obj, other_symbol_ref): SideDiff<'a>,
+    instructions_len: usize,
+    left: bool,
+    appearance: &Appearance,
+    ins_view_state: &mut FunctionViewState,
+) {
+    let mut scroll_area = ScrollArea::both()
+        .id_source(if left { "asm_left" } else { "asm_right" })
+        .auto_shrink([false, false]);
+    if ins_view_state.link_scroll {
+        scroll_area = scroll_area.vertical_scroll_offset(ins_view_state.scroll_offset);
+    }
+    let output =
+        scroll_area.show_rows(ui, appearance.code_font.size, instructions_len, |ui, rows| {
+            let (Some(obj), Some(symbol_ref)) = (obj, symbol_ref) else {
+                return;
+            };
+            for idx in rows {
+                let counterpart =
+                    other_obj.zip(other_symbol_ref).and_then(|((obj, diff), symbol_ref)| {
+                        let section = obj.section_symbol(symbol_ref).0?;
+                        Some((&diff.symbol_diff(symbol_ref).instructions[idx], section))
+                    });
+                asm_col_ui(ui, idx, obj, symbol_ref, counterpart, left, appearance, ins_view_state);
+            }
+        });
+    // Both sides have the same number of rows, but may differ in height when one scrolls
+    // horizontally, which shouldn't pull the other one back
+    let max_offset = (output.content_size.y - output.inner_rect.height()).max(0.0);
+    let offset = output.state.offset.y;
+    if ins_view_state.link_scroll
+        && (offset - ins_view_state.scroll_offset.min(max_offset)).abs() > 0.5
+    {
+        // Scrolled by the user, so move the other side to match
+        ins_view_state.scroll_offset = offset;
+        ui.ctx().request_repaint();
+    }
 }
 
 fn asm_table_ui(
-    table: TableBuilder<'_>,
-    left_obj: Option<&(ObjInfo, ObjDiff)>,
-    right_obj: Option<&(ObjInfo, ObjDiff)>,
-    selected_symbol: &SymbolRefByName,
+    ui: &mut egui::Ui,
+    left: SideDiff<'_>,
+    right: SideDiff<'_>,
+    column_width: f32,
     appearance: &Appearance,
     ins_view_state: &mut FunctionViewState,
 ) -> Option<()> {
-    let left_symbol = left_obj.and_then(|(obj, _)| find_symbol(obj, selected_symbol));
-    let right_symbol = right_obj.and_then(|(obj, _)| find_symbol(obj, selected_symbol));
-    let instructions_len = match (left_symbol, right_symbol) {
-        (Some(left_symbol_ref), Some(right_symbol_ref)) => {
-            let left_len = left_obj.unwrap().1.symbol_diff(left_symbol_ref).instructions.len();
-            let right_len = right_obj.unwrap().1.symbol_diff(right_symbol_ref).instructions.len();
+    let symbol_len =
+        |(obj, symbol_ref): SideDiff<'_>| Some(obj?.1.symbol_diff(symbol_ref?).instructions.len());
+    let instructions_len = match (symbol_len(left), symbol_len(right)) {
+        (Some(left_len), Some(right_len)) => {
             debug_assert_eq!(left_len, right_len);
             left_len
         }
-        (Some(left_symbol_ref), None) => {
-            left_obj.unwrap().1.symbol_diff(left_symbol_ref).instructions.len()
-        }
-        (None, Some(right_symbol_ref)) => {
-            right_obj.unwrap().1.symbol_diff(right_symbol_ref).instructions.len()
-        }
+        (Some(len), None) | (None, Some(len)) => len,
         (None, None) => return None,
     };
-    table.body(|body| {
-        body.rows(appearance.code_font.size, instructions_len, |mut row| {
-            if let (Some(left_obj), Some(left_symbol_ref)) = (left_obj, left_symbol) {
-                asm_col_ui(&mut row, left_obj, left_symbol_ref, appearance, ins_view_state);
-            } else {
-                empty_col_ui(&mut row);
-            }
-            if let (Some(right_obj), Some(right_symbol_ref)) = (right_obj, right_symbol) {
-                asm_col_ui(&mut row, right_obj, right_symbol_ref, appearance, ins_view_state);
-            } else {
-                empty_col_ui(&mut row);
-            }
-        });
+    let height = ui.available_height();
+    ui.horizontal_top(|ui| {
+        ui.spacing_mut().item_spacing.x = 0.0;
+        for (side, other, is_left) in [(left, right, true), (right, left, false)] {
+            ui.allocate_ui_with_layout(
+                Vec2 { x: column_width, y: height },
+                Layout::top_down(Align::Min),
+                |ui| {
+                    ui.set_width(column_width);
+                    asm_side_ui(
+                        ui,
+                        side,
+                        other,
+                        instructions_len,
+                        is_left,
+                        appearance,
+                        ins_view_state,
+                    );
+                },
+            );
+        }
     });
     Some(())
 }
 
+/// Matching instructions kept around each change in copied diffs.
+const DIFF_CONTEXT: usize = 3;
+
+/// Arguments and symbol names longer than this are cut short in copied diffs.
+const MAX_ARG_LEN: usize = 48;
+
+fn html_diff_side(
+    obj: Option<&(ObjInfo, ObjDiff)>,
+    symbol_ref: Option<SymbolRef>,
+    address_mode: AddressMode,
+) -> Option<HtmlDiffSide<'_>> {
+    let (obj, diff) = obj?;
+    let symbol_ref = symbol_ref?;
+    let (section, symbol) = obj.section_symbol(symbol_ref);
+    Some(HtmlDiffSide {
+        arch: obj.arch.as_ref(),
+        section,
+        symbol,
+        diff: diff.symbol_diff(symbol_ref),
+        base_addr: section.map(|s| address_mode.base_address(s, symbol)).unwrap_or(symbol.address),
+        address_mode,
+    })
+}
+
 pub fn function_diff_ui(ui: &mut egui::Ui, state: &mut DiffViewState, appearance: &Appearance) {
+    // The export is written by the dialog's thread
+    state.function_state.export_dialog.poll();
     let (Some(result), Some(selected_symbol)) = (&state.build, &state.symbol_state.selected_symbol)
     else {
         return;
     };
+    let (left_ref, right_ref) =
+        state.symbol_state.selected_refs(result.first_obj.as_ref(), result.second_obj.as_ref());
 
     // Header
     let available_width = ui.available_width();
@@ -378,15 +646,94 @@ pub fn function_diff_ui(ui: &mut egui::Ui, state: &mut DiffViewState, appearance
                         ui.separator();
                         if ui
                             .add_enabled(
-                                !state.scratch_running && state.scratch_available,
+                                !state.scratch_running && state.scratch_unavailable.is_none(),
                                 egui::Button::new("📲 decomp.me"),
                             )
                             .on_hover_text_at_pointer("Create a new scratch on decomp.me (beta)")
-                            .on_disabled_hover_text("Scratch configuration missing")
+                            .on_disabled_hover_text(
+                                state.scratch_unavailable.unwrap_or("Creating scratch…"),
+                            )
                             .clicked()
                         {
                             state.queue_scratch = true;
                         }
+                        if ui
+                            .add_enabled(
+                                !state.fetch_scratch_running && state.scratch_slug.is_some(),
+                                egui::Button::new("📥 Load scratch"),
+                            )
+                            .on_hover_text_at_pointer(
+                                "Diff against the scratch's current state on decomp.me",
+                            )
+                            .on_disabled_hover_text("No scratch created for this function")
+                            .clicked()
+                        {
+                            state.queue_fetch_scratch = true;
+                        }
+                        let address_mode = state.function_state.address_mode;
+                        let html = || {
+                            function_diff_html(
+                                html_diff_side(result.first_obj.as_ref(), left_ref, address_mode),
+                                html_diff_side(result.second_obj.as_ref(), right_ref, address_mode),
+                                &state.function_state,
+                                appearance,
+                            )
+                        };
+                        if ui
+                            .button("📋 Copy HTML")
+                            .on_hover_text_at_pointer("Copy the diff as a standalone HTML page")
+                            .clicked()
+                        {
+                            let html = html();
+                            ui.output_mut(|output| output.copied_text = html);
+                        }
+                        if ui
+                            .button("💾 Save HTML…")
+                            .on_hover_text_at_pointer("Save the diff as a standalone HTML page")
+                            .clicked()
+                        {
+                            let html = html();
+                            let file_name = format!("{}.html", selected_symbol.symbol_name);
+                            state.function_state.export_dialog.queue(
+                                || {
+                                    Box::pin(
+                                        rfd::AsyncFileDialog::new()
+                                            .add_filter("HTML", &["html"])
+                                            .set_file_name(file_name)
+                                            .save_file(),
+                                    )
+                                },
+                                move |path| {
+                                    if let Err(e) = std::fs::write(&path, html) {
+                                        log::error!("Failed to write {}: {e}", path.display());
+                                    }
+                                    FileDialogResult::None
+                                },
+                            );
+                        }
+                        if ui
+                            .button("📋 Copy diff")
+                            .on_hover_text_at_pointer(
+                                "Copy the changes as a unified diff in a Markdown code block",
+                            )
+                            .clicked()
+                        {
+                            let side = |obj, symbol_ref| {
+                                html_diff_side(obj, symbol_ref, AddressMode::default())
+                                    .map(|side| (side.symbol, side.diff))
+                            };
+                            let mut text = "```diff\n".to_string();
+                            write_unified_diff(
+                                &mut text,
+                                side(result.first_obj.as_ref(), left_ref),
+                                side(result.second_obj.as_ref(), right_ref),
+                                Some(DIFF_CONTEXT),
+                                Some(MAX_ARG_LEN),
+                            )
+                            .unwrap();
+                            text.push_str("```\n");
+                            ui.output_mut(|output| output.copied_text = text);
+                        }
                     });
 
                     let name = selected_symbol
@@ -406,6 +753,11 @@ pub fn function_diff_ui(ui: &mut egui::Ui, state: &mut DiffViewState, appearance
                     ui.scope(|ui| {
                         ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
                         ui.label("Diff target:");
+                        if state.function_state.show_cycle_costs {
+                            if let Some(cost) = symbol_cost(result.first_obj.as_ref(), left_ref) {
+                                ui.label(format!("Cycles: {cost}"));
+                            }
+                        }
                     });
                 },
             );
@@ -449,12 +801,8 @@ pub fn function_diff_ui(ui: &mut egui::Ui, state: &mut DiffViewState, appearance
                         if let Some(match_percent) = result
                             .second_obj
                             .as_ref()
-                            .and_then(|(obj, diff)| {
-                                find_symbol(obj, selected_symbol).map(|sref| {
-                                    &diff.sections[sref.section_idx].symbols[sref.symbol_idx]
-                                })
-                            })
-                            .and_then(|symbol| symbol.match_percent)
+                            .zip(right_ref)
+                            .and_then(|((_, diff), sref)| diff.symbol_diff(sref).match_percent)
                         {
                             ui.colored_label(
                                 match_color_for_symbol(match_percent, appearance),
@@ -463,7 +811,18 @@ pub fn function_diff_ui(ui: &mut egui::Ui, state: &mut DiffViewState, appearance
                         } else {
                             ui.colored_label(appearance.replace_color, "Missing");
                         }
-                        ui.label("Diff base:");
+                        match &state.symbol_state.base_symbol {
+                            Some(base) => ui.label(format!(
+                                "Diff base: {}",
+                                base.demangled_symbol_name.as_deref().unwrap_or(&base.symbol_name)
+                            )),
+                            None => ui.label("Diff base:"),
+                        };
+                        if state.function_state.show_cycle_costs {
+                            if let Some(cost) = symbol_cost(result.second_obj.as_ref(), right_ref) {
+                                ui.label(format!("Cycles: {cost}"));
+                            }
+                        }
                     });
                 },
             );
@@ -473,19 +832,11 @@ pub fn function_diff_ui(ui: &mut egui::Ui, state: &mut DiffViewState, appearance
 
     // Table
     ui.style_mut().interaction.selectable_labels = false;
-    let available_height = ui.available_height();
-    let table = TableBuilder::new(ui)
-        .striped(false)
-        .cell_layout(Layout::left_to_right(Align::Min))
-        .columns(Column::exact(column_width).clip(true), 2)
-        .resizable(false)
-        .auto_shrink([false, false])
-        .min_scrolled_height(available_height);
     asm_table_ui(
-        table,
-        result.first_obj.as_ref(),
-        result.second_obj.as_ref(),
-        selected_symbol,
+        ui,
+        (result.first_obj.as_ref(), left_ref),
+        (result.second_obj.as_ref(), right_ref),
+        column_width,
         appearance,
         &mut state.function_state,
     );
@@ -1,6 +1,9 @@
-use egui::{ProgressBar, RichText, Widget};
+use egui::{text::LayoutJob, CollapsingHeader, ProgressBar, RichText, ScrollArea, Widget};
 
-use crate::{jobs::JobQueue, views::appearance::Appearance};
+use crate::{
+    jobs::JobQueue,
+    views::{appearance::Appearance, write_text},
+};
 
 pub fn jobs_ui(ui: &mut egui::Ui, jobs: &mut JobQueue, appearance: &Appearance) {
     ui.label("Jobs");
@@ -49,6 +52,22 @@ pub fn jobs_ui(ui: &mut egui::Ui, jobs: &mut JobQueue, appearance: &Appearance)
                 })
                 .on_hover_text_at_pointer(&status.status);
             }
+            if job.handle.is_some() && !status.log.is_empty() {
+                CollapsingHeader::new("Output").id_source(job.id).show(ui, |ui| {
+                    ScrollArea::vertical().max_height(200.0).stick_to_bottom(true).show(ui, |ui| {
+                        let mut text = LayoutJob::default();
+                        for line in &status.log {
+                            let color = if line.stderr {
+                                appearance.replace_color
+                            } else {
+                                appearance.text_color
+                            };
+                            write_text(&line.text, color, &mut text, appearance.code_font.clone());
+                        }
+                        ui.label(text);
+                    });
+                });
+            }
         });
     }
 
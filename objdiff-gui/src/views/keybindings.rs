@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+
+use egui::{Key, KeyboardShortcut, Modifiers};
+use strum::{EnumMessage, VariantArray};
+
+use crate::views::appearance::Appearance;
+
+/// Actions that can be bound to keyboard shortcuts.
+#[derive(
+    Debug,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+)]
+pub enum KeyAction {
+    #[strum(message = "Show keyboard shortcuts")]
+    ToggleHelp,
+    #[strum(message = "Build and diff")]
+    Build,
+    #[strum(message = "Back to the symbol list")]
+    Back,
+    #[strum(message = "Show unmatched functions")]
+    ToggleDashboard,
+    #[strum(message = "Next unmatched function")]
+    NextUnmatched,
+    #[strum(message = "Previous unmatched function")]
+    PrevUnmatched,
+    #[strum(message = "Increase code font size")]
+    IncreaseFontSize,
+    #[strum(message = "Decrease code font size")]
+    DecreaseFontSize,
+}
+
+impl KeyAction {
+    fn default_shortcuts(self) -> Vec<KeyboardShortcut> {
+        match self {
+            KeyAction::ToggleHelp => vec![
+                KeyboardShortcut::new(Modifiers::NONE, Key::F1),
+                KeyboardShortcut::new(Modifiers::NONE, Key::Questionmark),
+            ],
+            KeyAction::Build => vec![KeyboardShortcut::new(Modifiers::NONE, Key::F5)],
+            KeyAction::Back => vec![KeyboardShortcut::new(Modifiers::NONE, Key::Escape)],
+            KeyAction::ToggleDashboard => {
+                vec![KeyboardShortcut::new(Modifiers::COMMAND, Key::U)]
+            }
+            KeyAction::NextUnmatched => vec![KeyboardShortcut::new(Modifiers::NONE, Key::N)],
+            KeyAction::PrevUnmatched => vec![KeyboardShortcut::new(Modifiers::SHIFT, Key::N)],
+            KeyAction::IncreaseFontSize => vec![
+                KeyboardShortcut::new(Modifiers::COMMAND, Key::Plus),
+                KeyboardShortcut::new(Modifiers::COMMAND, Key::Equals),
+            ],
+            KeyAction::DecreaseFontSize => {
+                vec![KeyboardShortcut::new(Modifiers::COMMAND, Key::Minus)]
+            }
+        }
+    }
+}
+
+/// Keyboard shortcuts for every action. An empty list unbinds the action.
+///
+/// Actions missing from a saved config get their default shortcuts when it's loaded.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(from = "BTreeMap<KeyAction, Vec<KeyboardShortcut>>")]
+pub struct Keybindings(BTreeMap<KeyAction, Vec<KeyboardShortcut>>);
+
+impl Default for Keybindings {
+    fn default() -> Self { Self::from(BTreeMap::new()) }
+}
+
+impl From<BTreeMap<KeyAction, Vec<KeyboardShortcut>>> for Keybindings {
+    fn from(mut bindings: BTreeMap<KeyAction, Vec<KeyboardShortcut>>) -> Self {
+        for &action in KeyAction::VARIANTS {
+            bindings.entry(action).or_insert_with(|| action.default_shortcuts());
+        }
+        Self(bindings)
+    }
+}
+
+impl Keybindings {
+    pub fn shortcuts(&self, action: KeyAction) -> &[KeyboardShortcut] {
+        self.0.get(&action).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Consumes the shortcuts pressed this frame for the actions that are `enabled`, returning
+    /// those actions. Shortcuts that would type text, and Escape, are left alone while a text
+    /// field has focus, and Escape also while a popup is open.
+    pub fn pressed(
+        &self,
+        ctx: &egui::Context,
+        enabled: impl Fn(KeyAction) -> bool,
+    ) -> Vec<KeyAction> {
+        let typing = ctx.wants_keyboard_input();
+        let popup_open = ctx.memory(|m| m.any_popup_open());
+        let mut shortcuts = KeyAction::VARIANTS
+            .iter()
+            .filter(|&&action| enabled(action))
+            .flat_map(|&action| self.shortcuts(action).iter().map(move |&s| (action, s)))
+            .filter(|(_, shortcut)| {
+                let escape = shortcut.logical_key == Key::Escape;
+                !(typing && (escape || types_text(shortcut))) && !(popup_open && escape)
+            })
+            .collect::<Vec<_>>();
+        // egui ignores extra Shift and Alt when matching, so e.g. Shift+N has to be tried
+        // before N
+        shortcuts.sort_by_key(|(_, s)| {
+            let m = s.modifiers;
+            std::cmp::Reverse(m.alt as u8 + m.shift as u8 + m.command as u8)
+        });
+        let mut actions = vec![];
+        for (action, shortcut) in shortcuts {
+            if !actions.contains(&action) && ctx.input_mut(|i| i.consume_shortcut(&shortcut)) {
+                actions.push(action);
+            }
+        }
+        actions
+    }
+}
+
+fn types_text(shortcut: &KeyboardShortcut) -> bool {
+    let function_key = matches!(
+        shortcut.logical_key,
+        Key::F1
+            | Key::F2
+            | Key::F3
+            | Key::F4
+            | Key::F5
+            | Key::F6
+            | Key::F7
+            | Key::F8
+            | Key::F9
+            | Key::F10
+            | Key::F11
+            | Key::F12
+    );
+    !function_key && !shortcut.modifiers.command && !shortcut.modifiers.alt
+}
+
+pub fn keybindings_window(
+    ctx: &egui::Context,
+    show: &mut bool,
+    keybindings: &Keybindings,
+    appearance: &Appearance,
+) {
+    egui::Window::new("Keyboard shortcuts").open(show).resizable(false).show(ctx, |ui| {
+        egui::Grid::new("keybindings").num_columns(2).spacing([20.0, 4.0]).show(ui, |ui| {
+            for &action in KeyAction::VARIANTS {
+                ui.label(action.get_message().unwrap());
+                let shortcuts = keybindings.shortcuts(action);
+                if shortcuts.is_empty() {
+                    ui.colored_label(appearance.deemphasized_text_color, "Unbound");
+                } else {
+                    let text = shortcuts
+                        .iter()
+                        .map(|s| ctx.format_shortcut(s))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    ui.colored_label(appearance.emphasized_text_color, text);
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
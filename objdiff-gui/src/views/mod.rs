@@ -2,6 +2,7 @@ use egui::{text::LayoutJob, Color32, FontId, TextFormat};
 
 pub(crate) mod appearance;
 pub(crate) mod config;
+pub(crate) mod dashboard;
 pub(crate) mod data_diff;
 pub(crate) mod debug;
 pub(crate) mod demangle;
@@ -11,6 +12,7 @@ pub(crate) mod frame_history;
 pub(crate) mod function_diff;
 pub(crate) mod graphics;
 pub(crate) mod jobs;
+pub(crate) mod keybindings;
 pub(crate) mod rlwinm;
 pub(crate) mod symbol_diff;
 
@@ -1,34 +1,135 @@
-use std::mem::take;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    mem::take,
+    time::{Duration, Instant},
+};
 
 use egui::{
     text::LayoutJob, Align, CollapsingHeader, Color32, Id, Layout, OpenUrl, ScrollArea,
     SelectableLabel, TextEdit, Ui, Vec2, Widget,
 };
 use egui_extras::{Size, StripBuilder};
+use globset::{Glob, GlobMatcher};
 use objdiff_core::{
-    diff::{ObjDiff, ObjSymbolDiff},
-    obj::{ObjInfo, ObjSection, ObjSectionKind, ObjSymbol, ObjSymbolFlags, SymbolRef},
+    build::BuildStatus,
+    diff::{
+        diff_pending_symbol, DiffObjConfig, ObjDiff, ObjSectionDiff, ObjSymbolDiff, PairConfidence,
+    },
+    obj::{
+        ObjInfo, ObjSection, ObjSectionKind, ObjSymbol, ObjSymbolFlagSet, ObjSymbolFlags,
+        SymbolIdentity, SymbolIdentityPolicy, SymbolRef,
+    },
 };
 use regex::{Regex, RegexBuilder};
+use strum::{EnumMessage, VariantArray};
 
 use crate::{
     app::AppConfigRef,
+    build_history::append_build_record,
+    deeplink::DeepLink,
     jobs::{
         create_scratch::{start_create_scratch, CreateScratchConfig, CreateScratchResult},
-        objdiff::{BuildStatus, ObjDiffResult},
+        dashboard::{CodeProgress, ProjectProgress},
+        fetch_scratch::{start_fetch_scratch, FetchScratchConfig},
+        objdiff::ObjDiffResult,
         Job, JobQueue, JobResult,
     },
-    views::{appearance::Appearance, function_diff::FunctionViewState, write_text},
+    snapshot::{list_snapshots, save_snapshot},
+    sound::{play_sound, CompletionSoundConfig, CompletionSoundTrigger},
+    views::{
+        appearance::Appearance, data_diff::DataDiffCache, function_diff::FunctionViewState,
+        write_text,
+    },
 };
 
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
 pub struct SymbolRefByName {
     pub symbol_name: String,
     pub demangled_symbol_name: Option<String>,
     pub section_name: String,
+    /// Offset within the section, if known
+    pub section_address: Option<u64>,
+}
+
+impl SymbolRefByName {
+    pub fn new(symbol: &ObjSymbol, section: &ObjSection) -> Self {
+        Self {
+            symbol_name: symbol.name.clone(),
+            demangled_symbol_name: symbol.demangled_name.clone(),
+            section_name: section.name.clone(),
+            section_address: Some(symbol.section_address),
+        }
+    }
+
+    /// Resolves the symbol in `obj` using `policy`, falling back to the mangled name when the
+    /// policy's identity can't be matched.
+    pub fn find(&self, obj: &ObjInfo, policy: SymbolIdentityPolicy) -> Option<SymbolRef> {
+        let (policy, identity) = match (policy, self.section_address) {
+            (SymbolIdentityPolicy::ByName, _) => (
+                policy,
+                SymbolIdentity::Name(
+                    self.demangled_symbol_name.as_ref().unwrap_or(&self.symbol_name).clone(),
+                ),
+            ),
+            (SymbolIdentityPolicy::BySectionOffset, Some(offset)) => {
+                (policy, SymbolIdentity::SectionOffset(self.section_name.clone(), offset))
+            }
+            _ => (
+                SymbolIdentityPolicy::ByMangledName,
+                SymbolIdentity::MangledName(self.symbol_name.clone()),
+            ),
+        };
+        obj.find_symbol(policy, &identity).or_else(|| {
+            obj.find_symbol(
+                SymbolIdentityPolicy::ByMangledName,
+                &SymbolIdentity::MangledName(self.symbol_name.clone()),
+            )
+        })
+    }
+
+    /// Resolves the symbol on both sides. A side where it can't be found follows the other
+    /// side's pairing instead, for symbols named differently on each side (e.g. mapped ones).
+    ///
+    /// Section offsets only identify the symbol on the side they were taken from, so with
+    /// [`SymbolIdentityPolicy::BySectionOffset`] one side is resolved and the other always
+    /// follows its pairing.
+    pub fn find_pair(
+        &self,
+        left: Option<&(ObjInfo, ObjDiff)>,
+        right: Option<&(ObjInfo, ObjDiff)>,
+        policy: SymbolIdentityPolicy,
+    ) -> (Option<SymbolRef>, Option<SymbolRef>) {
+        let paired = |side: Option<&(ObjInfo, ObjDiff)>, symbol_ref: Option<SymbolRef>| {
+            side.zip(symbol_ref).and_then(|((_, diff), r)| diff.symbol_diff(r).diff_symbol)
+        };
+        if policy == SymbolIdentityPolicy::BySectionOffset {
+            // The side the offset came from also has the symbol's name there
+            let resolve = |side: Option<&(ObjInfo, ObjDiff)>| {
+                side.and_then(|(obj, _)| {
+                    self.find(obj, policy)
+                        .filter(|&r| obj.section_symbol(r).1.name == self.symbol_name)
+                })
+            };
+            if let Some(left_ref) = resolve(left) {
+                return (Some(left_ref), paired(left, Some(left_ref)));
+            }
+            if let Some(right_ref) = resolve(right) {
+                return (paired(right, Some(right_ref)), Some(right_ref));
+            }
+        }
+        let left_ref = left.and_then(|(obj, _)| self.find(obj, policy));
+        let right_ref = right.and_then(|(obj, _)| self.find(obj, policy));
+        match (left_ref, right_ref) {
+            (Some(_), None) if right.is_some() => (left_ref, paired(left, left_ref)),
+            (None, Some(_)) if left.is_some() => (paired(right, right_ref), right_ref),
+            refs => refs,
+        }
+    }
 }
 
 #[allow(clippy::enum_variant_names)]
-#[derive(Default, Eq, PartialEq, Copy, Clone)]
+#[derive(Default, Eq, PartialEq, Copy, Clone, serde::Deserialize, serde::Serialize)]
 pub enum View {
     #[default]
     SymbolDiff,
@@ -48,46 +149,602 @@ pub struct DiffViewState {
     pub search_regex: Option<Regex>,
     pub queue_build: bool,
     pub build_running: bool,
-    pub scratch_available: bool,
+    /// Why a scratch can't be created, if it can't
+    pub scratch_unavailable: Option<&'static str>,
     pub queue_scratch: bool,
     pub scratch_running: bool,
+    /// Scratch previously created for the selected function
+    pub scratch_slug: Option<String>,
+    pub queue_fetch_scratch: bool,
+    pub fetch_scratch_running: bool,
+    /// Saved snapshots of the selected object
+    pub snapshots: Vec<String>,
+    pub snapshot_label: String,
+    pub base_snapshot: Option<String>,
+    pub queue_snapshot: bool,
+    pub queue_select_snapshot: Option<Option<String>>,
+    pub refresh_snapshots: bool,
+    /// Other objects in the project with a base object, to diff the target against
+    pub base_objects: Vec<String>,
+    pub base_object: Option<String>,
+    pub queue_select_base_object: Option<Option<String>>,
+    /// Show runs of evenly spaced data relocations as one entry
+    pub group_data_relocs: bool,
+    pub data_diff_cache: DataDiffCache,
+    log_error_pattern: String,
+    log_error_regex: Option<Regex>,
+    /// Set when a build arrives, to check the selected symbol for completion
+    check_completion: bool,
+    completion_trigger: CompletionSoundTrigger,
+    /// Set when a saved session is restored, to drop its selected symbol if it no longer exists
+    validate_selection: bool,
+    /// Brief message shown over the view, and when it was raised
+    pub toast: Option<(String, Instant)>,
+    /// Code matched across the project, kept when switching objects
+    pub project_progress: ProjectProgress,
+    /// Width of the side panel next to the symbol list
+    pub side_panel_width: Option<f32>,
+}
+
+/// Diff view state saved across sessions.
+#[derive(Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct DiffSession {
+    /// Object the session belongs to
+    pub object: Option<String>,
+    pub selected_symbol: Option<SymbolRefByName>,
+    pub current_view: View,
+    pub search: String,
+    pub reverse_fn_order: bool,
+    pub side_panel_width: Option<f32>,
+    pub bookmarks: BTreeSet<String>,
+}
+
+/// Which side's order both symbol lists follow, pairing rows across panels.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+)]
+pub enum SymbolAlignment {
+    #[default]
+    #[strum(message = "File order (default)", detailed_message = "List each side in file order.")]
+    None,
+    #[strum(
+        message = "Align to target",
+        detailed_message = "List both sides in the target's order, lining up matching symbols."
+    )]
+    Target,
+    #[strum(
+        message = "Align to base",
+        detailed_message = "List both sides in the base's order, lining up matching symbols."
+    )]
+    Base,
+}
+
+/// Order of the symbols within each section.
+#[derive(Default, Copy, Clone, Eq, PartialEq, strum::VariantArray, strum::EnumMessage)]
+pub enum SymbolSortMode {
+    #[default]
+    #[strum(message = "Address")]
+    Address,
+    #[strum(message = "Match % (lowest first)")]
+    MatchPercentAsc,
+    #[strum(message = "Match % (highest first)")]
+    MatchPercentDesc,
+    #[strum(message = "Name")]
+    Name,
+}
+
+/// What happens when a symbol row is clicked.
+#[derive(
+    Default,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    serde::Deserialize,
+    serde::Serialize,
+    strum::VariantArray,
+    strum::EnumMessage,
+)]
+pub enum SymbolClickAction {
+    #[default]
+    #[strum(message = "Nothing")]
+    None,
+    #[strum(message = "Open diff")]
+    Open,
+    #[strum(message = "Select without opening")]
+    Preview,
+    #[strum(message = "Copy name")]
+    CopyName,
+    #[strum(message = "Copy link")]
+    CopyLink,
+    #[strum(message = "Toggle bookmark")]
+    Bookmark,
+    #[strum(message = "Pair with a symbol on the other side")]
+    SetPair,
+}
+
+/// egui's window for the second click of a double click
+const DOUBLE_CLICK_DELAY: Duration = Duration::from_millis(300);
+
+/// Actions bound to each kind of click on a symbol row.
+#[derive(Copy, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SymbolClickActions {
+    pub click: SymbolClickAction,
+    pub double_click: SymbolClickAction,
+    /// Click while holding Ctrl (Cmd on macOS)
+    pub modifier_click: SymbolClickAction,
+}
+
+impl Default for SymbolClickActions {
+    fn default() -> Self {
+        Self {
+            click: SymbolClickAction::Open,
+            double_click: SymbolClickAction::None,
+            modifier_click: SymbolClickAction::CopyName,
+        }
+    }
+}
+
+/// Symbol flags offered by the symbol list's flag filter.
+const FILTER_FLAGS: [(ObjSymbolFlags, &str); 6] = [
+    (ObjSymbolFlags::Global, "Global"),
+    (ObjSymbolFlags::Local, "Local"),
+    (ObjSymbolFlags::Weak, "Weak"),
+    (ObjSymbolFlags::Common, "Common"),
+    (ObjSymbolFlags::Hidden, "Hidden"),
+    (ObjSymbolFlags::Protected, "Protected"),
+];
+
+/// Filters the symbol list by symbol flags, on top of the text search.
+#[derive(Default, Copy, Clone)]
+pub struct SymbolFlagFilter {
+    /// Flags a symbol must all have
+    require: ObjSymbolFlagSet,
+    /// Flags a symbol must have none of
+    exclude: ObjSymbolFlagSet,
+}
+
+impl SymbolFlagFilter {
+    pub fn is_empty(&self) -> bool { self.require.0.is_empty() && self.exclude.0.is_empty() }
+
+    pub fn matches(&self, symbol: &ObjSymbol) -> bool {
+        symbol.flags.0.contains(self.require.0) && symbol.flags.0.is_disjoint(self.exclude.0)
+    }
+
+    /// Whether symbols must have (`Some(true)`) or lack (`Some(false)`) the flag.
+    fn get(&self, flag: ObjSymbolFlags) -> Option<bool> {
+        if self.require.0.contains(flag) {
+            Some(true)
+        } else if self.exclude.0.contains(flag) {
+            Some(false)
+        } else {
+            None
+        }
+    }
+
+    fn set(&mut self, flag: ObjSymbolFlags, value: Option<bool>) {
+        self.require.0 -= flag;
+        self.exclude.0 -= flag;
+        match value {
+            Some(true) => self.require.0 |= flag,
+            Some(false) => self.exclude.0 |= flag,
+            None => {}
+        }
+    }
+}
+
+/// Optional segments of a symbol row. The name is always shown.
+#[derive(Copy, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SymbolColumns {
+    /// Scope flags, e.g. `[g]`
+    pub flags: bool,
+    pub match_percent: bool,
+    /// Match percentage ignoring register allocation, when it differs
+    pub regalloc_match_percent: bool,
+    /// Marker for pairings that may be coincidental
+    pub pair_confidence: bool,
+    pub size: bool,
+    /// Marker for paired symbols whose sizes differ
+    pub size_mismatch: bool,
+    /// Address within the section
+    pub address: bool,
+}
+
+impl Default for SymbolColumns {
+    fn default() -> Self {
+        Self {
+            flags: true,
+            match_percent: true,
+            regalloc_match_percent: false,
+            pair_confidence: true,
+            size: false,
+            size_mismatch: true,
+            address: false,
+        }
+    }
+}
+
+impl SymbolClickActions {
+    /// The action bound to a click. A double click also reports its first click, so with a
+    /// double click action bound, `symbol_ui` holds the single click action back until the
+    /// double click window has passed.
+    pub fn resolve(
+        &self,
+        clicked: bool,
+        double_clicked: bool,
+        modifier: bool,
+    ) -> SymbolClickAction {
+        if double_clicked {
+            self.double_click
+        } else if clicked && modifier {
+            self.modifier_click
+        } else if clicked {
+            self.click
+        } else {
+            SymbolClickAction::None
+        }
+    }
 }
 
 #[derive(Default)]
 pub struct SymbolViewState {
     pub highlighted_symbol: (Option<SymbolRef>, Option<SymbolRef>),
     pub selected_symbol: Option<SymbolRefByName>,
+    /// Symbol picked on the base side, instead of following the selected symbol's pairing
+    pub base_symbol: Option<SymbolRefByName>,
+    /// Whether each side keeps its own selection, while diffing against another object's base
+    pub independent_sides: bool,
     pub reverse_fn_order: bool,
-    pub disable_reverse_fn_order: bool,
+    /// The selected object's `reverse_fn_order`, overriding the global toggle
+    pub obj_reverse_fn_order: Option<bool>,
     pub show_hidden_symbols: bool,
+    pub flag_filter: SymbolFlagFilter,
     pub queue_extab_decode: bool,
+    pub identity_policy: SymbolIdentityPolicy,
+    pub collapse_matched_sections: bool,
+    /// Sections the user has opened or collapsed, by name, exempt from auto-collapse. Kept
+    /// across objects and saved with the app state.
+    pub section_open: BTreeMap<String, bool>,
+    pub symbol_alignment: SymbolAlignment,
+    pub link_scroll: bool,
+    /// Vertical offset shared by both symbol lists while scrolling is linked
+    scroll_offset: f32,
+    pub sort_mode: SymbolSortMode,
+    pub click_actions: SymbolClickActions,
+    pub columns: SymbolColumns,
+    /// Deferred symbol diffs to compute on the next update, with the side they're on
+    pub pending_diffs: BTreeSet<(SymbolRef, bool)>,
+    section_order: Vec<Glob>,
+    section_matchers: Vec<GlobMatcher>,
+    /// Link to the current object, used to build per-symbol links
+    pub deep_link: Option<DeepLink>,
+    /// Row clicked once while a double click action is bound, and when
+    pending_click: Option<((SymbolRef, bool), Instant)>,
+    /// Bookmarked symbol names, marked in the symbol list
+    pub bookmarks: BTreeSet<String>,
+    /// Symbol name and side picked as the first half of a pair
+    pub pair_candidate: Option<(String, bool)>,
+    /// Target and base symbol names to diff against each other
+    pub queue_pair: Option<(String, String)>,
+}
+
+impl SymbolViewState {
+    /// Selects a symbol picked on one side. While the sides are independent, a base symbol
+    /// picked with a target symbol selected is paired with it, and the target stays selected.
+    fn select_symbol(&mut self, symbol: SymbolRefByName, left: bool) {
+        if self.independent_sides && !left {
+            if let Some(target) = &self.selected_symbol {
+                self.queue_pair = Some((target.symbol_name.clone(), symbol.symbol_name.clone()));
+                self.base_symbol = Some(symbol);
+                return;
+            }
+            self.base_symbol = Some(symbol.clone());
+        } else {
+            self.base_symbol = None;
+        }
+        self.selected_symbol = Some(symbol);
+    }
+
+    /// Resolves the symbol shown on each side, see [`SymbolRefByName::find_pair`].
+    pub fn selected_refs(
+        &self,
+        left: Option<&(ObjInfo, ObjDiff)>,
+        right: Option<&(ObjInfo, ObjDiff)>,
+    ) -> (Option<SymbolRef>, Option<SymbolRef>) {
+        let Some(selected) = &self.selected_symbol else {
+            return (None, None);
+        };
+        let (left_ref, right_ref) = selected.find_pair(left, right, self.identity_policy);
+        match &self.base_symbol {
+            Some(base) => {
+                (left_ref, right.and_then(|(obj, _)| base.find(obj, self.identity_policy)))
+            }
+            None => (left_ref, right_ref),
+        }
+    }
 }
 
 impl DiffViewState {
+    pub fn session(&self, object: Option<String>) -> DiffSession {
+        DiffSession {
+            object,
+            selected_symbol: self.symbol_state.selected_symbol.clone(),
+            current_view: self.current_view,
+            search: self.search.clone(),
+            reverse_fn_order: self.symbol_state.reverse_fn_order,
+            side_panel_width: self.side_panel_width,
+            bookmarks: self.symbol_state.bookmarks.clone(),
+        }
+    }
+
+    /// Restores the saved layout, and the selection and filter if `same_object` is set.
+    pub fn restore_session(&mut self, session: DiffSession, same_object: bool) {
+        self.symbol_state.reverse_fn_order = session.reverse_fn_order;
+        self.side_panel_width = session.side_panel_width;
+        if !same_object {
+            return;
+        }
+        self.symbol_state.bookmarks = session.bookmarks;
+        self.validate_selection = session.selected_symbol.is_some();
+        self.symbol_state.selected_symbol = session.selected_symbol;
+        self.current_view =
+            if self.validate_selection { session.current_view } else { View::SymbolDiff };
+        self.search_regex = (!session.search.is_empty())
+            .then(|| RegexBuilder::new(&session.search).case_insensitive(true).build().ok())
+            .flatten();
+        self.search = session.search;
+    }
+
+    /// Opens the next (or previous) function in the target object below 100%, wrapping around.
+    pub fn select_unmatched(&mut self, forward: bool) {
+        let Some((obj, diff)) = self.build.as_ref().and_then(|b| b.first_obj.as_ref()) else {
+            return;
+        };
+        let state = &self.symbol_state;
+        // Code symbols in the order the symbol list shows them, with whether they're unmatched
+        let mut order = vec![];
+        for (section_idx, section) in obj.sections.iter().enumerate() {
+            if section.kind != ObjSectionKind::Code {
+                continue;
+            }
+            let section_diff = &diff.sections[section_idx];
+            let mut rows: Vec<_> = (0..section.symbols.len()).map(|i| (Some(i), None)).collect();
+            if state.sort_mode != SymbolSortMode::Address {
+                sort_rows(&mut rows, (section, section_diff), None, true, state.sort_mode);
+            } else if state.obj_reverse_fn_order.unwrap_or(state.reverse_fn_order) {
+                rows.reverse();
+            }
+            for symbol_idx in rows.into_iter().filter_map(|(i, _)| i) {
+                let symbol = &section.symbols[symbol_idx];
+                if symbol.flags.0.contains(ObjSymbolFlags::Hidden) && !state.show_hidden_symbols {
+                    continue;
+                }
+                let symbol_ref = SymbolRef { section_idx, symbol_idx };
+                // Lazily diffed functions have no match percentage yet, so they may not match
+                let unmatched =
+                    diff.symbol_diff(symbol_ref).match_percent.map_or(true, |p| p < 100.0);
+                order.push((symbol_ref, symbol, section, unmatched));
+            }
+        }
+        let current = state
+            .selected_symbol
+            .as_ref()
+            .and_then(|s| s.find(obj, state.identity_policy))
+            .and_then(|current| order.iter().position(|(r, ..)| *r == current));
+        let next = if forward {
+            let start = current.map_or(0, |i| i + 1);
+            order[start..].iter().chain(&order[..start]).find(|(.., unmatched)| *unmatched)
+        } else {
+            let end = current.unwrap_or(order.len());
+            order[..end].iter().rev().chain(order[end..].iter().rev()).find(|(.., u)| *u)
+        };
+        match next {
+            Some(&(_, symbol, section, _)) => {
+                self.symbol_state.selected_symbol = Some(SymbolRefByName::new(symbol, section));
+                self.current_view = View::FunctionDiff;
+            }
+            None => self.toast = Some(("All functions matched".to_string(), Instant::now())),
+        }
+    }
+
+    /// Clears a restored selected symbol that's missing from the first successful build.
+    fn clear_stale_selection(&mut self) {
+        let Some(result) = &self.build else {
+            return;
+        };
+        let objs = [&result.first_obj, &result.second_obj];
+        if objs.iter().all(|obj| obj.is_none()) {
+            return;
+        }
+        self.validate_selection = false;
+        let Some(selected) = &self.symbol_state.selected_symbol else {
+            return;
+        };
+        let policy = self.symbol_state.identity_policy;
+        if !objs
+            .iter()
+            .any(|obj| obj.as_ref().is_some_and(|(obj, _)| selected.find(obj, policy).is_some()))
+        {
+            log::info!("Saved symbol '{}' no longer exists", selected.symbol_name);
+            self.symbol_state.selected_symbol = None;
+            self.current_view = View::SymbolDiff;
+        }
+    }
+
     pub fn pre_update(&mut self, jobs: &mut JobQueue, config: &AppConfigRef) {
         jobs.results.retain_mut(|result| match result {
             JobResult::ObjDiff(result) => {
                 self.build = take(result);
+                self.data_diff_cache = Default::default();
+                self.refresh_snapshots = true;
+                self.check_completion = true;
+                if let Some(result) = &self.build {
+                    let success = result.first_status.success && result.second_status.success;
+                    // Lazily diffed objects don't have match percentages yet
+                    let progress = result
+                        .first_obj
+                        .as_ref()
+                        .filter(|(_, d)| d.pending.is_empty())
+                        .map(|(obj, diff)| CodeProgress::new(obj, diff));
+                    if !success || progress.is_some() {
+                        if let Some(name) = config
+                            .read()
+                            .ok()
+                            .and_then(|c| c.selected_obj.as_ref().map(|o| o.name.clone()))
+                        {
+                            self.project_progress.update(&name, progress.filter(|_| success));
+                        }
+                    }
+                    let built = !result.first_status.cmdline.is_empty()
+                        || !result.second_status.cmdline.is_empty();
+                    if built && result.first_status.success && result.second_status.success {
+                        if let Ok(mut config) = config.write() {
+                            if let Some(name) = config.selected_obj.as_ref().map(|o| o.name.clone())
+                            {
+                                // Lazily diffed objects don't have match percentages yet
+                                if let (Some(path), Some((obj, diff))) = (
+                                    &config.build_history_path,
+                                    result.first_obj.as_ref().filter(|(_, d)| d.pending.is_empty()),
+                                ) {
+                                    if let Err(e) = append_build_record(
+                                        path,
+                                        &name,
+                                        obj,
+                                        diff,
+                                        config.diff_obj_config.match_visibility,
+                                        result.time,
+                                    ) {
+                                        log::warn!("Failed to record build history: {e:?}");
+                                    }
+                                }
+                                config.build_times.insert(name, result.time);
+                            }
+                        }
+                    }
+                }
                 false
             }
             JobResult::CreateScratch(result) => {
                 self.scratch = take(result);
+                if let (Some(result), Ok(mut config)) = (&self.scratch, config.write()) {
+                    config
+                        .scratch_slugs
+                        .entry(result.unit_name.clone())
+                        .or_default()
+                        .insert(result.function_name.clone(), result.scratch_slug.clone());
+                }
                 false
             }
             _ => true,
         });
         self.build_running = jobs.is_running(Job::ObjDiff);
         self.scratch_running = jobs.is_running(Job::CreateScratch);
+        self.fetch_scratch_running = jobs.is_running(Job::FetchScratch);
 
-        self.symbol_state.disable_reverse_fn_order = false;
         if let Ok(config) = config.read() {
-            if let Some(obj_config) = &config.selected_obj {
-                if let Some(value) = obj_config.reverse_fn_order {
-                    self.symbol_state.reverse_fn_order = value;
-                    self.symbol_state.disable_reverse_fn_order = true;
+            self.symbol_state.obj_reverse_fn_order =
+                config.selected_obj.as_ref().and_then(|obj| obj.reverse_fn_order);
+            self.scratch_unavailable = CreateScratchConfig::missing_requirement(&config);
+            self.symbol_state.identity_policy = config.symbol_identity;
+            self.base_snapshot.clone_from(&config.base_snapshot);
+            self.base_object.clone_from(&config.base_object);
+            self.symbol_state.independent_sides = config.base_object.is_some();
+            self.symbol_state.collapse_matched_sections = config.collapse_matched_sections;
+            self.symbol_state.symbol_alignment = config.symbol_alignment;
+            self.symbol_state.link_scroll = config.link_symbol_scroll;
+            self.function_state.link_scroll = config.link_function_scroll;
+            self.symbol_state.click_actions = config.symbol_click_actions;
+            self.symbol_state.columns = config.symbol_columns;
+            if self.log_error_pattern != config.build_log_error_pattern {
+                self.log_error_pattern.clone_from(&config.build_log_error_pattern);
+                self.log_error_regex = (!self.log_error_pattern.is_empty())
+                    .then(|| Regex::new(&self.log_error_pattern).ok())
+                    .flatten();
+            }
+            if self.symbol_state.section_order != config.section_order {
+                self.symbol_state.section_order.clone_from(&config.section_order);
+                self.symbol_state.section_matchers =
+                    config.section_order.iter().map(|g| g.compile_matcher()).collect();
+            }
+            self.scratch_slug = config
+                .selected_obj
+                .as_ref()
+                .and_then(|obj| config.scratch_slugs.get(&obj.name))
+                .zip(self.symbol_state.selected_symbol.as_ref())
+                .and_then(|(slugs, sym)| slugs.get(&sym.symbol_name))
+                .cloned();
+            self.symbol_state.deep_link =
+                config.project_dir.as_ref().zip(config.selected_obj.as_ref()).map(
+                    |(project_dir, obj)| DeepLink {
+                        project_dir: project_dir.clone(),
+                        object: Some(obj.name.clone()),
+                        symbol: None,
+                    },
+                );
+            if self.validate_selection {
+                self.clear_stale_selection();
+            }
+            self.resolve_pending_diffs(&config.diff_obj_config);
+            if take(&mut self.check_completion) && config.completion_sound.enabled {
+                self.check_completion_sound(&config.completion_sound);
+            }
+        }
+    }
+
+    /// Plays the completion sound if the selected symbol was just matched.
+    fn check_completion_sound(&mut self, sound: &CompletionSoundConfig) {
+        let Some(selected) = &self.symbol_state.selected_symbol else {
+            self.completion_trigger.reset();
+            return;
+        };
+        let Some((obj, diff)) = self.build.as_ref().and_then(|b| b.first_obj.as_ref()) else {
+            return;
+        };
+        let Some(match_percent) = selected
+            .find(obj, self.symbol_state.identity_policy)
+            .and_then(|symbol_ref| diff.symbol_diff(symbol_ref).match_percent)
+        else {
+            return;
+        };
+        if self.completion_trigger.update(&selected.symbol_name, match_percent, Instant::now()) {
+            if let Some(path) = &sound.path {
+                if let Err(e) = play_sound(path, sound.volume) {
+                    log::warn!("{e:?}");
                 }
             }
-            self.scratch_available = CreateScratchConfig::is_available(&config);
+        }
+    }
+
+    /// Diffs deferred symbols requested by the symbol list, and the selected symbol.
+    fn resolve_pending_diffs(&mut self, diff_config: &DiffObjConfig) {
+        let Some(result) = &mut self.build else {
+            return;
+        };
+        let mut requests = take(&mut self.symbol_state.pending_diffs);
+        let (left_ref, right_ref) =
+            self.symbol_state.selected_refs(result.first_obj.as_ref(), result.second_obj.as_ref());
+        requests.extend(left_ref.map(|r| (r, true)));
+        requests.extend(right_ref.map(|r| (r, false)));
+        for (symbol_ref, left) in requests {
+            let left_obj = result.first_obj.as_mut().map(|(o, d)| (&*o, d));
+            let right_obj = result.second_obj.as_mut().map(|(o, d)| (&*o, d));
+            // The previous build isn't kept, so there's nothing to diff against
+            if let Err(e) =
+                diff_pending_symbol(diff_config, left_obj, right_obj, None, symbol_ref, left)
+            {
+                log::error!("Failed to diff symbol: {e:?}");
+            }
         }
     }
 
@@ -96,6 +753,15 @@ impl DiffViewState {
             ctx.output_mut(|o| o.open_url = Some(OpenUrl::new_tab(result.scratch_url)));
         }
 
+        if let Some((left, right)) = self.symbol_state.queue_pair.take() {
+            if let Ok(mut config) = config.write() {
+                // Kept apart from the object's mappings, which follow the project config
+                config.symbol_pairs.retain(|(l, r)| *l != left && *r != right);
+                config.symbol_pairs.push((left, right));
+                config.queue_reload = true;
+            }
+        }
+
         if self.queue_build {
             self.queue_build = false;
             if let Ok(mut config) = config.write() {
@@ -122,6 +788,67 @@ impl DiffViewState {
                 }
             }
         }
+
+        if self.queue_snapshot
+            || self.refresh_snapshots
+            || self.queue_select_snapshot.is_some()
+            || self.queue_select_base_object.is_some()
+        {
+            if let Ok(mut config) = config.write() {
+                let config = &mut *config;
+                if let Some(label) = self.queue_select_snapshot.take() {
+                    config.base_snapshot = label;
+                    config.base_object = None;
+                    config.queue_build = true;
+                    self.symbol_state.base_symbol = None;
+                }
+                if let Some(name) = self.queue_select_base_object.take() {
+                    config.base_object = name;
+                    config.base_snapshot = None;
+                    config.queue_build = true;
+                    self.symbol_state.base_symbol = None;
+                }
+                if let (Some(project_dir), Some(obj)) = (&config.project_dir, &config.selected_obj)
+                {
+                    if take(&mut self.queue_snapshot) {
+                        if let Some(target_path) = &obj.target_path {
+                            let label = take(&mut self.snapshot_label);
+                            match save_snapshot(project_dir, &obj.name, label.trim(), target_path) {
+                                Ok(()) => self.refresh_snapshots = true,
+                                Err(e) => log::error!("Failed to save snapshot: {e:?}"),
+                            }
+                        }
+                    }
+                    if take(&mut self.refresh_snapshots) {
+                        self.snapshots = list_snapshots(project_dir, &obj.name);
+                        let has_base_dir = config.base_obj_dir.is_some();
+                        self.base_objects = config
+                            .objects
+                            .iter()
+                            .filter(|o| {
+                                o.name() != obj.name
+                                    && (o.base_path.is_some() || has_base_dir && o.path.is_some())
+                            })
+                            .map(|o| o.name().to_string())
+                            .collect();
+                    }
+                }
+            }
+        }
+
+        if self.queue_fetch_scratch {
+            self.queue_fetch_scratch = false;
+            if let (Some(slug), Ok(config)) = (self.scratch_slug.clone(), config.read()) {
+                match FetchScratchConfig::from_config(&config, slug) {
+                    Ok(config) => {
+                        jobs.push_once(Job::FetchScratch, || start_fetch_scratch(ctx, config));
+                    }
+                    Err(err) => {
+                        log::error!("Failed to create fetch scratch config: {err}");
+                    }
+                }
+            }
+        }
     }
 }
 
@@ -146,15 +873,27 @@ fn symbol_context_menu_ui(
         ui.style_mut().wrap = Some(false);
 
         if let Some(name) = &symbol.demangled_name {
-            if ui.button(format!("Copy \"{name}\"")).clicked() {
+            if ui.button(format!("Copy \"{name}\"")).on_hover_text("Copy demangled name").clicked()
+            {
                 ui.output_mut(|output| output.copied_text.clone_from(name));
                 ui.close_menu();
             }
         }
-        if ui.button(format!("Copy \"{}\"", symbol.name)).clicked() {
+        if ui
+            .button(format!("Copy \"{}\"", symbol.name))
+            .on_hover_text("Copy symbol name")
+            .clicked()
+        {
             ui.output_mut(|output| output.copied_text.clone_from(&symbol.name));
             ui.close_menu();
         }
+        if let Some(link) = &state.deep_link {
+            if ui.button("Copy link").clicked() {
+                let link = DeepLink { symbol: Some(symbol.name.clone()), ..link.clone() };
+                ui.output_mut(|output| output.copied_text = link.to_url());
+                ui.close_menu();
+            }
+        }
         if let Some(address) = symbol.virtual_address {
             if ui.button(format!("Copy \"{:#x}\" (virtual address)", address)).clicked() {
                 ui.output_mut(|output| output.copied_text = format!("{:#x}", address));
@@ -164,18 +903,20 @@ fn symbol_context_menu_ui(
         if let Some(section) = section {
             if symbol.has_extab && ui.button("Decode exception table").clicked() {
                 state.queue_extab_decode = true;
-                state.selected_symbol = Some(SymbolRefByName {
-                    symbol_name: symbol.name.clone(),
-                    demangled_symbol_name: symbol.demangled_name.clone(),
-                    section_name: section.name.clone(),
-                });
+                state.selected_symbol = Some(SymbolRefByName::new(symbol, section));
                 ui.close_menu();
             }
         }
     });
 }
 
-fn symbol_hover_ui(ui: &mut Ui, symbol: &ObjSymbol, appearance: &Appearance) {
+fn symbol_hover_ui(
+    ui: &mut Ui,
+    symbol: &ObjSymbol,
+    confidence: Option<PairConfidence>,
+    paired: Option<&ObjSymbol>,
+    appearance: &Appearance,
+) {
     ui.scope(|ui| {
         ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
         ui.style_mut().wrap = Some(false);
@@ -190,9 +931,36 @@ fn symbol_hover_ui(ui: &mut Ui, symbol: &ObjSymbol, appearance: &Appearance) {
                 format!("Size: {:x} (assumed)", symbol.size),
             );
         }
+        if let Some(other_size) = paired.map(|s| s.size).filter(|&size| size != symbol.size) {
+            let (sign, delta) = if symbol.size > other_size {
+                ('+', symbol.size - other_size)
+            } else {
+                ('-', other_size - symbol.size)
+            };
+            ui.colored_label(
+                appearance.replace_color,
+                format!("Other side's size: {other_size:x} ({sign}{delta:#x})"),
+            );
+        }
         if let Some(address) = symbol.virtual_address {
             ui.colored_label(appearance.replace_color, format!("Virtual address: {:#x}", address));
         }
+        match confidence {
+            Some(PairConfidence::Mapped) => {
+                let name = paired.map(|s| s.name.as_str()).unwrap_or_default();
+                ui.colored_label(appearance.highlight_color, format!("Mapped to: {name}"));
+            }
+            Some(PairConfidence::High) => {
+                ui.colored_label(appearance.highlight_color, "Paired by exact name");
+            }
+            Some(PairConfidence::Medium) => {
+                ui.colored_label(appearance.replace_color, "Paired by normalized name");
+            }
+            Some(PairConfidence::Low) => {
+                ui.colored_label(appearance.delete_color, "Paired by address only");
+            }
+            None => {}
+        }
         if symbol.has_extab {
             if let (Some(extab_name), Some(extabindex_name)) =
                 (&symbol.extab_name, &symbol.extabindex_name)
@@ -210,11 +978,23 @@ fn symbol_hover_ui(ui: &mut Ui, symbol: &ObjSymbol, appearance: &Appearance) {
     });
 }
 
+/// The symbol paired with this one on the other side.
+fn paired_symbol<'a>(
+    symbol_diff: &ObjSymbolDiff,
+    other: Option<&'a (ObjInfo, ObjDiff)>,
+) -> Option<&'a ObjSymbol> {
+    let (other_obj, _) = other?;
+    Some(other_obj.section_symbol(symbol_diff.diff_symbol?).1)
+}
+
 #[must_use]
+#[allow(clippy::too_many_arguments)]
 fn symbol_ui(
     ui: &mut Ui,
     symbol: &ObjSymbol,
     symbol_diff: &ObjSymbolDiff,
+    confidence: Option<PairConfidence>,
+    paired: Option<&ObjSymbol>,
     section: Option<&ObjSection>,
     state: &mut SymbolViewState,
     appearance: &Appearance,
@@ -227,68 +1007,204 @@ fn symbol_ui(
     let mut job = LayoutJob::default();
     let name: &str =
         if let Some(demangled) = &symbol.demangled_name { demangled } else { &symbol.name };
-    let mut selected = false;
+    let mut selected = state.selected_symbol.as_ref().is_some_and(|selected| {
+        selected.symbol_name == symbol.name
+            && section.is_some_and(|section| selected.section_name == section.name)
+    });
     if let Some(sym_ref) =
         if left { state.highlighted_symbol.0 } else { state.highlighted_symbol.1 }
     {
-        selected = symbol_diff.symbol_ref == sym_ref;
+        selected |= symbol_diff.symbol_ref == sym_ref;
     }
-    if !symbol.flags.0.is_empty() {
+    let columns = state.columns;
+    let size_differs = paired.is_some_and(|s| s.size != symbol.size);
+    if columns.flags {
+        // One slot each for binding, weakness and visibility, so names line up
+        let flags = symbol.flags.0;
+        let slots = [
+            if flags.contains(ObjSymbolFlags::Common) {
+                ("c", appearance.replace_color)
+            } else if flags.contains(ObjSymbolFlags::Global) {
+                ("g", appearance.insert_color)
+            } else if flags.contains(ObjSymbolFlags::Local) {
+                ("l", appearance.text_color)
+            } else {
+                (" ", appearance.text_color)
+            },
+            if flags.contains(ObjSymbolFlags::Weak) {
+                ("w", appearance.text_color)
+            } else {
+                (" ", appearance.text_color)
+            },
+            if flags.contains(ObjSymbolFlags::Hidden) {
+                ("h", appearance.deemphasized_text_color)
+            } else if flags.contains(ObjSymbolFlags::Protected) {
+                ("p", appearance.deemphasized_text_color)
+            } else {
+                (" ", appearance.text_color)
+            },
+        ];
         write_text("[", appearance.text_color, &mut job, appearance.code_font.clone());
-        if symbol.flags.0.contains(ObjSymbolFlags::Common) {
-            write_text("c", appearance.replace_color, &mut job, appearance.code_font.clone());
-        } else if symbol.flags.0.contains(ObjSymbolFlags::Global) {
-            write_text("g", appearance.insert_color, &mut job, appearance.code_font.clone());
-        } else if symbol.flags.0.contains(ObjSymbolFlags::Local) {
-            write_text("l", appearance.text_color, &mut job, appearance.code_font.clone());
-        }
-        if symbol.flags.0.contains(ObjSymbolFlags::Weak) {
-            write_text("w", appearance.text_color, &mut job, appearance.code_font.clone());
+        for (text, color) in slots {
+            write_text(text, color, &mut job, appearance.code_font.clone());
         }
-        if symbol.flags.0.contains(ObjSymbolFlags::Hidden) {
+        write_text("] ", appearance.text_color, &mut job, appearance.code_font.clone());
+    }
+    if columns.match_percent {
+        if state.pending_diffs.contains(&(symbol_diff.symbol_ref, left)) {
             write_text(
-                "h",
+                "(…) ",
                 appearance.deemphasized_text_color,
                 &mut job,
                 appearance.code_font.clone(),
             );
+        } else if let Some(match_percent) = symbol_diff.match_percent {
+            write_text("(", appearance.text_color, &mut job, appearance.code_font.clone());
+            write_text(
+                &format!("{match_percent:.0}%"),
+                match_color_for_symbol(match_percent, appearance),
+                &mut job,
+                appearance.code_font.clone(),
+            );
+            if let Some(regalloc_percent) = symbol_diff
+                .regalloc_match_percent
+                .filter(|&p| columns.regalloc_match_percent && p != match_percent)
+            {
+                write_text(
+                    &format!(" ~{regalloc_percent:.0}%"),
+                    match_color_for_symbol(regalloc_percent, appearance),
+                    &mut job,
+                    appearance.code_font.clone(),
+                );
+            }
+            write_text(") ", appearance.text_color, &mut job, appearance.code_font.clone());
         }
-        write_text("] ", appearance.text_color, &mut job, appearance.code_font.clone());
     }
-    if let Some(match_percent) = symbol_diff.match_percent {
-        write_text("(", appearance.text_color, &mut job, appearance.code_font.clone());
+    if columns.address {
+        write_text(
+            &format!("{:x} ", symbol.section_address),
+            appearance.deemphasized_text_color,
+            &mut job,
+            appearance.code_font.clone(),
+        );
+    }
+    if columns.size {
         write_text(
-            &format!("{match_percent:.0}%"),
-            match_color_for_symbol(match_percent, appearance),
+            &format!("{:#x} ", symbol.size),
+            if size_differs {
+                appearance.replace_color
+            } else {
+                appearance.deemphasized_text_color
+            },
             &mut job,
             appearance.code_font.clone(),
         );
-        write_text(") ", appearance.text_color, &mut job, appearance.code_font.clone());
+    }
+    if columns.size_mismatch && size_differs {
+        write_text("Δ ", appearance.replace_color, &mut job, appearance.code_font.clone());
+    }
+    // Flag pairings that may be coincidental
+    match confidence.filter(|_| columns.pair_confidence) {
+        Some(PairConfidence::Medium) => {
+            write_text("≈ ", appearance.replace_color, &mut job, appearance.code_font.clone());
+        }
+        Some(PairConfidence::Low) => {
+            write_text("? ", appearance.delete_color, &mut job, appearance.code_font.clone());
+        }
+        Some(PairConfidence::High | PairConfidence::Mapped) | None => {}
+    }
+    if state.bookmarks.contains(&symbol.name) {
+        write_text("★ ", appearance.replace_color, &mut job, appearance.code_font.clone());
+    }
+    if state.pair_candidate.as_ref().is_some_and(|(n, l)| *l == left && *n == symbol.name) {
+        write_text("⇄ ", appearance.replace_color, &mut job, appearance.code_font.clone());
     }
     write_text(name, appearance.highlight_color, &mut job, appearance.code_font.clone());
+    // Mapped symbols are named differently on each side, so show both
+    if let Some(paired) = paired.filter(|_| confidence == Some(PairConfidence::Mapped)) {
+        let paired_name = paired.demangled_name.as_ref().unwrap_or(&paired.name);
+        write_text(
+            &format!(" ↔ {paired_name}"),
+            appearance.deemphasized_text_color,
+            &mut job,
+            appearance.code_font.clone(),
+        );
+    }
     let response = SelectableLabel::new(selected, job)
         .ui(ui)
-        .on_hover_ui_at_pointer(|ui| symbol_hover_ui(ui, symbol, appearance));
+        .on_hover_ui_at_pointer(|ui| symbol_hover_ui(ui, symbol, confidence, paired, appearance));
     response.context_menu(|ui| symbol_context_menu_ui(ui, state, symbol, section));
-    if response.clicked() {
-        if let Some(section) = section {
-            if section.kind == ObjSectionKind::Code {
-                state.selected_symbol = Some(SymbolRefByName {
-                    symbol_name: symbol.name.clone(),
-                    demangled_symbol_name: symbol.demangled_name.clone(),
-                    section_name: section.name.clone(),
-                });
-                ret = Some(View::FunctionDiff);
-            } else if section.kind == ObjSectionKind::Data {
-                state.selected_symbol = Some(SymbolRefByName {
-                    symbol_name: section.name.clone(),
-                    demangled_symbol_name: None,
-                    section_name: section.name.clone(),
-                });
-                ret = Some(View::DataDiff);
+    let modifier = ui.input(|i| i.modifiers.command);
+    let mut action =
+        state.click_actions.resolve(response.clicked(), response.double_clicked(), modifier);
+    let row = (symbol_diff.symbol_ref, left);
+    if response.double_clicked() {
+        state.pending_click = None;
+    } else if response.clicked()
+        && !modifier
+        && state.click_actions.double_click != SymbolClickAction::None
+    {
+        // Wait for a possible second click, so a double click doesn't run both actions
+        state.pending_click = Some((row, Instant::now()));
+        ui.ctx().request_repaint_after(DOUBLE_CLICK_DELAY);
+        action = SymbolClickAction::None;
+    } else if let Some((pending_row, time)) = state.pending_click {
+        if pending_row == row && time.elapsed() >= DOUBLE_CLICK_DELAY {
+            state.pending_click = None;
+            action = state.click_actions.click;
+        }
+    }
+    match action {
+        SymbolClickAction::None => {}
+        SymbolClickAction::Open => {
+            if let Some(section) = section {
+                if section.kind == ObjSectionKind::Code {
+                    state.select_symbol(SymbolRefByName::new(symbol, section), left);
+                    ret = Some(View::FunctionDiff);
+                } else if section.kind == ObjSectionKind::Data {
+                    state.selected_symbol = Some(SymbolRefByName {
+                        symbol_name: section.name.clone(),
+                        demangled_symbol_name: None,
+                        section_name: section.name.clone(),
+                        section_address: None,
+                    });
+                    ret = Some(View::DataDiff);
+                }
             }
         }
-    } else if response.hovered() {
+        SymbolClickAction::Preview => {
+            if let Some(section) = section {
+                state.select_symbol(SymbolRefByName::new(symbol, section), left);
+            }
+        }
+        SymbolClickAction::CopyName => {
+            ui.output_mut(|output| output.copied_text.clone_from(&symbol.name));
+        }
+        SymbolClickAction::CopyLink => {
+            if let Some(link) = &state.deep_link {
+                let link = DeepLink { symbol: Some(symbol.name.clone()), ..link.clone() };
+                ui.output_mut(|output| output.copied_text = link.to_url());
+            }
+        }
+        SymbolClickAction::Bookmark => {
+            if !state.bookmarks.remove(&symbol.name) {
+                state.bookmarks.insert(symbol.name.clone());
+            }
+        }
+        SymbolClickAction::SetPair => match state.pair_candidate.take() {
+            Some((name, candidate_left)) if candidate_left != left => {
+                state.queue_pair = Some(if left {
+                    (symbol.name.clone(), name)
+                } else {
+                    (name, symbol.name.clone())
+                });
+            }
+            // Picking the candidate again clears it
+            Some((name, _)) if name == symbol.name => {}
+            _ => state.pair_candidate = Some((symbol.name.clone(), left)),
+        },
+    }
+    if !response.clicked() && response.hovered() {
         state.highlighted_symbol = if let Some(diff_symbol) = symbol_diff.diff_symbol {
             if left {
                 (Some(symbol_diff.symbol_ref), Some(diff_symbol))
@@ -309,6 +1225,46 @@ fn symbol_ui(
     ret
 }
 
+/// Stably sorts rows by the target side's symbol, or by the base side's symbol in a gap, so
+/// that aligned rows stay in step on both sides. Symbols without a match percentage come last.
+fn sort_rows(
+    rows: &mut [(Option<usize>, Option<usize>)],
+    own: (&ObjSection, &ObjSectionDiff),
+    other: Option<(&ObjSection, &ObjSectionDiff)>,
+    left: bool,
+    mode: SymbolSortMode,
+) {
+    let row_symbol = |&(own_idx, other_idx): &(Option<usize>, Option<usize>)| {
+        let own_symbol = own_idx.map(|i| (&own.0.symbols[i], own.1.symbols[i].match_percent));
+        let other_symbol = other
+            .zip(other_idx)
+            .map(|((section, diff), i)| (&section.symbols[i], diff.symbols[i].match_percent));
+        if left {
+            own_symbol.or(other_symbol)
+        } else {
+            other_symbol.or(own_symbol)
+        }
+    };
+    match mode {
+        SymbolSortMode::Address => {}
+        SymbolSortMode::Name => rows.sort_by_cached_key(|row| {
+            row_symbol(row).map(|(s, _)| s.demangled_name.as_ref().unwrap_or(&s.name).clone())
+        }),
+        SymbolSortMode::MatchPercentAsc | SymbolSortMode::MatchPercentDesc => {
+            let percent =
+                |row: &(Option<usize>, Option<usize>)| row_symbol(row).and_then(|(_, p)| p);
+            let descending = mode == SymbolSortMode::MatchPercentDesc;
+            rows.sort_by(|a, b| match (percent(a), percent(b)) {
+                (Some(a), Some(b)) if descending => b.total_cmp(&a),
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            });
+        }
+    }
+}
+
 fn symbol_matches_search(symbol: &ObjSymbol, search_regex: Option<&Regex>) -> bool {
     if let Some(search_regex) = search_regex {
         search_regex.is_match(&symbol.name)
@@ -318,31 +1274,100 @@ fn symbol_matches_search(symbol: &ObjSymbol, search_regex: Option<&Regex>) -> bo
     }
 }
 
+/// Queues a deferred symbol diff now that the symbol is shown.
+fn request_pending_diff(
+    ui: &Ui,
+    obj_diff: &ObjDiff,
+    symbol_diff: &ObjSymbolDiff,
+    state: &mut SymbolViewState,
+    left: bool,
+) {
+    if obj_diff.pending.contains(&symbol_diff.symbol_ref)
+        && state.pending_diffs.insert((symbol_diff.symbol_ref, left))
+    {
+        ui.ctx().request_repaint();
+    }
+}
+
+/// Orders sections by the first pattern they match, keeping file order otherwise.
+/// Sections matching no pattern come last.
+fn section_display_order(sections: &[ObjSection], patterns: &[GlobMatcher]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..sections.len()).collect();
+    order.sort_by_key(|&i| {
+        patterns.iter().position(|p| p.is_match(&sections[i].name)).unwrap_or(patterns.len())
+    });
+    order
+}
+
+/// Size-weighted match percentage of a section's diffed symbols, with how many of them fully
+/// match and how many there are. `None` if no symbol has a match percentage.
+fn section_progress(
+    section: &ObjSection,
+    section_diff: &ObjSectionDiff,
+) -> Option<(f32, usize, usize)> {
+    let (mut matched, mut total, mut size, mut weighted) = (0, 0, 0u64, 0.0f64);
+    for (symbol, symbol_diff) in section.symbols.iter().zip(&section_diff.symbols) {
+        let Some(percent) = symbol_diff.match_percent else {
+            continue;
+        };
+        total += 1;
+        if percent >= 100.0 {
+            matched += 1;
+        }
+        size += symbol.size;
+        weighted += symbol.size as f64 * percent as f64;
+    }
+    if total == 0 {
+        return None;
+    }
+    let percent = if size > 0 {
+        (weighted / size as f64) as f32
+    } else {
+        matched as f32 / total as f32 * 100.0
+    };
+    Some((percent, matched, total))
+}
+
 #[must_use]
 fn symbol_list_ui(
     ui: &mut Ui,
     obj: &(ObjInfo, ObjDiff),
+    other: Option<&(ObjInfo, ObjDiff)>,
     state: &mut SymbolViewState,
     search_regex: Option<&Regex>,
     appearance: &Appearance,
     left: bool,
 ) -> Option<View> {
     let mut ret = None;
-    ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+    let mut scroll_area = ScrollArea::both().auto_shrink([false, false]);
+    if state.link_scroll {
+        scroll_area = scroll_area.vertical_scroll_offset(state.scroll_offset);
+    }
+    let output = scroll_area.show(ui, |ui| {
         ui.scope(|ui| {
             ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
             ui.style_mut().wrap = Some(false);
 
-            if !obj.0.common.is_empty() {
+            // Both the text search and the flag filter must match
+            let flag_filter = state.flag_filter;
+            let filtered = search_regex.is_some() || !flag_filter.is_empty();
+            let matches = |symbol: &ObjSymbol| {
+                symbol_matches_search(symbol, search_regex) && flag_filter.matches(symbol)
+            };
+            // Sections without symbols matching the search are hidden
+            let any_match = |symbols: &[ObjSymbol]| symbols.iter().any(matches);
+            if any_match(&obj.0.common) {
                 CollapsingHeader::new(".comm").default_open(true).show(ui, |ui| {
                     for (symbol, symbol_diff) in obj.0.common.iter().zip(&obj.1.common) {
-                        if !symbol_matches_search(symbol, search_regex) {
+                        if !matches(symbol) {
                             continue;
                         }
                         ret = ret.or(symbol_ui(
                             ui,
                             symbol,
                             symbol_diff,
+                            obj.1.pair_confidence.get(&symbol_diff.symbol_ref).copied(),
+                            paired_symbol(symbol_diff, other),
                             None,
                             state,
                             appearance,
@@ -352,13 +1377,39 @@ fn symbol_list_ui(
                 });
             }
 
-            for (section, section_diff) in obj.0.sections.iter().zip(&obj.1.sections) {
+            let mut order = section_display_order(&obj.0.sections, &state.section_matchers);
+            if state.symbol_alignment != SymbolAlignment::None {
+                // Sections on both sides come first, so that their rows line up
+                if let Some((other_obj, _)) = other {
+                    order.sort_by_key(|&i| {
+                        !other_obj.sections.iter().any(|s| s.name == obj.0.sections[i].name)
+                    });
+                }
+            }
+            for idx in order {
+                let (section, section_diff) = (&obj.0.sections[idx], &obj.1.sections[idx]);
+                if filtered && !any_match(&section.symbols) {
+                    // Keep aligned sections in step with the other side
+                    let other_match = state.symbol_alignment != SymbolAlignment::None
+                        && other.is_some_and(|(other_obj, _)| {
+                            other_obj
+                                .sections
+                                .iter()
+                                .any(|s| s.name == section.name && any_match(&s.symbols))
+                        });
+                    if !other_match {
+                        continue;
+                    }
+                }
                 let mut header = LayoutJob::simple_singleline(
                     format!("{} ({:x})", section.name, section.size),
                     appearance.code_font.clone(),
                     Color32::PLACEHOLDER,
                 );
-                if let Some(match_percent) = section_diff.match_percent {
+                let progress = section_progress(section, section_diff);
+                let match_percent =
+                    section_diff.match_percent.or(progress.map(|(percent, _, _)| percent));
+                if let Some(match_percent) = match_percent {
                     write_text(
                         " (",
                         Color32::PLACEHOLDER,
@@ -371,6 +1422,16 @@ fn symbol_list_ui(
                         &mut header,
                         appearance.code_font.clone(),
                     );
+                    if let Some((_, matched, total)) = progress {
+                        let noun =
+                            if section.kind == ObjSectionKind::Code { "funcs" } else { "symbols" };
+                        write_text(
+                            &format!(", {matched}/{total} {noun}"),
+                            Color32::PLACEHOLDER,
+                            &mut header,
+                            appearance.code_font.clone(),
+                        );
+                    }
                     write_text(
                         ")",
                         Color32::PLACEHOLDER,
@@ -378,53 +1439,147 @@ fn symbol_list_ui(
                         appearance.code_font.clone(),
                     );
                 }
-                CollapsingHeader::new(header)
+                let collapse =
+                    state.collapse_matched_sections && match_percent.is_some_and(|p| p >= 100.0);
+                let open = state.section_open.get(&section.name).copied().unwrap_or(!collapse);
+                let response = CollapsingHeader::new(header)
                     .id_source(Id::new(section.name.clone()).with(section.orig_index))
-                    .default_open(true)
+                    .open(Some(open))
                     .show(ui, |ui| {
-                        if section.kind == ObjSectionKind::Code && state.reverse_fn_order {
-                            for (symbol, symbol_diff) in
-                                section.symbols.iter().zip(&section_diff.symbols).rev()
-                            {
-                                if !symbol_matches_search(symbol, search_regex) {
-                                    continue;
-                                }
-                                ret = ret.or(symbol_ui(
-                                    ui,
-                                    symbol,
-                                    symbol_diff,
-                                    Some(section),
-                                    state,
-                                    appearance,
-                                    left,
-                                ));
+                        let other_section = other.and_then(|(other_obj, other_diff)| {
+                            let idx =
+                                other_obj.sections.iter().position(|s| s.name == section.name)?;
+                            Some((idx, &other_diff.sections[idx]))
+                        });
+                        // Rows of (own symbol, other side's symbol)
+                        let mut rows = match (state.symbol_alignment, other_section) {
+                            (SymbolAlignment::Target, Some((other_idx, other_diff))) if left => {
+                                section_diff.aligned_rows(other_diff, other_idx)
                             }
-                        } else {
-                            for (symbol, symbol_diff) in
-                                section.symbols.iter().zip(&section_diff.symbols)
-                            {
-                                if !symbol_matches_search(symbol, search_regex) {
-                                    continue;
-                                }
-                                ret = ret.or(symbol_ui(
-                                    ui,
-                                    symbol,
-                                    symbol_diff,
-                                    Some(section),
-                                    state,
-                                    appearance,
-                                    left,
-                                ));
+                            (SymbolAlignment::Base, Some((other_idx, other_diff))) if !left => {
+                                section_diff.aligned_rows(other_diff, other_idx)
                             }
+                            (
+                                SymbolAlignment::Target | SymbolAlignment::Base,
+                                Some((_, other_diff)),
+                            ) => other_diff
+                                .aligned_rows(section_diff, idx)
+                                .into_iter()
+                                .map(|(a, b)| (b, a))
+                                .collect(),
+                            _ => (0..section.symbols.len()).map(|i| (Some(i), None)).collect(),
+                        };
+                        if state.sort_mode != SymbolSortMode::Address {
+                            let other_section = other.zip(other_section).map(
+                                |((other_obj, _), (other_idx, other_diff))| {
+                                    (&other_obj.sections[other_idx], other_diff)
+                                },
+                            );
+                            sort_rows(
+                                &mut rows,
+                                (section, section_diff),
+                                other_section,
+                                left,
+                                state.sort_mode,
+                            );
+                        } else if section.kind == ObjSectionKind::Code
+                            && state.obj_reverse_fn_order.unwrap_or(state.reverse_fn_order)
+                        {
+                            rows.reverse();
+                        }
+                        let other_matches = |i: Option<usize>| {
+                            let other_symbol = other.zip(other_section).zip(i).map(
+                                |(((other_obj, _), (other_idx, _)), i)| {
+                                    &other_obj.sections[other_idx].symbols[i]
+                                },
+                            );
+                            other_symbol.is_some_and(matches)
+                        };
+                        for (own, other_symbol) in rows {
+                            let Some(symbol_idx) = own.filter(|&i| matches(&section.symbols[i]))
+                            else {
+                                // Leave a gap where the other side shows a symbol
+                                if other_matches(other_symbol) {
+                                    SelectableLabel::new(
+                                        false,
+                                        LayoutJob::simple_singleline(
+                                            " ".to_string(),
+                                            appearance.code_font.clone(),
+                                            Color32::PLACEHOLDER,
+                                        ),
+                                    )
+                                    .ui(ui);
+                                }
+                                continue;
+                            };
+                            let (symbol, symbol_diff) =
+                                (&section.symbols[symbol_idx], &section_diff.symbols[symbol_idx]);
+                            request_pending_diff(ui, &obj.1, symbol_diff, state, left);
+                            ret = ret.or(symbol_ui(
+                                ui,
+                                symbol,
+                                symbol_diff,
+                                obj.1.pair_confidence.get(&symbol_diff.symbol_ref).copied(),
+                                paired_symbol(symbol_diff, other),
+                                Some(section),
+                                state,
+                                appearance,
+                                left,
+                            ));
                         }
                     });
+                if response.header_response.clicked() {
+                    state.section_open.insert(section.name.clone(), !open);
+                }
             }
         });
     });
+    // A shorter list stops at its end, which shouldn't pull the other one back
+    let max_offset = (output.content_size.y - output.inner_rect.height()).max(0.0);
+    let offset = output.state.offset.y;
+    if state.link_scroll && (offset - state.scroll_offset.min(max_offset)).abs() > 0.5 {
+        // Scrolled by the user, so move the other list to match
+        state.scroll_offset = offset;
+        ui.ctx().request_repaint();
+    }
     ret
 }
 
-fn build_log_ui(ui: &mut Ui, status: &BuildStatus, appearance: &Appearance) {
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum LogLineKind {
+    Normal,
+    Warning,
+    Error,
+}
+
+/// Whether `word` occurs in `haystack` on its own, so "error" doesn't match "0 errors" or
+/// "-Werror".
+fn contains_word(haystack: &str, word: &str) -> bool {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    haystack.match_indices(word).any(|(idx, _)| {
+        !haystack[..idx].chars().next_back().is_some_and(is_word_char)
+            && !haystack[idx + word.len()..].chars().next().is_some_and(is_word_char)
+    })
+}
+
+/// Classifies a build log line by whether it looks like an error or warning.
+fn classify_log_line(line: &str, error_regex: Option<&Regex>) -> LogLineKind {
+    let lower = line.to_ascii_lowercase();
+    if error_regex.is_some_and(|r| r.is_match(line)) || contains_word(&lower, "error") {
+        LogLineKind::Error
+    } else if contains_word(&lower, "warning") {
+        LogLineKind::Warning
+    } else {
+        LogLineKind::Normal
+    }
+}
+
+fn build_log_ui(
+    ui: &mut Ui,
+    status: &BuildStatus,
+    error_regex: Option<&Regex>,
+    appearance: &Appearance,
+) {
     ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
         ui.horizontal(|ui| {
             if ui.button("Copy command").clicked() {
@@ -441,12 +1596,52 @@ fn build_log_ui(ui: &mut Ui, status: &BuildStatus, appearance: &Appearance) {
             ui.style_mut().wrap = Some(false);
 
             ui.label(&status.cmdline);
-            ui.colored_label(appearance.replace_color, &status.stdout);
-            ui.colored_label(appearance.delete_color, &status.stderr);
+            let mut job = LayoutJob::default();
+            for line in status.stdout.lines().chain(status.stderr.lines()) {
+                let color = match classify_log_line(line, error_regex) {
+                    LogLineKind::Normal => appearance.text_color,
+                    LogLineKind::Warning => appearance.replace_color,
+                    LogLineKind::Error => appearance.delete_color,
+                };
+                write_text(line, color, &mut job, appearance.code_font.clone());
+                write_text("\n", color, &mut job, appearance.code_font.clone());
+            }
+            ui.label(job);
         });
     });
 }
 
+fn project_progress_ui(ui: &mut Ui, progress: &ProjectProgress, appearance: &Appearance) {
+    let Some(match_percent) = progress.match_percent() else {
+        return;
+    };
+    let mut job = LayoutJob::default();
+    write_text("Project: ", appearance.text_color, &mut job, appearance.code_font.clone());
+    write_text(
+        &format!("{match_percent:.2}%"),
+        match_color_for_symbol(match_percent, appearance),
+        &mut job,
+        appearance.code_font.clone(),
+    );
+    if !progress.failed.is_empty() {
+        write_text(
+            &format!(" ({} failed units excluded)", progress.failed.len()),
+            appearance.replace_color,
+            &mut job,
+            appearance.code_font.clone(),
+        );
+    }
+    let response = ui.label(job);
+    if !progress.failed.is_empty() {
+        response.on_hover_ui(|ui| {
+            ui.label("Failed to build or load:");
+            for unit in &progress.failed {
+                ui.label(unit);
+            }
+        });
+    }
+}
+
 fn missing_obj_ui(ui: &mut Ui, appearance: &Appearance) {
     ui.scope(|ui| {
         ui.style_mut().override_text_style = Some(egui::TextStyle::Monospace);
@@ -456,8 +1651,37 @@ fn missing_obj_ui(ui: &mut Ui, appearance: &Appearance) {
     });
 }
 
+fn flag_filter_ui(ui: &mut Ui, filter: &mut SymbolFlagFilter) {
+    let label = if filter.is_empty() { "Flags" } else { "Flags (active)" };
+    ui.menu_button(label, |ui| {
+        egui::Grid::new("flag_filter").num_columns(4).show(ui, |ui| {
+            for (flag, name) in FILTER_FLAGS {
+                let mut value = filter.get(flag);
+                ui.label(name);
+                ui.selectable_value(&mut value, None, "Any");
+                ui.selectable_value(&mut value, Some(true), "Only");
+                ui.selectable_value(&mut value, Some(false), "Hide");
+                filter.set(flag, value);
+                ui.end_row();
+            }
+        });
+        if ui.add_enabled(!filter.is_empty(), egui::Button::new("Reset")).clicked() {
+            *filter = SymbolFlagFilter::default();
+        }
+    });
+}
+
 pub fn symbol_diff_ui(ui: &mut Ui, state: &mut DiffViewState, appearance: &Appearance) {
-    let DiffViewState { build, current_view, symbol_state, search, search_regex, .. } = state;
+    let DiffViewState {
+        build,
+        current_view,
+        symbol_state,
+        search,
+        search_regex,
+        log_error_regex,
+        project_progress,
+        ..
+    } = state;
     let Some(result) = build else {
         return;
     };
@@ -490,6 +1714,7 @@ pub fn symbol_diff_ui(ui: &mut Ui, state: &mut DiffViewState, appearance: &Appea
                         } else {
                             ui.colored_label(appearance.delete_color, "Fail");
                         }
+                        project_progress_ui(ui, project_progress, appearance);
                     });
 
                     if TextEdit::singleline(search).hint_text("Filter symbols").ui(ui).changed() {
@@ -503,6 +1728,22 @@ pub fn symbol_diff_ui(ui: &mut Ui, state: &mut DiffViewState, appearance: &Appea
                             *search_regex = None;
                         }
                     }
+                    flag_filter_ui(ui, &mut symbol_state.flag_filter);
+
+                    egui::ComboBox::from_id_source("symbol_sort_mode")
+                        .selected_text(format!(
+                            "Sort: {}",
+                            symbol_state.sort_mode.get_message().unwrap()
+                        ))
+                        .show_ui(ui, |ui| {
+                            for &mode in SymbolSortMode::VARIANTS {
+                                ui.selectable_value(
+                                    &mut symbol_state.sort_mode,
+                                    mode,
+                                    mode.get_message().unwrap(),
+                                );
+                            }
+                        });
                 },
             );
 
@@ -529,9 +1770,86 @@ pub fn symbol_diff_ui(ui: &mut Ui, state: &mut DiffViewState, appearance: &Appea
                         }
                     });
 
-                    if ui.add_enabled(!state.build_running, egui::Button::new("Build")).clicked() {
-                        state.queue_build = true;
-                    }
+                    ui.horizontal(|ui| {
+                        if ui
+                            .add_enabled(!state.build_running, egui::Button::new("Build"))
+                            .clicked()
+                        {
+                            state.queue_build = true;
+                        }
+                        let selected = state
+                            .base_snapshot
+                            .as_deref()
+                            .or(state.base_object.as_deref())
+                            .unwrap_or("Base object");
+                        egui::ComboBox::from_id_source("base_snapshot")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                if ui
+                                    .selectable_label(
+                                        state.base_snapshot.is_none()
+                                            && state.base_object.is_none(),
+                                        "Base object",
+                                    )
+                                    .clicked()
+                                {
+                                    state.queue_select_snapshot = Some(None);
+                                }
+                                for label in &state.snapshots {
+                                    if ui
+                                        .selectable_label(
+                                            state.base_snapshot.as_ref() == Some(label),
+                                            label,
+                                        )
+                                        .clicked()
+                                    {
+                                        state.queue_select_snapshot = Some(Some(label.clone()));
+                                    }
+                                }
+                                if !state.base_objects.is_empty() {
+                                    ui.separator();
+                                    ui.menu_button("Other object's base", |ui| {
+                                        egui::ScrollArea::vertical().max_height(300.0).show(
+                                            ui,
+                                            |ui| {
+                                                for name in &state.base_objects {
+                                                    if ui
+                                                        .selectable_label(
+                                                            state.base_object.as_ref()
+                                                                == Some(name),
+                                                            name,
+                                                        )
+                                                        .clicked()
+                                                    {
+                                                        state.queue_select_base_object =
+                                                            Some(Some(name.clone()));
+                                                        ui.close_menu();
+                                                    }
+                                                }
+                                            },
+                                        );
+                                    });
+                                }
+                            })
+                            .response
+                            .on_hover_text("Object to diff the target against.");
+                    });
+                    ui.horizontal(|ui| {
+                        TextEdit::singleline(&mut state.snapshot_label)
+                            .hint_text("Snapshot label")
+                            .desired_width(150.0)
+                            .ui(ui);
+                        if ui
+                            .add_enabled(
+                                !state.snapshot_label.trim().is_empty(),
+                                egui::Button::new("Snapshot target"),
+                            )
+                            .on_hover_text("Saves a copy of the target object under this label.")
+                            .clicked()
+                        {
+                            state.queue_snapshot = true;
+                        }
+                    });
                 },
             );
         },
@@ -550,6 +1868,7 @@ pub fn symbol_diff_ui(ui: &mut Ui, state: &mut DiffViewState, appearance: &Appea
                                 ret = ret.or(symbol_list_ui(
                                     ui,
                                     obj,
+                                    result.second_obj.as_ref(),
                                     symbol_state,
                                     search_regex.as_ref(),
                                     appearance,
@@ -559,7 +1878,12 @@ pub fn symbol_diff_ui(ui: &mut Ui, state: &mut DiffViewState, appearance: &Appea
                                 missing_obj_ui(ui, appearance);
                             }
                         } else {
-                            build_log_ui(ui, &result.first_status, appearance);
+                            build_log_ui(
+                                ui,
+                                &result.first_status,
+                                log_error_regex.as_ref(),
+                                appearance,
+                            );
                         }
                     });
                 });
@@ -570,6 +1894,7 @@ pub fn symbol_diff_ui(ui: &mut Ui, state: &mut DiffViewState, appearance: &Appea
                                 ret = ret.or(symbol_list_ui(
                                     ui,
                                     obj,
+                                    result.first_obj.as_ref(),
                                     symbol_state,
                                     search_regex.as_ref(),
                                     appearance,
@@ -579,7 +1904,12 @@ pub fn symbol_diff_ui(ui: &mut Ui, state: &mut DiffViewState, appearance: &Appea
                                 missing_obj_ui(ui, appearance);
                             }
                         } else {
-                            build_log_ui(ui, &result.second_status, appearance);
+                            build_log_ui(
+                                ui,
+                                &result.second_status,
+                                log_error_regex.as_ref(),
+                                appearance,
+                            );
                         }
                     });
                 });
@@ -591,3 +1921,30 @@ pub fn symbol_diff_ui(ui: &mut Ui, state: &mut DiffViewState, appearance: &Appea
         *current_view = view;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_log_lines() {
+        let classify = |line| classify_log_line(line, None);
+        assert_eq!(classify("src/main.c:12: error: expected ';'"), LogLineKind::Error);
+        assert_eq!(classify("Error: unknown opcode"), LogLineKind::Error);
+        assert_eq!(classify("src/main.c:3: warning: unused variable"), LogLineKind::Warning);
+        assert_eq!(classify("mwcceppc -Werror -c src/main.c"), LogLineKind::Normal);
+        assert_eq!(classify("Build finished: 0 errors, 0 warnings"), LogLineKind::Normal);
+        assert_eq!(classify("error_count = 0"), LogLineKind::Normal);
+        assert_eq!(classify("ninja: no work to do."), LogLineKind::Normal);
+    }
+
+    #[test]
+    fn classify_log_lines_with_error_regex() {
+        let regex = Regex::new(r"^FAILED:").unwrap();
+        let classify = |line| classify_log_line(line, Some(&regex));
+        assert_eq!(classify("FAILED: build/main.o"), LogLineKind::Error);
+        assert_eq!(classify("src/main.c:12: error: expected ';'"), LogLineKind::Error);
+        assert_eq!(classify("src/main.c:3: warning: unused variable"), LogLineKind::Warning);
+        assert_eq!(classify("[1/2] CC build/main.o"), LogLineKind::Normal);
+    }
+}